@@ -0,0 +1,149 @@
+// backfill throughput against a synthetic midgard that answers after a fixed latency,
+// `cargo bench --bench ingestion` reports stored documents per second per store and pipeline setting
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::{collections::HashMap, fs, path::PathBuf, sync::Arc, thread, time::{Duration, Instant}};
+
+use actix_web::{rt::System, web, App, HttpResponse, HttpServer};
+use common::{app_state_with_config, HOUR, T0};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::{json, Value};
+use tokenmetrics::{
+    config::Config,
+    services::backfill_service::backfill,
+    stores::{memory_store::MemoryStore, metrics_store::MetricsStore, sqlite_store::SqliteStore},
+};
+
+// a midgard round trip from a nearby host
+const LATENCY: Duration = Duration::from_millis(25);
+const PAGE_SIZE: u32 = 400;
+const PAGES: i64 = 4;
+const EARNINGS_POOLS: usize = 10;
+const DEPTH_POOLS: [&str; 4] = ["BTC.BTC", "ETH.ETH", "BNB.BNB", "DOGE.DOGE"];
+// (concurrency, prefetch_pages)
+const SETTINGS: [(usize, usize); 2] = [(1, 1), (4, 2)];
+
+// the first interval and the meta of a recorded page
+struct Template {
+    interval: Value,
+    meta: Value,
+}
+
+fn template(name: &str) -> Template {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/midgard").join(format!("{}.json", name));
+    let page: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    Template { interval: page["intervals"][0].clone(), meta: page["meta"].clone() }
+}
+
+// `count` hour intervals from `from` like midgard answers, empty past the backfilled range
+fn page(template: &Template, query: &HashMap<String, String>) -> HttpResponse {
+    let from: i64 = query.get("from").and_then(|from| from.parse().ok()).unwrap_or(T0);
+    let count: i64 = query.get("count").and_then(|count| count.parse().ok()).unwrap_or(PAGE_SIZE as i64);
+    let end = T0 + PAGES * PAGE_SIZE as i64 * HOUR;
+    let intervals: Vec<Value> = (0..count)
+        .map(|index| from + index * HOUR)
+        .take_while(|start| *start < end)
+        .map(|start| {
+            let mut interval = template.interval.clone();
+            interval["startTime"] = json!(start.to_string());
+            interval["endTime"] = json!((start + HOUR).to_string());
+            interval
+        })
+        .collect();
+    let mut meta = template.meta.clone();
+    meta["endTime"] = json!((from + intervals.len() as i64 * HOUR).to_string());
+    HttpResponse::Ok().json(json!({ "intervals": intervals, "meta": meta }))
+}
+
+async fn depths(templates: web::Data<HashMap<&'static str, Template>>, query: web::Query<HashMap<String, String>>) -> HttpResponse {
+    actix_web::rt::time::sleep(LATENCY).await;
+    page(&templates["depths"], &query)
+}
+
+async fn earnings(templates: web::Data<HashMap<&'static str, Template>>, query: web::Query<HashMap<String, String>>) -> HttpResponse {
+    actix_web::rt::time::sleep(LATENCY).await;
+    page(&templates["earnings"], &query)
+}
+
+// on its own thread and actix system, the backfills run on the criterion runtime
+fn start_midgard() -> String {
+    let mut earnings_template = template("earnings");
+    let pool = earnings_template.interval["pools"][0].clone();
+    earnings_template.interval["pools"] = (0..EARNINGS_POOLS)
+        .map(|index| {
+            let mut pool = pool.clone();
+            pool["pool"] = json!(format!("POOL{}.ASSET", index));
+            pool
+        })
+        .collect();
+    let templates = web::Data::new(HashMap::from([("depths", template("depths_BTC.BTC")), ("earnings", earnings_template)]));
+    let (address, received) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+        System::new().block_on(async move {
+            let server = HttpServer::new(move || {
+                App::new()
+                    .app_data(templates.clone())
+                    .route("/v2/history/depths/{pool}", web::get().to(depths))
+                    .route("/v2/history/earnings", web::get().to(earnings))
+            })
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+            address.send(server.addrs()[0]).unwrap();
+            server.run().await
+        })
+    });
+    format!("http://{}", received.recv().unwrap())
+}
+
+async fn backfill_once(base_url: &str, collection: &str, store: &str, (concurrency, prefetch_pages): (usize, usize)) -> Duration {
+    let mut config = Config::default();
+    config.midgard.base_url = base_url.to_string();
+    config.midgard.page_size = PAGE_SIZE;
+    config.pools = DEPTH_POOLS.iter().map(|pool| pool.to_string()).collect();
+    config.ingestion.concurrency = concurrency;
+    config.ingestion.prefetch_pages = prefetch_pages;
+    let store: Arc<dyn MetricsStore> = match store {
+        "sqlite" => Arc::new(SqliteStore::open("sqlite://:memory:").unwrap()),
+        _ => Arc::new(MemoryStore::default()),
+    };
+    let state = app_state_with_config(config, store).await;
+    let started = Instant::now();
+    let reports = backfill(&state.db, &state.config, collection, None, T0, T0 + PAGES * PAGE_SIZE as i64 * HOUR).await.unwrap();
+    let elapsed = started.elapsed();
+    assert!(reports.iter().all(|report| report.failed_pages == 0), "{:?}", reports);
+    elapsed
+}
+
+fn ingestion(c: &mut Criterion) {
+    let base_url = start_midgard();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let intervals = (PAGES * PAGE_SIZE as i64) as u64;
+    // earnings store a summary and every pool per interval
+    for (collection, documents) in [("earnings", intervals * (EARNINGS_POOLS as u64 + 1)), ("depths", intervals * DEPTH_POOLS.len() as u64)] {
+        let mut group = c.benchmark_group(format!("backfill_{}", collection));
+        group.throughput(Throughput::Elements(documents));
+        group.sample_size(10);
+        for store in ["memory", "sqlite"] {
+            for setting in SETTINGS {
+                let id = BenchmarkId::new(store, format!("concurrency {} prefetch {}", setting.0, setting.1));
+                group.bench_function(id, |b| {
+                    b.to_async(&runtime).iter_custom(|iterations| {
+                        let base_url = base_url.clone();
+                        async move {
+                            let mut total = Duration::ZERO;
+                            for _ in 0..iterations {
+                                total += backfill_once(&base_url, collection, store, setting).await;
+                            }
+                            total
+                        }
+                    })
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, ingestion);
+criterion_main!(benches);
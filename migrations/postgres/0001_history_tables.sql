@@ -0,0 +1,120 @@
+-- history tables of the postgres store, columns are the model fields, id is the record ObjectId in hex
+-- seq keeps the insertion order the interval buckets take their last record from
+
+CREATE TABLE depth_history (
+    seq BIGSERIAL,
+    id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    asset_depth DOUBLE PRECISION NOT NULL,
+    asset_price DOUBLE PRECISION NOT NULL,
+    asset_price_usd DOUBLE PRECISION NOT NULL,
+    end_time BIGINT NOT NULL,
+    liquidity_units DOUBLE PRECISION NOT NULL,
+    luvi DOUBLE PRECISION NOT NULL,
+    members_count BIGINT NOT NULL,
+    rune_depth DOUBLE PRECISION NOT NULL,
+    start_time BIGINT NOT NULL,
+    synth_supply DOUBLE PRECISION NOT NULL,
+    synth_units DOUBLE PRECISION NOT NULL,
+    units DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (end_time, seq)
+);
+CREATE INDEX depth_history_pool_end_time ON depth_history (pool, end_time);
+
+CREATE TABLE swap_history (
+    seq BIGSERIAL,
+    id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    average_slip DOUBLE PRECISION NOT NULL,
+    end_time BIGINT NOT NULL,
+    from_trade_average_slip DOUBLE PRECISION NOT NULL,
+    from_trade_count BIGINT NOT NULL,
+    from_trade_fees DOUBLE PRECISION NOT NULL,
+    from_trade_volume DOUBLE PRECISION NOT NULL,
+    from_trade_volume_usd DOUBLE PRECISION NOT NULL,
+    rune_price_usd DOUBLE PRECISION NOT NULL,
+    start_time BIGINT NOT NULL,
+    synth_mint_average_slip DOUBLE PRECISION NOT NULL,
+    synth_mint_count BIGINT NOT NULL,
+    synth_mint_fees DOUBLE PRECISION NOT NULL,
+    synth_mint_volume DOUBLE PRECISION NOT NULL,
+    synth_mint_volume_usd DOUBLE PRECISION NOT NULL,
+    synth_redeem_average_slip DOUBLE PRECISION NOT NULL,
+    synth_redeem_count BIGINT NOT NULL,
+    synth_redeem_fees DOUBLE PRECISION NOT NULL,
+    synth_redeem_volume DOUBLE PRECISION NOT NULL,
+    synth_redeem_volume_usd DOUBLE PRECISION NOT NULL,
+    to_asset_average_slip DOUBLE PRECISION NOT NULL,
+    to_asset_count BIGINT NOT NULL,
+    to_asset_fees DOUBLE PRECISION NOT NULL,
+    to_asset_volume DOUBLE PRECISION NOT NULL,
+    to_asset_volume_usd DOUBLE PRECISION NOT NULL,
+    to_rune_average_slip DOUBLE PRECISION NOT NULL,
+    to_rune_count BIGINT NOT NULL,
+    to_rune_fees DOUBLE PRECISION NOT NULL,
+    to_rune_volume DOUBLE PRECISION NOT NULL,
+    to_rune_volume_usd DOUBLE PRECISION NOT NULL,
+    to_trade_average_slip DOUBLE PRECISION NOT NULL,
+    to_trade_count BIGINT NOT NULL,
+    to_trade_fees DOUBLE PRECISION NOT NULL,
+    to_trade_volume DOUBLE PRECISION NOT NULL,
+    to_trade_volume_usd DOUBLE PRECISION NOT NULL,
+    total_count BIGINT NOT NULL,
+    total_fees DOUBLE PRECISION NOT NULL,
+    total_volume DOUBLE PRECISION NOT NULL,
+    total_volume_usd DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (end_time, seq)
+);
+CREATE INDEX swap_history_pool_end_time ON swap_history (pool, end_time);
+
+CREATE TABLE earnings_summary (
+    id TEXT PRIMARY KEY,
+    avg_node_count DOUBLE PRECISION NOT NULL,
+    block_rewards DOUBLE PRECISION NOT NULL,
+    bonding_earnings DOUBLE PRECISION NOT NULL,
+    earnings BIGINT NOT NULL,
+    end_time BIGINT NOT NULL,
+    liquidity_earnings DOUBLE PRECISION NOT NULL,
+    liquidity_fees BIGINT NOT NULL,
+    start_time BIGINT NOT NULL,
+    rune_price_usd DOUBLE PRECISION NOT NULL
+);
+CREATE INDEX earnings_summary_end_time ON earnings_summary (end_time);
+
+CREATE TABLE earnings (
+    seq BIGSERIAL,
+    id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    asset_liquidity_fees DOUBLE PRECISION NOT NULL,
+    earning BIGINT NOT NULL,
+    rewards DOUBLE PRECISION NOT NULL,
+    rune_liquidity_fees DOUBLE PRECISION NOT NULL,
+    saver_earning DOUBLE PRECISION NOT NULL,
+    total_liquidity_fees_rune DOUBLE PRECISION NOT NULL,
+    start_time BIGINT NOT NULL,
+    end_time BIGINT NOT NULL,
+    earnings_summary TEXT NOT NULL,
+    PRIMARY KEY (end_time, seq)
+);
+CREATE INDEX earnings_pool_end_time ON earnings (pool, end_time);
+
+CREATE TABLE rune_pool_history (
+    seq BIGSERIAL,
+    id TEXT NOT NULL,
+    count DOUBLE PRECISION NOT NULL,
+    end_time BIGINT NOT NULL,
+    start_time BIGINT NOT NULL,
+    units DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (end_time, seq)
+);
+
+-- with the timescaledb extension installed the history tables become hypertables chunked by 30 days of end_time
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        PERFORM create_hypertable('depth_history', 'end_time', chunk_time_interval => 2592000, if_not_exists => TRUE);
+        PERFORM create_hypertable('swap_history', 'end_time', chunk_time_interval => 2592000, if_not_exists => TRUE);
+        PERFORM create_hypertable('earnings', 'end_time', chunk_time_interval => 2592000, if_not_exists => TRUE);
+        PERFORM create_hypertable('rune_pool_history', 'end_time', chunk_time_interval => 2592000, if_not_exists => TRUE);
+    END IF;
+END $$;
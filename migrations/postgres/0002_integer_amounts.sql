@@ -0,0 +1,41 @@
+-- 1e8 scaled amounts become exact integers, the stored doubles are rounded
+
+ALTER TABLE depth_history
+    ALTER COLUMN asset_depth TYPE BIGINT USING round(asset_depth)::BIGINT,
+    ALTER COLUMN liquidity_units TYPE BIGINT USING round(liquidity_units)::BIGINT,
+    ALTER COLUMN rune_depth TYPE BIGINT USING round(rune_depth)::BIGINT,
+    ALTER COLUMN synth_supply TYPE BIGINT USING round(synth_supply)::BIGINT,
+    ALTER COLUMN synth_units TYPE BIGINT USING round(synth_units)::BIGINT,
+    ALTER COLUMN units TYPE BIGINT USING round(units)::BIGINT;
+
+ALTER TABLE swap_history
+    ALTER COLUMN from_trade_fees TYPE BIGINT USING round(from_trade_fees)::BIGINT,
+    ALTER COLUMN from_trade_volume TYPE BIGINT USING round(from_trade_volume)::BIGINT,
+    ALTER COLUMN synth_mint_fees TYPE BIGINT USING round(synth_mint_fees)::BIGINT,
+    ALTER COLUMN synth_mint_volume TYPE BIGINT USING round(synth_mint_volume)::BIGINT,
+    ALTER COLUMN synth_redeem_fees TYPE BIGINT USING round(synth_redeem_fees)::BIGINT,
+    ALTER COLUMN synth_redeem_volume TYPE BIGINT USING round(synth_redeem_volume)::BIGINT,
+    ALTER COLUMN to_asset_fees TYPE BIGINT USING round(to_asset_fees)::BIGINT,
+    ALTER COLUMN to_asset_volume TYPE BIGINT USING round(to_asset_volume)::BIGINT,
+    ALTER COLUMN to_rune_fees TYPE BIGINT USING round(to_rune_fees)::BIGINT,
+    ALTER COLUMN to_rune_volume TYPE BIGINT USING round(to_rune_volume)::BIGINT,
+    ALTER COLUMN to_trade_fees TYPE BIGINT USING round(to_trade_fees)::BIGINT,
+    ALTER COLUMN to_trade_volume TYPE BIGINT USING round(to_trade_volume)::BIGINT,
+    ALTER COLUMN total_fees TYPE BIGINT USING round(total_fees)::BIGINT,
+    ALTER COLUMN total_volume TYPE BIGINT USING round(total_volume)::BIGINT;
+
+ALTER TABLE earnings_summary
+    ALTER COLUMN block_rewards TYPE BIGINT USING round(block_rewards)::BIGINT,
+    ALTER COLUMN bonding_earnings TYPE BIGINT USING round(bonding_earnings)::BIGINT,
+    ALTER COLUMN liquidity_earnings TYPE BIGINT USING round(liquidity_earnings)::BIGINT;
+
+ALTER TABLE earnings
+    ALTER COLUMN asset_liquidity_fees TYPE BIGINT USING round(asset_liquidity_fees)::BIGINT,
+    ALTER COLUMN rewards TYPE BIGINT USING round(rewards)::BIGINT,
+    ALTER COLUMN rune_liquidity_fees TYPE BIGINT USING round(rune_liquidity_fees)::BIGINT,
+    ALTER COLUMN saver_earning TYPE BIGINT USING round(saver_earning)::BIGINT,
+    ALTER COLUMN total_liquidity_fees_rune TYPE BIGINT USING round(total_liquidity_fees_rune)::BIGINT;
+
+ALTER TABLE rune_pool_history
+    ALTER COLUMN count TYPE BIGINT USING round(count)::BIGINT,
+    ALTER COLUMN units TYPE BIGINT USING round(units)::BIGINT;
@@ -0,0 +1,12 @@
+-- midgard intervals the ingestion rejected, the interval as received and the reasons are json text
+
+CREATE TABLE quarantine (
+    seq BIGSERIAL PRIMARY KEY,
+    id TEXT NOT NULL UNIQUE,
+    collection TEXT NOT NULL,
+    pool TEXT,
+    interval_json TEXT NOT NULL,
+    reasons_json TEXT NOT NULL,
+    quarantined_at BIGINT NOT NULL
+);
+CREATE INDEX quarantine_collection ON quarantine (collection, seq);
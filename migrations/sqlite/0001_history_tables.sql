@@ -0,0 +1,106 @@
+-- history tables of the sqlite store, the same layout as migrations/postgres, columns are the model fields, id is the record ObjectId in hex
+-- seq keeps the insertion order the interval buckets take their last record from
+
+CREATE TABLE depth_history (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    asset_depth REAL NOT NULL,
+    asset_price REAL NOT NULL,
+    asset_price_usd REAL NOT NULL,
+    end_time INTEGER NOT NULL,
+    liquidity_units REAL NOT NULL,
+    luvi REAL NOT NULL,
+    members_count INTEGER NOT NULL,
+    rune_depth REAL NOT NULL,
+    start_time INTEGER NOT NULL,
+    synth_supply REAL NOT NULL,
+    synth_units REAL NOT NULL,
+    units REAL NOT NULL
+);
+CREATE INDEX depth_history_pool_end_time ON depth_history (pool, end_time);
+
+CREATE TABLE swap_history (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    average_slip REAL NOT NULL,
+    end_time INTEGER NOT NULL,
+    from_trade_average_slip REAL NOT NULL,
+    from_trade_count INTEGER NOT NULL,
+    from_trade_fees REAL NOT NULL,
+    from_trade_volume REAL NOT NULL,
+    from_trade_volume_usd REAL NOT NULL,
+    rune_price_usd REAL NOT NULL,
+    start_time INTEGER NOT NULL,
+    synth_mint_average_slip REAL NOT NULL,
+    synth_mint_count INTEGER NOT NULL,
+    synth_mint_fees REAL NOT NULL,
+    synth_mint_volume REAL NOT NULL,
+    synth_mint_volume_usd REAL NOT NULL,
+    synth_redeem_average_slip REAL NOT NULL,
+    synth_redeem_count INTEGER NOT NULL,
+    synth_redeem_fees REAL NOT NULL,
+    synth_redeem_volume REAL NOT NULL,
+    synth_redeem_volume_usd REAL NOT NULL,
+    to_asset_average_slip REAL NOT NULL,
+    to_asset_count INTEGER NOT NULL,
+    to_asset_fees REAL NOT NULL,
+    to_asset_volume REAL NOT NULL,
+    to_asset_volume_usd REAL NOT NULL,
+    to_rune_average_slip REAL NOT NULL,
+    to_rune_count INTEGER NOT NULL,
+    to_rune_fees REAL NOT NULL,
+    to_rune_volume REAL NOT NULL,
+    to_rune_volume_usd REAL NOT NULL,
+    to_trade_average_slip REAL NOT NULL,
+    to_trade_count INTEGER NOT NULL,
+    to_trade_fees REAL NOT NULL,
+    to_trade_volume REAL NOT NULL,
+    to_trade_volume_usd REAL NOT NULL,
+    total_count INTEGER NOT NULL,
+    total_fees REAL NOT NULL,
+    total_volume REAL NOT NULL,
+    total_volume_usd REAL NOT NULL
+);
+CREATE INDEX swap_history_pool_end_time ON swap_history (pool, end_time);
+
+CREATE TABLE earnings_summary (
+    id TEXT PRIMARY KEY,
+    avg_node_count REAL NOT NULL,
+    block_rewards REAL NOT NULL,
+    bonding_earnings REAL NOT NULL,
+    earnings INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    liquidity_earnings REAL NOT NULL,
+    liquidity_fees INTEGER NOT NULL,
+    start_time INTEGER NOT NULL,
+    rune_price_usd REAL NOT NULL
+);
+CREATE INDEX earnings_summary_end_time ON earnings_summary (end_time);
+
+CREATE TABLE earnings (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    asset_liquidity_fees REAL NOT NULL,
+    earning INTEGER NOT NULL,
+    rewards REAL NOT NULL,
+    rune_liquidity_fees REAL NOT NULL,
+    saver_earning REAL NOT NULL,
+    total_liquidity_fees_rune REAL NOT NULL,
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    earnings_summary TEXT NOT NULL
+);
+CREATE INDEX earnings_pool_end_time ON earnings (pool, end_time);
+
+CREATE TABLE rune_pool_history (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    count REAL NOT NULL,
+    end_time INTEGER NOT NULL,
+    start_time INTEGER NOT NULL,
+    units REAL NOT NULL
+);
+CREATE INDEX rune_pool_history_end_time ON rune_pool_history (end_time);
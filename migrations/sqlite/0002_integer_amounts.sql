@@ -0,0 +1,126 @@
+-- 1e8 scaled amounts become exact integers, sqlite cannot change a column type so every table is rebuilt
+-- with INTEGER amounts and the stored doubles are rounded
+
+CREATE TABLE depth_history_integer_amounts (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    asset_depth INTEGER NOT NULL,
+    asset_price REAL NOT NULL,
+    asset_price_usd REAL NOT NULL,
+    end_time INTEGER NOT NULL,
+    liquidity_units INTEGER NOT NULL,
+    luvi REAL NOT NULL,
+    members_count INTEGER NOT NULL,
+    rune_depth INTEGER NOT NULL,
+    start_time INTEGER NOT NULL,
+    synth_supply INTEGER NOT NULL,
+    synth_units INTEGER NOT NULL,
+    units INTEGER NOT NULL
+);
+INSERT INTO depth_history_integer_amounts (seq, id, pool, asset_depth, asset_price, asset_price_usd, end_time, liquidity_units, luvi, members_count, rune_depth, start_time, synth_supply, synth_units, units)
+SELECT seq, id, pool, CAST(ROUND(asset_depth) AS INTEGER), asset_price, asset_price_usd, end_time, CAST(ROUND(liquidity_units) AS INTEGER), luvi, members_count, CAST(ROUND(rune_depth) AS INTEGER), start_time, CAST(ROUND(synth_supply) AS INTEGER), CAST(ROUND(synth_units) AS INTEGER), CAST(ROUND(units) AS INTEGER) FROM depth_history;
+DROP TABLE depth_history;
+ALTER TABLE depth_history_integer_amounts RENAME TO depth_history;
+CREATE INDEX depth_history_pool_end_time ON depth_history (pool, end_time);
+
+CREATE TABLE swap_history_integer_amounts (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    average_slip REAL NOT NULL,
+    end_time INTEGER NOT NULL,
+    from_trade_average_slip REAL NOT NULL,
+    from_trade_count INTEGER NOT NULL,
+    from_trade_fees INTEGER NOT NULL,
+    from_trade_volume INTEGER NOT NULL,
+    from_trade_volume_usd REAL NOT NULL,
+    rune_price_usd REAL NOT NULL,
+    start_time INTEGER NOT NULL,
+    synth_mint_average_slip REAL NOT NULL,
+    synth_mint_count INTEGER NOT NULL,
+    synth_mint_fees INTEGER NOT NULL,
+    synth_mint_volume INTEGER NOT NULL,
+    synth_mint_volume_usd REAL NOT NULL,
+    synth_redeem_average_slip REAL NOT NULL,
+    synth_redeem_count INTEGER NOT NULL,
+    synth_redeem_fees INTEGER NOT NULL,
+    synth_redeem_volume INTEGER NOT NULL,
+    synth_redeem_volume_usd REAL NOT NULL,
+    to_asset_average_slip REAL NOT NULL,
+    to_asset_count INTEGER NOT NULL,
+    to_asset_fees INTEGER NOT NULL,
+    to_asset_volume INTEGER NOT NULL,
+    to_asset_volume_usd REAL NOT NULL,
+    to_rune_average_slip REAL NOT NULL,
+    to_rune_count INTEGER NOT NULL,
+    to_rune_fees INTEGER NOT NULL,
+    to_rune_volume INTEGER NOT NULL,
+    to_rune_volume_usd REAL NOT NULL,
+    to_trade_average_slip REAL NOT NULL,
+    to_trade_count INTEGER NOT NULL,
+    to_trade_fees INTEGER NOT NULL,
+    to_trade_volume INTEGER NOT NULL,
+    to_trade_volume_usd REAL NOT NULL,
+    total_count INTEGER NOT NULL,
+    total_fees INTEGER NOT NULL,
+    total_volume INTEGER NOT NULL,
+    total_volume_usd REAL NOT NULL
+);
+INSERT INTO swap_history_integer_amounts (seq, id, pool, average_slip, end_time, from_trade_average_slip, from_trade_count, from_trade_fees, from_trade_volume, from_trade_volume_usd, rune_price_usd, start_time, synth_mint_average_slip, synth_mint_count, synth_mint_fees, synth_mint_volume, synth_mint_volume_usd, synth_redeem_average_slip, synth_redeem_count, synth_redeem_fees, synth_redeem_volume, synth_redeem_volume_usd, to_asset_average_slip, to_asset_count, to_asset_fees, to_asset_volume, to_asset_volume_usd, to_rune_average_slip, to_rune_count, to_rune_fees, to_rune_volume, to_rune_volume_usd, to_trade_average_slip, to_trade_count, to_trade_fees, to_trade_volume, to_trade_volume_usd, total_count, total_fees, total_volume, total_volume_usd)
+SELECT seq, id, pool, average_slip, end_time, from_trade_average_slip, from_trade_count, CAST(ROUND(from_trade_fees) AS INTEGER), CAST(ROUND(from_trade_volume) AS INTEGER), from_trade_volume_usd, rune_price_usd, start_time, synth_mint_average_slip, synth_mint_count, CAST(ROUND(synth_mint_fees) AS INTEGER), CAST(ROUND(synth_mint_volume) AS INTEGER), synth_mint_volume_usd, synth_redeem_average_slip, synth_redeem_count, CAST(ROUND(synth_redeem_fees) AS INTEGER), CAST(ROUND(synth_redeem_volume) AS INTEGER), synth_redeem_volume_usd, to_asset_average_slip, to_asset_count, CAST(ROUND(to_asset_fees) AS INTEGER), CAST(ROUND(to_asset_volume) AS INTEGER), to_asset_volume_usd, to_rune_average_slip, to_rune_count, CAST(ROUND(to_rune_fees) AS INTEGER), CAST(ROUND(to_rune_volume) AS INTEGER), to_rune_volume_usd, to_trade_average_slip, to_trade_count, CAST(ROUND(to_trade_fees) AS INTEGER), CAST(ROUND(to_trade_volume) AS INTEGER), to_trade_volume_usd, total_count, CAST(ROUND(total_fees) AS INTEGER), CAST(ROUND(total_volume) AS INTEGER), total_volume_usd FROM swap_history;
+DROP TABLE swap_history;
+ALTER TABLE swap_history_integer_amounts RENAME TO swap_history;
+CREATE INDEX swap_history_pool_end_time ON swap_history (pool, end_time);
+
+CREATE TABLE earnings_summary_integer_amounts (
+    id TEXT PRIMARY KEY,
+    avg_node_count REAL NOT NULL,
+    block_rewards INTEGER NOT NULL,
+    bonding_earnings INTEGER NOT NULL,
+    earnings INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    liquidity_earnings INTEGER NOT NULL,
+    liquidity_fees INTEGER NOT NULL,
+    start_time INTEGER NOT NULL,
+    rune_price_usd REAL NOT NULL
+);
+INSERT INTO earnings_summary_integer_amounts (id, avg_node_count, block_rewards, bonding_earnings, earnings, end_time, liquidity_earnings, liquidity_fees, start_time, rune_price_usd)
+SELECT id, avg_node_count, CAST(ROUND(block_rewards) AS INTEGER), CAST(ROUND(bonding_earnings) AS INTEGER), earnings, end_time, CAST(ROUND(liquidity_earnings) AS INTEGER), liquidity_fees, start_time, rune_price_usd FROM earnings_summary;
+DROP TABLE earnings_summary;
+ALTER TABLE earnings_summary_integer_amounts RENAME TO earnings_summary;
+CREATE INDEX earnings_summary_end_time ON earnings_summary (end_time);
+
+CREATE TABLE earnings_integer_amounts (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    asset_liquidity_fees INTEGER NOT NULL,
+    earning INTEGER NOT NULL,
+    rewards INTEGER NOT NULL,
+    rune_liquidity_fees INTEGER NOT NULL,
+    saver_earning INTEGER NOT NULL,
+    total_liquidity_fees_rune INTEGER NOT NULL,
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    earnings_summary TEXT NOT NULL
+);
+INSERT INTO earnings_integer_amounts (seq, id, pool, asset_liquidity_fees, earning, rewards, rune_liquidity_fees, saver_earning, total_liquidity_fees_rune, start_time, end_time, earnings_summary)
+SELECT seq, id, pool, CAST(ROUND(asset_liquidity_fees) AS INTEGER), earning, CAST(ROUND(rewards) AS INTEGER), CAST(ROUND(rune_liquidity_fees) AS INTEGER), CAST(ROUND(saver_earning) AS INTEGER), CAST(ROUND(total_liquidity_fees_rune) AS INTEGER), start_time, end_time, earnings_summary FROM earnings;
+DROP TABLE earnings;
+ALTER TABLE earnings_integer_amounts RENAME TO earnings;
+CREATE INDEX earnings_pool_end_time ON earnings (pool, end_time);
+
+CREATE TABLE rune_pool_history_integer_amounts (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    start_time INTEGER NOT NULL,
+    units INTEGER NOT NULL
+);
+INSERT INTO rune_pool_history_integer_amounts (seq, id, count, end_time, start_time, units)
+SELECT seq, id, CAST(ROUND(count) AS INTEGER), end_time, start_time, CAST(ROUND(units) AS INTEGER) FROM rune_pool_history;
+DROP TABLE rune_pool_history;
+ALTER TABLE rune_pool_history_integer_amounts RENAME TO rune_pool_history;
+CREATE INDEX rune_pool_history_end_time ON rune_pool_history (end_time);
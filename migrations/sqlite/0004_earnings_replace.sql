@@ -0,0 +1,4 @@
+-- an earnings interval stored again replaces the summary with the same start_time and end_time and its pools
+
+CREATE INDEX earnings_summary_start_end_time ON earnings_summary (start_time, end_time);
+CREATE INDEX earnings_earnings_summary ON earnings (earnings_summary);
@@ -1,4 +1,15 @@
-use utoipa::OpenApi;
+use utoipa::{openapi::security::{ApiKey, ApiKeyValue, SecurityScheme}, Modify, OpenApi};
+
+// X-API-Key header used by the /admin, /alerts and /usage routes
+pub struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme("api_key", SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))));
+        }
+    }
+}
 
 #[derive(OpenApi)]
 #[openapi(
@@ -11,7 +22,34 @@ use utoipa::OpenApi;
         crate::routes::depth_route::get_depth_price_history,
         crate::routes::swap_route::get_swaps_history,
         crate::routes::earning_route::get_earnings_history,
-        crate::routes::rune_pool_route::get_rune_pool_history
+        crate::routes::rune_pool_route::get_rune_pool_history,
+        crate::routes::midgard_route::get_midgard_depths,
+        crate::routes::midgard_route::get_midgard_swaps,
+        crate::routes::midgard_route::get_midgard_earnings,
+        crate::routes::midgard_route::get_midgard_rune_pool,
+        crate::routes::tools_route::get_swap_quote,
+        crate::routes::candle_route::get_candles,
+        crate::routes::indicator_route::get_indicators,
+        crate::routes::anomaly_route::get_anomalies,
+        crate::routes::alert_route::create_alert_rule,
+        crate::routes::alert_route::get_alert_rules,
+        crate::routes::alert_route::delete_alert_rule,
+        crate::routes::alert_route::get_alert_events,
+        crate::routes::stream_route::get_event_stream,
+        crate::routes::stream_route::get_websocket_stream,
+        crate::routes::graphql_route::graphql_query,
+        crate::routes::usage_route::get_usage,
+        crate::routes::metrics_route::get_metrics,
+        crate::routes::health_route::get_health,
+        crate::routes::health_route::get_ready,
+        crate::routes::health_route::get_data_health,
+        crate::routes::admin_route::fetch_all_depths_to_db,
+        crate::routes::admin_route::fetch_all_swaps_to_db,
+        crate::routes::admin_route::fetch_all_earnings_to_db,
+        crate::routes::admin_route::fetch_all_rune_pools_to_db,
+        crate::routes::admin_route::get_quarantine,
+        crate::routes::admin_route::get_quarantined_interval,
+        crate::routes::admin_route::replay_quarantined_interval
     ),
     components(schemas(
         crate::models::depth_history_model::PoolDepthPriceHistory,
@@ -19,7 +57,22 @@ use utoipa::OpenApi;
         crate::models::swap_history_model::SwapHistory,
         crate::models::earning_history_model::PoolEarningHistory,
         crate::models::earning_history_model::PoolEarningSummary,
-        crate::models::api_request_param_model::QueryParams
-    ))
+        crate::models::api_request_param_model::QueryParams,
+        crate::models::swap_quote_model::SwapQuote,
+        crate::models::candle_model::Candle,
+        crate::models::candle_model::CandleMeta,
+        crate::models::candle_model::CandleSeries,
+        crate::models::anomaly_model::Anomaly,
+        crate::models::alert_rule_model::AlertRule,
+        crate::models::alert_rule_model::AlertRuleRequest,
+        crate::models::alert_rule_model::AlertEvent,
+        crate::models::stream_event_model::StreamEvent,
+        crate::models::usage_model::ApiUsage,
+        crate::models::health_model::CollectionFreshness,
+        crate::models::health_model::DataFreshness,
+        crate::models::quarantine_model::QuarantineEntry,
+        crate::models::quarantine_model::ReplayReport
+    )),
+    modifiers(&SecurityAddon)
 )]
 pub struct ApiDoc;
\ No newline at end of file
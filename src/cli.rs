@@ -0,0 +1,245 @@
+use std::{fs::File, io::{self, BufWriter, Write}};
+
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::{
+    config::Config,
+    models::{api_key_model::ApiKey, custom_error_model::CustomError},
+    services::{
+        backfill_service::{backfill, validate_collection},
+        db::DataBase,
+        maintenance_service::{export_collection, find_gaps, reindex, verify_collection, ExportFormat},
+        midgard_verify_service::{verify_against_midgard, MidgardVerifyPlan},
+    },
+    utils::constants::{INGESTED_COLLECTIONS, VALID_INTERVALS},
+};
+
+/// Midgard history replica: HTTP server and maintenance commands
+#[derive(Debug,Parser)]
+#[command(name = "tokenmetrics", version)]
+pub struct Cli{
+    /// Database url overriding the config, sqlite://path keeps the history in a local file without MongoDB,
+    /// postgres://... keeps it in PostgreSQL and mongodb://... replaces database.uri
+    #[arg(long, global = true)]
+    pub db : Option<String>,
+    /// Runs the HTTP server when omitted
+    #[command(subcommand)]
+    pub command : Option<Command>
+}
+
+#[derive(Debug,Subcommand)]
+pub enum Command{
+    /// Run the HTTP server and the ingestion cron
+    Serve,
+    /// Fetch hour intervals from Midgard into a collection
+    Backfill(RangeArgs),
+    /// List missing hour intervals of a collection
+    Gaps(RangeArgs),
+    /// Write the records of a collection as json lines or csv
+    Export(ExportArgs),
+    /// Create the indexes used by the API, the ingestion and the api keys
+    Reindex,
+    /// Report duplicate, malformed and missing intervals, exits 2 when any are found, earnings summaries their pools do not add up to are listed without changing the exit code
+    Verify(VerifyArgs),
+    /// Compare sampled history responses with Midgard field by field, exits 2 on any discrepancy
+    VerifyMidgard(VerifyMidgardArgs),
+    /// Manage X-API-Key keys
+    #[command(subcommand)]
+    Keys(KeysCommand),
+}
+
+#[derive(Debug,Args)]
+pub struct RangeArgs{
+    /// One of depths, swaps, earnings, runepool
+    #[arg(long)]
+    pub collection : String,
+    /// Pool like BTC.BTC, defaults to every pool
+    #[arg(long)]
+    pub pool : Option<String>,
+    /// Unix timestamp
+    #[arg(long)]
+    pub from : Option<i64>,
+    /// Unix timestamp
+    #[arg(long)]
+    pub to : Option<i64>
+}
+
+#[derive(Debug,Clone,Copy,ValueEnum)]
+pub enum ExportFormatArg{
+    Json,
+    Csv,
+}
+
+#[derive(Debug,Args)]
+pub struct ExportArgs{
+    #[command(flatten)]
+    pub range : RangeArgs,
+    #[arg(long, value_enum, default_value = "json")]
+    pub format : ExportFormatArg,
+    /// File to write, stdout when omitted
+    #[arg(long)]
+    pub output : Option<String>
+}
+
+#[derive(Debug,Args)]
+pub struct VerifyArgs{
+    /// Collection to check, every ingested collection when omitted
+    #[arg(long)]
+    pub collection : Option<String>
+}
+
+#[derive(Debug,Args)]
+pub struct VerifyMidgardArgs{
+    /// One of depths, swaps, earnings, runepool, every collection when omitted
+    #[arg(long)]
+    pub collection : Option<String>,
+    /// Pool of depths and swaps like BTC.BTC, every configured pool when omitted
+    #[arg(long)]
+    pub pool : Option<String>,
+    /// Interval to compare, repeat for several
+    #[arg(long, default_value = "hour")]
+    pub interval : Vec<String>,
+    /// Windows per collection, pool and interval
+    #[arg(long, default_value_t = 5)]
+    pub samples : u32,
+    /// Intervals per window
+    #[arg(long, default_value_t = 24)]
+    pub count : u32,
+    /// Unix timestamp, defaults to ingestion.start_time
+    #[arg(long)]
+    pub from : Option<i64>,
+    /// Unix timestamp, defaults to the latest stored interval
+    #[arg(long)]
+    pub to : Option<i64>,
+    /// Relative numeric tolerance
+    #[arg(long, default_value_t = 1e-6)]
+    pub tolerance : f64
+}
+
+#[derive(Debug,Subcommand)]
+pub enum KeysCommand{
+    /// Create a key, it is printed once and only its hash is stored
+    Mint { name: String, role: String },
+    /// Revoke every active key with the prefix
+    Revoke { prefix: String },
+    List,
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<(), CustomError> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| CustomError::StandardError(e.to_string()))?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn verify_midgard_plan(config: &Config, args: VerifyMidgardArgs) -> Result<MidgardVerifyPlan, CustomError> {
+    let mut plan = MidgardVerifyPlan::new(config);
+    if let Some(collection) = &args.collection {
+        validate_collection(collection)?;
+        plan.collections.retain(|candidate| candidate.as_str() == collection);
+    }
+    if let Some(pool) = args.pool {
+        plan.pools = vec![pool];
+    }
+    for interval in &args.interval {
+        if !VALID_INTERVALS.contains(&interval.as_str()) {
+            return Err(CustomError::InvalidInput(format!("Interval must be in {:?}", VALID_INTERVALS)));
+        }
+    }
+    if args.samples == 0 || args.count == 0 {
+        return Err(CustomError::InvalidInput("samples and count must be positive".to_string()));
+    }
+    plan.intervals = args.interval;
+    plan.samples = args.samples;
+    plan.count = args.count;
+    plan.from = args.from.unwrap_or(plan.from);
+    plan.to = args.to;
+    plan.tolerance = args.tolerance;
+    Ok(plan)
+}
+
+async fn run_keys_command(db: &DataBase, command: KeysCommand) -> Result<(), CustomError> {
+    match command {
+        KeysCommand::Mint { name, role } => {
+            let key = ApiKey::mint_api_key(db, &name, &role).await?;
+            println!("{}", key);
+            eprintln!("Store this key now, only its hash is kept");
+        }
+        KeysCommand::Revoke { prefix } => {
+            let revoked = ApiKey::revoke_api_key(db, &prefix).await?;
+            println!("Revoked {} key(s)", revoked);
+        }
+        KeysCommand::List => {
+            for key in ApiKey::list_api_keys(db).await? {
+                let status = if key.revoked_at.is_some() { "revoked" } else { "active" };
+                println!("{}\t{}\t{}\t{}", key.prefix, key.role, status, key.name);
+            }
+        }
+    }
+    Ok(())
+}
+
+// every command except serve, returns the process exit code
+pub async fn run_command(db: &DataBase, config: &Config, command: Command) -> Result<i32, CustomError> {
+    match command {
+        Command::Serve => return Err(CustomError::StandardError("serve is handled by main".to_string())),
+        Command::Backfill(range) => {
+            let from = range.from.unwrap_or(config.ingestion.start_time);
+            let to = range.to.unwrap_or(Utc::now().timestamp());
+            let reports = backfill(db, config, &range.collection, range.pool.as_deref(), from, to).await?;
+            print_json(&reports)?;
+            if reports.iter().any(|report| report.failed_pages > 0) {
+                return Ok(2);
+            }
+        }
+        Command::Gaps(range) => {
+            let gaps = find_gaps(db, &range.collection, range.pool.as_deref(), range.from, range.to).await?;
+            print_json(&gaps)?;
+            if !gaps.is_empty() {
+                return Ok(2);
+            }
+        }
+        Command::Export(ExportArgs { range, format, output }) => {
+            let format = match format {
+                ExportFormatArg::Json => ExportFormat::Json,
+                ExportFormatArg::Csv => ExportFormat::Csv,
+            };
+            let mut out: Box<dyn Write> = match &output {
+                Some(path) => Box::new(BufWriter::new(
+                    File::create(path).map_err(|e| CustomError::StandardError(format!("Failed creating {} {}", path, e)))?,
+                )),
+                None => Box::new(BufWriter::new(io::stdout().lock())),
+            };
+            let written = export_collection(db, &range.collection, range.pool.as_deref(), range.from, range.to, format, &mut out).await?;
+            eprintln!("Exported {} records", written);
+        }
+        Command::Reindex => {
+            for index in reindex(db).await? {
+                println!("{}", index);
+            }
+        }
+        Command::Verify(VerifyArgs { collection }) => {
+            let collections = match &collection {
+                Some(collection) => vec![collection.as_str()],
+                None => INGESTED_COLLECTIONS.to_vec(),
+            };
+            let mut reports = Vec::new();
+            for collection in collections {
+                reports.push(verify_collection(db, collection).await?);
+            }
+            print_json(&reports)?;
+            if reports.iter().any(|report| !report.is_consistent()) {
+                return Ok(2);
+            }
+        }
+        Command::VerifyMidgard(args) => {
+            let report = verify_against_midgard(db, config, &verify_midgard_plan(config, args)?).await?;
+            print_json(&report)?;
+            if !report.is_consistent() {
+                return Ok(2);
+            }
+        }
+        Command::Keys(command) => run_keys_command(db, command).await?,
+    }
+    Ok(0)
+}
@@ -0,0 +1,288 @@
+use std::{env, fmt::Display, fs, path::Path, str::FromStr};
+
+use chrono::Utc;
+use dotenv::dotenv;
+use serde::Deserialize;
+
+use crate::{
+    middlewares::rate_limit_middleware::{default_route_groups, RouteGroup},
+    models::{custom_error_model::CustomError, health_model::DEFAULT_MAX_DATA_LAG_SECS},
+    utils::constants::API_START_TIME,
+};
+
+// read when CONFIG_FILE is not set, running without it falls back to defaults and env
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+// midgard rejects history requests for more than 400 intervals
+const MAX_MIDGARD_PAGE_SIZE: u32 = 400;
+
+#[derive(Debug,Clone,Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig{
+    pub host : String,
+    pub port : u16
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig { host: "0.0.0.0".to_string(), port: 3000 }
+    }
+}
+
+#[derive(Debug,Clone,Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig{
+    // required, also read from the legacy DB env var
+    pub uri : String,
+    pub name : String,
+    // postgres:// or sqlite:// url of the history store, the history stays in mongodb when unset
+    pub history_uri : Option<String>
+}
+
+impl DatabaseConfig {
+    // the --db url, mongodb urls replace uri and the others pick the history store
+    pub fn set_url(&mut self, url: &str) {
+        if url.starts_with("mongodb://") || url.starts_with("mongodb+srv://") {
+            self.uri = url.to_string();
+        } else {
+            self.history_uri = Some(url.to_string());
+        }
+    }
+
+    // a sqlite history needs no database server, uri becomes optional
+    pub fn is_embedded(&self) -> bool {
+        self.history_uri.as_deref().is_some_and(|uri| uri.starts_with("sqlite://"))
+    }
+}
+
+// the url with the password of its user info masked, for error messages and logs
+pub fn without_credentials(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let authority_end = rest.find(['/', '?']).unwrap_or(rest.len());
+    match rest[..authority_end].rsplit_once('@') {
+        Some((user_info, host)) => {
+            let user = user_info.split_once(':').map_or(user_info, |(user, _)| user);
+            format!("{}://{}:***@{}{}", scheme, user, host, &rest[authority_end..])
+        }
+        None => url.to_string(),
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig { uri: String::new(), name: "token-metrics".to_string(), history_uri: None }
+    }
+}
+
+#[derive(Debug,Clone,Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MidgardConfig{
+    pub base_url : String,
+    // intervals requested per history call
+    pub page_size : u32
+}
+
+impl Default for MidgardConfig {
+    fn default() -> Self {
+        MidgardConfig { base_url: "https://midgard.ninerealms.com".to_string(), page_size: MAX_MIDGARD_PAGE_SIZE }
+    }
+}
+
+#[derive(Debug,Clone,Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IngestionConfig{
+    // run the fetch cron in the server process
+    pub enabled : bool,
+    pub interval_secs : u64,
+    // first timestamp requested by the /admin backfills
+    pub start_time : i64,
+    // pools a backfill pages through at once
+    pub concurrency : usize,
+    // pages a backfill fetches ahead of the one being stored
+    pub prefetch_pages : usize
+}
+
+impl Default for IngestionConfig {
+    fn default() -> Self {
+        IngestionConfig { enabled: true, interval_secs: 3_600, start_time: API_START_TIME, concurrency: 4, prefetch_pages: 2 }
+    }
+}
+
+#[derive(Debug,Clone,Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig{
+    // /health/data turns 503 past this lag
+    pub max_data_lag_secs : i64,
+    // [[limits.rate_limits]] tables, the last group is the catch-all
+    pub rate_limits : Vec<RouteGroup>
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig { max_data_lag_secs: DEFAULT_MAX_DATA_LAG_SECS, rate_limits: default_route_groups() }
+    }
+}
+
+#[derive(Debug,Clone,Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config{
+    pub server : ServerConfig,
+    pub database : DatabaseConfig,
+    pub midgard : MidgardConfig,
+    pub ingestion : IngestionConfig,
+    // pools whose depths and swaps are ingested and served
+    pub pools : Vec<String>,
+    pub limits : LimitsConfig
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            server: ServerConfig::default(),
+            database: DatabaseConfig::default(),
+            midgard: MidgardConfig::default(),
+            ingestion: IngestionConfig::default(),
+            pools: vec!["BTC.BTC".to_string()],
+            limits: LimitsConfig::default(),
+        }
+    }
+}
+
+fn env_override<T: FromStr>(name: &str, target: &mut T, errors: &mut Vec<String>)
+where
+    T::Err: Display,
+{
+    if let Ok(value) = env::var(name) {
+        match value.parse::<T>() {
+            Ok(value) => *target = value,
+            Err(e) => errors.push(format!("{}={} is invalid: {}", name, value, e)),
+        }
+    }
+}
+
+impl Config {
+    // CONFIG_FILE (or ./config.toml when present), then env overrides, then the --db url, then validation
+    pub fn load(db_url: Option<&str>) -> Result<Self, CustomError> {
+        dotenv().ok();
+        let mut config = match env::var("CONFIG_FILE") {
+            Ok(path) => Config::from_file(&path)?,
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => Config::from_file(DEFAULT_CONFIG_FILE)?,
+            Err(_) => Config::default(),
+        };
+        config.apply_env_overrides()?;
+        if let Some(db_url) = db_url {
+            config.database.set_url(db_url);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, CustomError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| CustomError::StandardError(format!("Failed reading config file {}: {}", path, e)))?;
+        Config::from_toml(&content).map_err(|e| CustomError::StandardError(format!("{}: {}", path, e)))
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, CustomError> {
+        toml::from_str(content).map_err(|e| CustomError::StandardError(format!("Invalid config: {}", e)))
+    }
+
+    // SECTION_FIELD env vars win over the file, POOLS is comma separated
+    pub fn apply_env_overrides(&mut self) -> Result<(), CustomError> {
+        let mut errors = Vec::new();
+        env_override("SERVER_HOST", &mut self.server.host, &mut errors);
+        env_override("SERVER_PORT", &mut self.server.port, &mut errors);
+        env_override("DB", &mut self.database.uri, &mut errors);
+        env_override("DATABASE_URI", &mut self.database.uri, &mut errors);
+        env_override("DATABASE_NAME", &mut self.database.name, &mut errors);
+        if let Ok(history_uri) = env::var("DATABASE_HISTORY_URI") {
+            self.database.history_uri = Some(history_uri).filter(|uri| !uri.is_empty());
+        }
+        env_override("MIDGARD_BASE_URL", &mut self.midgard.base_url, &mut errors);
+        env_override("MIDGARD_PAGE_SIZE", &mut self.midgard.page_size, &mut errors);
+        env_override("INGESTION_ENABLED", &mut self.ingestion.enabled, &mut errors);
+        env_override("INGESTION_INTERVAL_SECS", &mut self.ingestion.interval_secs, &mut errors);
+        env_override("INGESTION_START_TIME", &mut self.ingestion.start_time, &mut errors);
+        env_override("INGESTION_CONCURRENCY", &mut self.ingestion.concurrency, &mut errors);
+        env_override("INGESTION_PREFETCH_PAGES", &mut self.ingestion.prefetch_pages, &mut errors);
+        env_override("LIMITS_MAX_DATA_LAG_SECS", &mut self.limits.max_data_lag_secs, &mut errors);
+        if let Ok(pools) = env::var("POOLS") {
+            self.pools = pools.split(',').map(str::trim).filter(|pool| !pool.is_empty()).map(str::to_string).collect();
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(CustomError::StandardError(format!("Invalid environment: {}", errors.join("; "))))
+        }
+    }
+
+    // reports every problem at once instead of failing on the first
+    pub fn validate(&self) -> Result<(), CustomError> {
+        let mut errors = Vec::new();
+        if self.server.host.trim().is_empty() {
+            errors.push("server.host must not be empty".to_string());
+        }
+        if self.server.port == 0 {
+            errors.push("server.port must not be 0".to_string());
+        }
+        if self.database.uri.is_empty() {
+            if !self.database.is_embedded() {
+                errors.push("database.uri is required (or the DB / DATABASE_URI env var) unless the history is in sqlite".to_string());
+            }
+        } else if !self.database.uri.starts_with("mongodb://") && !self.database.uri.starts_with("mongodb+srv://") {
+            errors.push("database.uri must start with mongodb:// or mongodb+srv://".to_string());
+        }
+        if let Some(history_uri) = &self.database.history_uri {
+            if !["postgres://", "postgresql://", "sqlite://"].iter().any(|scheme| history_uri.starts_with(scheme)) {
+                errors.push("database.history_uri must start with postgres://, postgresql:// or sqlite://".to_string());
+            }
+        }
+        if self.database.name.trim().is_empty() {
+            errors.push("database.name must not be empty".to_string());
+        }
+        if !self.midgard.base_url.starts_with("http://") && !self.midgard.base_url.starts_with("https://") {
+            errors.push("midgard.base_url must be an http(s) url".to_string());
+        }
+        if self.midgard.page_size == 0 || self.midgard.page_size > MAX_MIDGARD_PAGE_SIZE {
+            errors.push(format!("midgard.page_size must be between 1 and {}", MAX_MIDGARD_PAGE_SIZE));
+        }
+        if self.ingestion.interval_secs < 60 {
+            errors.push("ingestion.interval_secs must be at least 60".to_string());
+        }
+        if self.ingestion.start_time < 0 || self.ingestion.start_time >= Utc::now().timestamp() {
+            errors.push("ingestion.start_time must be a past unix timestamp".to_string());
+        }
+        if self.ingestion.concurrency == 0 || self.ingestion.prefetch_pages == 0 {
+            errors.push("ingestion.concurrency and ingestion.prefetch_pages must be at least 1".to_string());
+        }
+        if self.pools.is_empty() {
+            errors.push("pools must list at least one pool".to_string());
+        }
+        for pool in &self.pools {
+            if !pool.contains('.') {
+                errors.push(format!("pool {} must look like CHAIN.ASSET", pool));
+            }
+        }
+        if self.limits.max_data_lag_secs <= 0 {
+            errors.push("limits.max_data_lag_secs must be positive".to_string());
+        }
+        if self.limits.rate_limits.is_empty() {
+            errors.push("limits.rate_limits must define at least the catch-all group".to_string());
+        }
+        for group in &self.limits.rate_limits {
+            if group.cost <= 0.0 || group.refill_per_sec <= 0.0 || group.capacity < group.cost {
+                errors.push(format!("rate limit group {} needs cost > 0, refill_per_sec > 0 and capacity >= cost", group.name));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(CustomError::StandardError(format!("Invalid configuration: {}", errors.join("; "))))
+        }
+    }
+
+    pub fn bind_address(&self) -> (String, u16) {
+        (self.server.host.clone(), self.server.port)
+    }
+}
@@ -1,4 +1,13 @@
 pub mod depth_history_api_controller;
 pub mod swaps_history_api_controller;
 pub mod rune_pool_history_api_controller;
-pub mod earnings_history_api_controller;
\ No newline at end of file
+pub mod earnings_history_api_controller;
+pub mod swap_quote_api_controller;
+pub mod candles_api_controller;
+pub mod indicators_api_controller;
+pub mod anomalies_api_controller;
+pub mod alert_rules_api_controller;
+pub mod stream_api_controller;
+pub mod usage_api_controller;
+pub mod health_api_controller;
+pub mod midgard_compat_api_controller;
\ No newline at end of file
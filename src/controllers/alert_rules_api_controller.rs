@@ -0,0 +1,109 @@
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+
+use crate::{
+    models::{alert_rule_model::{AlertRule, AlertRuleRequest}, custom_error_model::CustomError},
+    services::{db::DataBase, metrics_service::timed_aggregation},
+};
+use tracing::warn;
+
+fn parse_object_id(id: &str) -> Result<ObjectId, CustomError> {
+    ObjectId::parse_str(id).map_err(|_| CustomError::InvalidInput(format!("Invalid id {}", id)))
+}
+
+impl DataBase {
+    // POST /alerts
+    #[tracing::instrument(skip(self, request))]
+    pub async fn create_alert_rule_api(&self, request: AlertRuleRequest) -> Result<Document, CustomError> {
+        let rule = AlertRule {
+            _id: ObjectId::new(),
+            name: request.name,
+            pool: request.pool,
+            metric: request.metric,
+            condition: request.condition,
+            threshold: request.threshold,
+            window_secs: request.window_secs.unwrap_or(0),
+            cooldown_secs: request.cooldown_secs.unwrap_or(3600),
+            webhook_url: request.webhook_url,
+            secret: request.secret,
+            enabled: true,
+            last_triggered_at: None,
+            last_triggered_end_time: None,
+            created_at: Utc::now().timestamp(),
+        };
+        let id = rule._id;
+        self.mongodb()?.alert_rules.insert_one(rule).await?;
+        Ok(doc! { "id": id.to_hex() })
+    }
+
+    // GET /alerts, the secret stays in the db
+    #[tracing::instrument(skip(self))]
+    pub async fn get_alert_rules_api(&self) -> Result<Vec<Document>, CustomError> {
+        let pipeline = vec![
+            doc! { "$sort": { "created_at": -1 } },
+            doc! { "$project": {
+                "_id": 0,
+                "id": { "$toString": "$_id" },
+                "name": 1,
+                "pool": 1,
+                "metric": 1,
+                "condition": 1,
+                "threshold": 1,
+                "windowSecs": "$window_secs",
+                "cooldownSecs": "$cooldown_secs",
+                "webhookUrl": "$webhook_url",
+                "enabled": 1,
+                "lastTriggeredAt": "$last_triggered_at",
+                "lastTriggeredEndTime": "$last_triggered_end_time",
+                "createdAt": "$created_at"
+            }},
+        ];
+        let mut cursor = timed_aggregation("alert_rules", self.mongodb()?.alert_rules.aggregate(pipeline)).await?;
+        let mut query_response = Vec::new();
+        while let Some(result) = cursor.next().await {
+            match result {
+                Ok(record) => query_response.push(record),
+                Err(e) => warn!(error = ?e, "Error fetching document"),
+            }
+        }
+        Ok(query_response)
+    }
+
+    // DELETE /alerts/{id}, events of the rule are kept as history
+    #[tracing::instrument(skip(self))]
+    pub async fn delete_alert_rule_api(&self, id: &str) -> Result<bool, CustomError> {
+        let result = self.mongodb()?.alert_rules.delete_one(doc! { "_id": parse_object_id(id)? }).await?;
+        Ok(result.deleted_count > 0)
+    }
+
+    // GET /alerts/{id}/events
+    #[tracing::instrument(skip(self))]
+    pub async fn get_alert_events_api(&self, id: &str) -> Result<Vec<Document>, CustomError> {
+        let pipeline = vec![
+            doc! { "$match": { "rule_id": parse_object_id(id)? } },
+            doc! { "$sort": { "triggered_at": -1 } },
+            doc! { "$limit": 400 },
+            doc! { "$project": {
+                "_id": 0,
+                "pool": 1,
+                "metric": 1,
+                "value": 1,
+                "endTime": "$end_time",
+                "triggeredAt": "$triggered_at",
+                "delivered": 1,
+                "attempts": 1,
+                "lastError": "$last_error"
+            }},
+        ];
+        let mut cursor = timed_aggregation("alert_events", self.mongodb()?.alert_events.aggregate(pipeline)).await?;
+        let mut query_response = Vec::new();
+        while let Some(result) = cursor.next().await {
+            match result {
+                Ok(record) => query_response.push(record),
+                Err(e) => warn!(error = ?e, "Error fetching document"),
+            }
+        }
+        Ok(query_response)
+    }
+}
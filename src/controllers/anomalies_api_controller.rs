@@ -0,0 +1,79 @@
+use futures_util::StreamExt;
+use mongodb::bson::{doc, Document};
+
+use crate::{models::{anomaly_model::AnomalyParams, custom_error_model::CustomError}, services::{db::DataBase, metrics_service::timed_aggregation}};
+use tracing::warn;
+
+impl DataBase {
+    // /anomalies
+    #[tracing::instrument(skip(self))]
+    pub async fn get_anomalies_api(&self, params: AnomalyParams) -> Result<Document, CustomError> {
+        let AnomalyParams {
+            pool,
+            kind,
+            severity,
+            from,
+            to,
+            page,
+            count,
+        } = params;
+
+        let mut query = doc! {};
+        if let Some(pool) = pool {
+            query.insert("pool", pool);
+        }
+        if let Some(kind) = kind {
+            query.insert("kind", kind);
+        }
+        if let Some(severity) = severity {
+            let severities = severity.split(',').map(|severity| severity.trim().to_string()).collect::<Vec<String>>();
+            query.insert("severity", doc! { "$in": severities });
+        }
+        if let Some(from) = from {
+            query.insert("start_time", doc! { "$gte": from as i64 });
+        }
+        if let Some(to) = to {
+            query.insert("end_time", doc! { "$lte": to as i64 });
+        }
+        let limit = count.unwrap_or(100) as i64;
+        let skip_size = (page.unwrap_or(1) as i64 - 1) * limit;
+
+        let pipeline = vec![
+            doc! { "$match": query },
+            doc! { "$sort": { "end_time": -1, "score": -1 } },
+            doc! { "$skip": skip_size },
+            doc! { "$limit": limit },
+            doc! { "$project": {
+                "_id": 0,
+                "pool": 1,
+                "collection": 1,
+                "kind": 1,
+                "metric": 1,
+                "value": 1,
+                "baselineMedian": "$baseline_median",
+                "baselineMAD": "$baseline_mad",
+                "score": 1,
+                "severity": 1,
+                "startTime": "$start_time",
+                "endTime": "$end_time",
+                "detectedAt": "$detected_at"
+            }},
+        ];
+
+        let mut cursor = timed_aggregation("anomalies", self.mongodb()?.anomalies.aggregate(pipeline)).await?;
+        let mut query_response = Vec::new();
+        while let Some(result) = cursor.next().await {
+            match result {
+                Ok(record) => query_response.push(record),
+                Err(e) => warn!(error = ?e, "Error fetching document"),
+            }
+        }
+        let response = doc! {
+            "meta": {
+                "count": query_response.len() as i64
+            },
+            "anomalies": query_response
+        };
+        Ok(response)
+    }
+}
@@ -0,0 +1,92 @@
+use chrono::Utc;
+
+use crate::{
+    models::{candle_model::{Candle, CandleMeta, CandleParams, CandleSeries}, custom_error_model::CustomError},
+    services::db::DataBase,
+    stores::metrics_store::{HistoryCollection, RecordQuery},
+    utils::{constants::RUNE_ASSET, db_helper_utils::{get_interval_start, get_seconds_per_interval}, parser_utils::bson_to_f64},
+};
+
+impl DataBase {
+    // /candles
+    #[tracing::instrument(skip(self))]
+    pub async fn get_candles_api(&self, params: CandleParams) -> Result<CandleSeries, CustomError> {
+        let CandleParams {
+            pool,
+            interval,
+            quote,
+            from,
+            to,
+            count,
+        } = params;
+
+        let interval = interval.unwrap_or("hour".to_string());
+        let quote = quote.unwrap_or("usd".to_string());
+        let seconds_per_interval = get_seconds_per_interval(&interval) as i64;
+        let count = count.unwrap_or(400) as i64;
+        let is_rune = pool == RUNE_ASSET;
+
+        // rune price comes from the swaps (same for every pool), asset prices from the pool depths
+        let (collection, price_field) = if is_rune {
+            (HistoryCollection::Swaps, "rune_price_usd")
+        } else if quote == "rune" {
+            (HistoryCollection::Depths, "asset_price")
+        } else {
+            (HistoryCollection::Depths, "asset_price_usd")
+        };
+
+        // same from fallback as the history endpoints, count intervals back from to or the latest record
+        let from = match from {
+            Some(from) => from as i64,
+            None => {
+                let calc_start = match to {
+                    Some(to) => to as i64,
+                    None => self.store.latest_end_time(collection).await.ok().flatten().unwrap_or(Utc::now().timestamp()),
+                };
+                calc_start - count * seconds_per_interval
+            }
+        };
+        let query = RecordQuery {
+            pool: (!is_rune).then(|| pool.clone()),
+            from: Some(from),
+            to: to.map(|to| to as i64),
+            limit: i64::MAX,
+            ..RecordQuery::default()
+        };
+
+        // records come oldest first, so the first of a bucket opens it and the last closes it
+        let mut candles: Vec<Candle> = Vec::new();
+        for record in self.store.records(collection, &query).await? {
+            let (Ok(end_time), Some(price)) = (record.get_i64("end_time"), record.get(price_field).and_then(bson_to_f64)) else {
+                continue;
+            };
+            let start_time = get_interval_start(end_time, seconds_per_interval);
+            match candles.last_mut() {
+                Some(candle) if candle.start_time == start_time => {
+                    candle.high = candle.high.max(price);
+                    candle.low = candle.low.min(price);
+                    candle.close = price;
+                }
+                _ => {
+                    if candles.len() as i64 == count {
+                        break;
+                    }
+                    candles.push(Candle {
+                        time: start_time,
+                        start_time,
+                        end_time: start_time + seconds_per_interval,
+                        open: price,
+                        high: price,
+                        low: price,
+                        close: price,
+                    });
+                }
+            }
+        }
+
+        Ok(CandleSeries {
+            meta: CandleMeta { pool, interval, quote },
+            candles,
+        })
+    }
+}
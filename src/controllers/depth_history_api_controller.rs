@@ -1,71 +1,21 @@
-use chrono::Utc;
 use futures_util::StreamExt;
 use mongodb::bson::{doc, Document};
 
 use crate::{
-    models::{api_request_param_model::QueryParams, custom_error_model::CustomError},
-    services::db::DataBase,
-    utils::{db_helper_utils::{build_query_sort_skip, get_seconds_per_interval}, parser_utils::subtract_bson_values},
+    models::custom_error_model::CustomError,
+    services::metrics_service::timed_aggregation,
+    stores::{metrics_store::HistoryQuery, mongo_store::MongoStore},
+    utils::parser_utils::subtract_bson_values,
 };
+use tracing::warn;
 
-impl DataBase {
-    // /depths
-    pub async fn get_depth_price_history_api(
-        &self,
-        params: QueryParams,
-    ) -> Result<Document, CustomError> {
-        let mut query = doc! {};
-        let QueryParams {
-            pool,
-            interval,
-            count,
-            to,
-            from,
-            page,
-            sort_by,
-            sort_order,
-            limit,
-        } = params;
-    
-        let seconds_per_interval = get_seconds_per_interval(interval.as_ref().unwrap_or(&"hour".to_string()).as_str());
-    
-        if let Some(pool) = pool {
-            // Check pool constraint
-            if pool != "BTC.BTC" {
-                return Err(CustomError::InvalidInput(
-                    "Depth and price history for only BTC.BTC available".to_string(),
-                ));
-            }
-            query.insert("pool", pool);
-        }
-        
-        // as per midgard api if from is not specified the from has to be fixed back relative to either current timestamp or "to" timestamp (if given) or w.r.t the latest record in the collection
-        if let Some(from) = from {
-            query.insert("start_time", doc! { "$gte": from as i64 });
-        } else {
-            let calc_start = if let Some(to) = to {
-                to as i64
-            } else {
-                self.get_max_end_time(&self.depth_history)
-                    .await
-                    .unwrap_or(Utc::now().timestamp())
-            };
-            let count = count.unwrap_or(400) as i64;
-            let queried_interval_duration = seconds_per_interval as i64;
-            query.insert(
-                "start_time",
-                doc! {"$gte": calc_start - (count * queried_interval_duration) as i64},
-            );
-        }
-
-        // common query building code part has been moved to a helper function
-        let (query_part, sort_filter, skip_size, _limit) =
-            build_query_sort_skip(to, sort_by, sort_order, page, limit, count).await;
-    
-        // update the actual query with the query_part from builder
-        query.extend(query_part.clone());
+impl MongoStore {
+    // /depths intervals, the meta is added by depth_history_response
+    #[tracing::instrument(skip(self))]
+    pub(crate) async fn depth_history_intervals(&self, query: &HistoryQuery) -> Result<Vec<Document>, CustomError> {
+        let seconds_per_interval = query.seconds_per_interval;
         let pipeline = vec![
-            doc! { "$match": query },
+            doc! { "$match": query.filter() },
             doc! {
                 "$group": {
                     "_id": {
@@ -116,12 +66,12 @@ impl DataBase {
                 "units": 1,
                 "pool" : 1
             }},
-            doc! { "$sort": sort_filter },
-            doc! { "$skip": skip_size as i64 },
-            doc! { "$limit": count.unwrap_or(400) as i64 },
+            doc! { "$sort": query.sort_filter() },
+            doc! { "$skip": query.skip },
+            doc! { "$limit": query.limit },
         ];
     
-        let mut cursor = self.depth_history.aggregate(pipeline).await?;
+        let mut cursor = timed_aggregation("depth_history", self.depth_history.aggregate(pipeline)).await?;
         let mut query_response = Vec::new();
     
         while let Some(result) = cursor.next().await {
@@ -130,30 +80,35 @@ impl DataBase {
                     record.remove("_id");
                     query_response.push(record);
                 }
-                Err(e) => eprintln!("Error fetching document: {:?}", e),
+                Err(e) => warn!(error = ?e, "Error fetching document"),
             }
         }
-        let first = query_response.first().unwrap();
-        let last = query_response.last().unwrap();
-        let response = doc! {
-            "meta":{
-                "endAssetDepth": last.get("assetDepth"),
-                "endLPUnits": last.get("units"),
-                "endMemberCount": last.get("membersCount"),
-                "endRuneDepth": last.get("runeDepth"),
-                "endSynthUnits": last.get("synthUnits"),
-                "endTime": last.get("endTime"),
-                "luviIncrease": subtract_bson_values(last.get("luvi").unwrap(),first.get("luvi").unwrap()),
-                "priceShiftLoss": subtract_bson_values(first.get("assetPrice").unwrap(), last.get("assetPrice").unwrap()),
-                "startAssetDepth": first.get("assetDepth"),
-                "startLPUnits": first.get("units"),
-                "startMemberCount": first.get("membersCount"),
-                "startRuneDepth": first.get("runeDepth"),
-                "startSynthUnits": first.get("synthUnits"),
-                "startTime": first.get("startTime")
-            },
-            "intervals": query_response
-        };
-        Ok(response)
-    }    
+        Ok(query_response)
+    }
+}
+
+pub fn depth_history_response(intervals: Vec<Document>) -> Result<Document, CustomError> {
+    let (Some(first), Some(last)) = (intervals.first(), intervals.last()) else {
+        return Err(CustomError::InvalidInput("No intervals found for the requested range".to_string()));
+    };
+    let response = doc! {
+        "meta":{
+            "endAssetDepth": last.get("assetDepth"),
+            "endLPUnits": last.get("units"),
+            "endMemberCount": last.get("membersCount"),
+            "endRuneDepth": last.get("runeDepth"),
+            "endSynthUnits": last.get("synthUnits"),
+            "endTime": last.get("endTime"),
+            "luviIncrease": subtract_bson_values(last.get("luvi").unwrap(),first.get("luvi").unwrap()),
+            "priceShiftLoss": subtract_bson_values(first.get("assetPrice").unwrap(), last.get("assetPrice").unwrap()),
+            "startAssetDepth": first.get("assetDepth"),
+            "startLPUnits": first.get("units"),
+            "startMemberCount": first.get("membersCount"),
+            "startRuneDepth": first.get("runeDepth"),
+            "startSynthUnits": first.get("synthUnits"),
+            "startTime": first.get("startTime")
+        },
+        "intervals": intervals
+    };
+    Ok(response)
 }
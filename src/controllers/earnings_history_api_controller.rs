@@ -1,73 +1,21 @@
-use chrono::Utc;
 use futures_util::StreamExt;
 use mongodb::bson::{doc, Document};
 
 use crate::{
-    models::{api_request_param_model::QueryParams, custom_error_model::CustomError},
-    services::db::DataBase,
-    utils::db_helper_utils::{build_query_sort_skip, get_seconds_per_interval},
+    models::custom_error_model::CustomError,
+    services::metrics_service::timed_aggregation,
+    stores::{metrics_store::HistoryQuery, mongo_store::MongoStore},
+    utils::parser_utils::{bson_to_amount, bson_to_f64},
 };
+use tracing::warn;
 
-impl DataBase {
-    pub async fn get_pool_earnings_history_api(
-        &self,
-        params: QueryParams,
-    ) -> Result<Document, CustomError> {
-        let mut query = doc! {};
-        let QueryParams {
-            pool,
-            interval,
-            count,
-            to,
-            from,
-            page,
-            sort_by,
-            sort_order,
-            limit,
-        } = params;
-
-        let seconds_per_interval =
-            get_seconds_per_interval(interval.as_ref().unwrap_or(&"hour".to_string()).as_str());
-
-        if let Some(pool) = pool {
-            query.insert("pool", pool);
-        }
-
-        // as per midgard api if from is not specified the from has to be fixed back relative to either current timestamp or "to" timestamp (if given) or w.r.t the latest record in the collection
-        if let Some(from) = from {
-            query.insert("start_time", doc! { "$gte": from as i64 });
-        } else {
-            let calc_start = if let Some(to) = to {
-                to as i64
-            } else {
-                self.get_max_end_time(&self.earnings)
-                    .await
-                    .unwrap_or(Utc::now().timestamp())
-            };
-            let count = count.unwrap_or(400) as i64;
-            let queried_interval_duration = seconds_per_interval as i64;
-            println!(
-                "{} {} {}",
-                calc_start,
-                count * queried_interval_duration,
-                calc_start - (count * queried_interval_duration)
-            );
-            query.insert(
-                "start_time",
-                doc! {"$gte": calc_start - (count * queried_interval_duration)},
-            );
-        }
-
-        // common query building code part has been moved to a helper function
-        let (query_part, sort_filter, skip_size, _limit) =
-            build_query_sort_skip(to, sort_by, sort_order, page, limit, count).await;
-
-        // update the actual query with the query_part from builder
-        query.extend(query_part.clone());
-        println!("{}", query);
-
+impl MongoStore {
+    // /earnings intervals per pool with their earnings_summary, the meta is added by earnings_history_response
+    #[tracing::instrument(skip(self))]
+    pub(crate) async fn earnings_history_intervals(&self, query: &HistoryQuery) -> Result<Vec<Document>, CustomError> {
+        let seconds_per_interval = query.seconds_per_interval;
         let pipeline = vec![
-            doc! { "$match": query },
+            doc! { "$match": query.filter() },
             doc! {
                 "$group": {
                     "_id": {
@@ -103,10 +51,10 @@ impl DataBase {
                 "interval_start": "$_id.interval_start",
                 "pool": "$_id.pool",
                 "assetLiquidityFees": 1,
-                "earnings": 1,
+                "earning": 1,
                 "rewards": 1,
                 "runeLiquidityFees": 1,
-                "saverEarnings": 1,
+                "saverEarning": 1,
                 "totalLiquidityFeesRune": 1,
                 "earnings_summary": {
                     "avgNodeCount": "$earnings_summary.avg_node_count",
@@ -115,91 +63,83 @@ impl DataBase {
                     "earnings": "$earnings_summary.earnings",
                     "endTime": "$earnings_summary.end_time",
                     "liquidityEarnings": "$earnings_summary.liquidity_earnings",
+                    "liquidityFees": "$earnings_summary.liquidity_fees",
                     "startTime": "$earnings_summary.start_time",
                     "runePriceUSD": "$earnings_summary.rune_price_usd"
                 }
             }},
-            doc! { "$sort": sort_filter },
-            doc! { "$skip": skip_size as i64 },
-            doc! { "$limit": count.unwrap_or(27) as i64 },
+            doc! { "$sort": query.sort_filter() },
+            doc! { "$skip": query.skip },
+            doc! { "$limit": query.limit },
         ];
 
-        let mut cursor = self.earnings.aggregate(pipeline).await?;
+        let mut cursor = timed_aggregation("earnings_history", self.earnings.aggregate(pipeline)).await?;
         let mut query_response = Vec::new();
-        let mut earnings_summary = None;
-        // average of all the earning summary blocks is the meta for earnings
-        let mut meta = doc! {
-            "avgNodeCount": 0,
-            "blockRewards": 0.0,
-            "bondingEarnings": 0.0,
-            "earnings": 0.0,
-            "liquidityEarnings": 0.0,
-            "runePriceUSD": 0.0,
-        };
-        let mut count = 0;
-
         while let Some(result) = cursor.next().await {
             match result {
-                Ok(mut record) => {
-                    if let Some(earnings) = record.get("earnings_summary") {
-                        earnings_summary = Some(earnings.clone());
-                        // Accumulate sums for meta calculations
-                        if let Some(earnings_doc) = earnings.as_document() {
-                            for field in [
-                                "avgNodeCount".to_string(),
-                                "blockRewards".to_string(),
-                                "bondingEarnings".to_string(),
-                                "earnings".to_string(),
-                                "liquidityEarnings".to_string(),
-                                "runePriceUSD".to_string().to_string(),
-                            ]
-                            .iter()
-                            {
-                                if let Some(value) = earnings_doc.get_i64(field).ok() {
-                                    meta.insert(field, meta.get_i64(field).unwrap_or(0) + value);
-                                } else if let Some(value) = earnings_doc.get_f64(field).ok() {
-                                    meta.insert(field, meta.get_f64(field).unwrap_or(0.0) + value);
-                                }
-                            }
-                            count += 1;
-                        }
-                    }
-                    record.remove("earnings_summary");
-                    query_response.push(record);
-                }
-                Err(e) => eprintln!("Error fetching document: {:?}", e),
+                Ok(record) => query_response.push(record),
+                Err(e) => warn!(error = ?e, "Error fetching document"),
             }
         }
+        Ok(query_response)
+    }
+}
+
+// 1e8 scaled amounts of the summaries, averaged as integers even when older records hold doubles
+const SUMMARY_AMOUNTS: [&str; 4] = ["blockRewards", "bondingEarnings", "earnings", "liquidityEarnings"];
+const SUMMARY_RATES: [&str; 2] = ["avgNodeCount", "runePriceUSD"];
+// stored pool fields only /v2/history/earnings serves, /earnings keeps its original fields without them as does
+// its summary without liquidityFees
+const MIDGARD_POOL_FIELDS: [&str; 2] = ["earning", "saverEarning"];
 
-        // Calculate averages if count is 1 sum itself is the avg
-        if count > 1 {
-            for field in [
-                "avgNodeCount".to_string(),
-                "blockRewards".to_string(),
-                "bondingEarnings".to_string(),
-                "earnings".to_string(),
-                "liquidityEarnings".to_string(),
-                "runePriceUSD".to_string(),
-            ]
-            .iter()
-            {
-                if let Some(sum) = meta.get_i64(field).ok() {
-                    meta.insert(field, sum / count);
-                } else if let Some(sum) = meta.get_f64(field).ok() {
-                    meta.insert(field, sum / count as f64);
+pub fn earnings_history_response(intervals: Vec<Document>) -> Result<Document, CustomError> {
+    let mut pools = Vec::new();
+    let mut earnings_summary = None;
+    // average of all the earning summary blocks is the meta for earnings
+    let mut amounts = [0_i64; SUMMARY_AMOUNTS.len()];
+    let mut rates = [0.0_f64; SUMMARY_RATES.len()];
+    let mut count: i64 = 0;
+
+    for mut record in intervals {
+        if let Some(mut earnings) = record.remove("earnings_summary") {
+            // Accumulate sums for meta calculations
+            if let Some(earnings_doc) = earnings.as_document_mut() {
+                earnings_doc.remove("liquidityFees");
+                for (sum, field) in amounts.iter_mut().zip(SUMMARY_AMOUNTS) {
+                    *sum = sum.saturating_add(earnings_doc.get(field).and_then(bson_to_amount).unwrap_or(0));
+                }
+                for (sum, field) in rates.iter_mut().zip(SUMMARY_RATES) {
+                    *sum += earnings_doc.get(field).and_then(bson_to_f64).unwrap_or(0.0);
                 }
+                count += 1;
             }
+            earnings_summary = Some(earnings);
+        }
+        for field in MIDGARD_POOL_FIELDS {
+            record.remove(field);
         }
+        pools.push(record);
+    }
 
-        // since earnings route has been scaled for all pools with 7L+ records, we summarize the total reponses instead of finding individual earnings summaries like in midgard
-        let result = doc! {
-            "meta": meta,
-            "intervals": {
-                "earnings_summary": earnings_summary,
-                "pools": query_response
-            }
-        };
+    // Calculate averages if count is 1 sum itself is the avg
+    let count = count.max(1);
+    let meta = doc! {
+        "avgNodeCount": rates[0] / count as f64,
+        "blockRewards": amounts[0] / count,
+        "bondingEarnings": amounts[1] / count,
+        "earnings": amounts[2] / count,
+        "liquidityEarnings": amounts[3] / count,
+        "runePriceUSD": rates[1] / count as f64,
+    };
 
-        Ok(result)
-    }
+    // since earnings route has been scaled for all pools with 7L+ records, we summarize the total reponses instead of finding individual earnings summaries like in midgard
+    let result = doc! {
+        "meta": meta,
+        "intervals": {
+            "earnings_summary": earnings_summary,
+            "pools": pools
+        }
+    };
+
+    Ok(result)
 }
@@ -0,0 +1,81 @@
+use chrono::Utc;
+use mongodb::bson::doc;
+
+use crate::{
+    models::{custom_error_model::CustomError, health_model::{CollectionFreshness, DataFreshness}},
+    services::db::DataBase,
+    stores::metrics_store::{HistoryCollection, RecordQuery},
+};
+
+impl DataBase {
+    // /ready
+    #[tracing::instrument(skip(self))]
+    pub async fn ping_api(&self) -> Result<(), CustomError> {
+        match &self.mongodb {
+            Some(mongodb) => {
+                mongodb.database.run_command(doc! { "ping": 1 }).await?;
+            }
+            // a sqlite or postgres history alone has to answer a query
+            None => {
+                self.store.latest_end_time(HistoryCollection::Depths).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // latest end_time per configured pool, a single None pool for collections without pools (pools None),
+    // pools that are no longer ingested (or never were, midgard returns earnings for all) are left out
+    async fn get_latest_end_times(&self, collection: HistoryCollection, pools: Option<&[String]>) -> Result<Vec<(Option<String>, i64)>, CustomError> {
+        let pools: Vec<Option<String>> = match pools {
+            Some(pools) => pools.iter().cloned().map(Some).collect(),
+            None => vec![None],
+        };
+        let mut latest = Vec::new();
+        for pool in pools {
+            let query = RecordQuery { pool: pool.clone(), descending: true, limit: 1, ..RecordQuery::default() };
+            if let Some(end_time) = self.store.records(collection, &query).await?.first().and_then(|record| record.get_i64("end_time").ok()) {
+                latest.push((pool, end_time));
+            }
+        }
+        Ok(latest)
+    }
+
+    // /health/data
+    #[tracing::instrument(skip(self))]
+    pub async fn get_data_freshness_api(&self, max_lag_secs: i64, pools: &[String]) -> Result<DataFreshness, CustomError> {
+        let now = Utc::now().timestamp();
+        let latest = [
+            ("depth_history", self.get_latest_end_times(HistoryCollection::Depths, Some(pools)).await?),
+            ("swap_history", self.get_latest_end_times(HistoryCollection::Swaps, Some(pools)).await?),
+            ("earnings", self.get_latest_end_times(HistoryCollection::Earnings, Some(pools)).await?),
+            ("rune_pool_history", self.get_latest_end_times(HistoryCollection::RunePool, None).await?),
+        ];
+
+        let mut empty_collections = Vec::new();
+        let mut collections = Vec::new();
+        for (collection, end_times) in latest {
+            if end_times.is_empty() {
+                empty_collections.push(collection.to_string());
+            }
+            for (pool, latest_end_time) in end_times {
+                // the last midgard interval is still open and ends in the future
+                let lag_secs = (now - latest_end_time).max(0);
+                collections.push(CollectionFreshness {
+                    collection: collection.to_string(),
+                    pool,
+                    latest_end_time,
+                    lag_secs,
+                    stale: lag_secs > max_lag_secs,
+                });
+            }
+        }
+        let stale = !empty_collections.is_empty() || collections.iter().any(|freshness| freshness.stale);
+        Ok(DataFreshness {
+            status: if stale { "stale" } else { "ok" }.to_string(),
+            max_lag_secs,
+            checked_at: now,
+            empty_collections,
+            collections,
+        })
+    }
+}
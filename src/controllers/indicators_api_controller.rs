@@ -0,0 +1,123 @@
+use chrono::Utc;
+use mongodb::bson::{doc, Bson, Document};
+
+use crate::{
+    models::{custom_error_model::CustomError, indicator_model::{parse_indicators, resolve_metric, Indicator, IndicatorParams}},
+    services::db::DataBase,
+    stores::metrics_store::RecordQuery,
+    utils::{
+        db_helper_utils::{get_interval_start, get_seconds_per_interval},
+        indicator_utils::{bollinger, ema, rsi, sma, volatility},
+        parser_utils::bson_to_f64,
+    },
+};
+
+fn to_bson(value: Option<f64>) -> Bson {
+    value.map(Bson::Double).unwrap_or(Bson::Null)
+}
+
+impl DataBase {
+    // /indicators
+    #[tracing::instrument(skip(self))]
+    pub async fn get_indicators_api(&self, params: IndicatorParams) -> Result<Document, CustomError> {
+        let IndicatorParams {
+            metric,
+            pool,
+            interval,
+            indicators,
+            from,
+            to,
+            count,
+        } = params;
+
+        let interval = interval.unwrap_or("hour".to_string());
+        let seconds_per_interval = get_seconds_per_interval(&interval) as i64;
+        let count = count.unwrap_or(400) as i64;
+        let indicators = parse_indicators(&indicators)?;
+        let (collection, field, accumulator) = resolve_metric(&metric)
+            .ok_or(CustomError::InvalidInput(format!("Unsupported metric {}", metric)))?;
+        let collection = collection.history_collection();
+
+        // same from fallback as the history endpoints, count intervals back from to or the latest record
+        let from = match from {
+            Some(from) => from as i64,
+            None => {
+                let calc_start = match to {
+                    Some(to) => to as i64,
+                    None => self.store.latest_end_time(collection).await.ok().flatten().unwrap_or(Utc::now().timestamp()),
+                };
+                calc_start - count * seconds_per_interval
+            }
+        };
+        // fetch enough earlier intervals to warm up the longest indicator so the first returned point has values
+        let warm_up = indicators.iter().map(|indicator| indicator.period()).max().unwrap_or(0) as i64 + 1;
+        let query = RecordQuery {
+            pool: Some(pool.clone()),
+            from: Some(from - warm_up * seconds_per_interval),
+            to: to.map(|to| to as i64),
+            limit: i64::MAX,
+            ..RecordQuery::default()
+        };
+
+        // one point per interval, records come oldest first so the last one of a bucket is its level
+        let mut points: Vec<Document> = Vec::new();
+        for record in self.store.records(collection, &query).await? {
+            let Ok(end_time) = record.get_i64("end_time") else {
+                continue;
+            };
+            let value = record.get(field).and_then(bson_to_f64).unwrap_or(0.0);
+            let start_time = get_interval_start(end_time, seconds_per_interval);
+            match points.last_mut() {
+                Some(point) if point.get_i64("startTime").ok() == Some(start_time) => {
+                    let value = if accumulator == "$sum" { point.get_f64("value").unwrap_or(0.0) + value } else { value };
+                    point.insert("value", value);
+                }
+                _ => points.push(doc! { "startTime": start_time, "endTime": start_time + seconds_per_interval, "value": value }),
+            }
+        }
+        let values = points
+            .iter()
+            .map(|point| point.get_f64("value").unwrap_or(0.0))
+            .collect::<Vec<f64>>();
+
+        // compute every requested series over the full (warm-up included) window
+        let mut series: Vec<(String, Vec<Option<f64>>)> = Vec::new();
+        for indicator in &indicators {
+            match *indicator {
+                Indicator::Sma(period) => series.push((format!("sma{}", period), sma(&values, period))),
+                Indicator::Ema(period) => series.push((format!("ema{}", period), ema(&values, period))),
+                Indicator::Rsi(period) => series.push((format!("rsi{}", period), rsi(&values, period))),
+                Indicator::Bollinger(period) => {
+                    let bands = bollinger(&values, period, 2.0);
+                    series.push((format!("bollinger{}Upper", period), bands.upper));
+                    series.push((format!("bollinger{}Middle", period), bands.middle));
+                    series.push((format!("bollinger{}Lower", period), bands.lower));
+                }
+                Indicator::Volatility(period) => series.push((format!("volatility{}", period), volatility(&values, period))),
+            }
+        }
+
+        let mut query_response = Vec::new();
+        for (i, mut point) in points.into_iter().enumerate() {
+            if point.get_i64("startTime").unwrap_or(0) < from {
+                continue;
+            }
+            for (name, values) in &series {
+                point.insert(name, to_bson(values[i]));
+            }
+            query_response.push(point);
+        }
+        query_response.truncate(count as usize);
+
+        let response = doc! {
+            "meta": {
+                "metric": metric,
+                "pool": pool,
+                "interval": interval,
+                "series": series.iter().map(|(name, _)| name.clone()).collect::<Vec<String>>()
+            },
+            "intervals": query_response
+        };
+        Ok(response)
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+
+use mongodb::bson::{Bson, Document};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    models::{api_request_param_model::QueryParams, custom_error_model::CustomError},
+    services::{depth_history_service, earnings_history_service, rune_pool_service, swap_history_service},
+    stores::metrics_store::{HistoryCollection, HistoryQuery, MetricsStore, EARNING_FIELDS, EARNING_SUMMARY_FIELDS, SWAP_FIELDS},
+};
+
+// midgard answers at most 400 intervals, like the count param
+const MAX_INTERVALS: i64 = 400;
+
+// the /v2/history routes answer like midgard: ascending intervals, every number as a string,
+// serialized through the midgard response structs the ingestion parses so fields and their order match
+
+fn number(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Double(value) => Some(*value),
+        Bson::Int32(value) => Some(*value as f64),
+        Bson::Int64(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
+// integers without a fraction like midgard, f64 display never uses an exponent
+fn midgard_string(value: &Bson) -> Option<String> {
+    match value {
+        Bson::Int32(value) => Some(value.to_string()),
+        Bson::Int64(value) => Some(value.to_string()),
+        Bson::Double(value) if value.is_finite() => Some(value.to_string()),
+        Bson::String(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn field(document: &Document, name: &str) -> Option<f64> {
+    document.get(name).and_then(number)
+}
+
+// fields midgard does not know, like pool or interval_start, are dropped by the structs
+fn midgard_object(document: &Document) -> Map<String, Value> {
+    document
+        .iter()
+        .filter_map(|(name, value)| Some((name.clone(), Value::String(midgard_string(value)?))))
+        .collect()
+}
+
+// a field the stored intervals lack fails here instead of being sent as something midgard never returns
+fn midgard_struct<T: DeserializeOwned>(object: Map<String, Value>) -> Result<T, CustomError> {
+    serde_json::from_value(Value::Object(object))
+        .map_err(|e| CustomError::DatabaseError(format!("Incomplete interval for the midgard response {}", e)))
+}
+
+fn insert_number(object: &mut Map<String, Value>, name: &str, value: f64) {
+    object.insert(name.to_string(), Value::String(value.to_string()));
+}
+
+// integer amounts add up exactly, a double on either side makes the total a double
+fn add(total: Bson, value: &Bson) -> Bson {
+    match (&total, value) {
+        (Bson::Int64(total), Bson::Int64(value)) => total.checked_add(*value).map(Bson::Int64),
+        (Bson::Int64(total), Bson::Int32(value)) => total.checked_add(*value as i64).map(Bson::Int64),
+        _ => None,
+    }
+    .unwrap_or_else(|| Bson::Double(number(&total).unwrap_or(0.0) + number(value).unwrap_or(0.0)))
+}
+
+fn total<'a>(values: impl Iterator<Item = &'a Bson>) -> Bson {
+    values.filter(|value| number(value).is_some()).fold(Bson::Int64(0), add)
+}
+
+fn insert_total(object: &mut Map<String, Value>, name: &str, total: &Bson) {
+    if let Some(total) = midgard_string(total) {
+        object.insert(name.to_string(), Value::String(total));
+    }
+}
+
+fn ratio(end: f64, start: f64) -> f64 {
+    if start == 0.0 { 0.0 } else { end / start }
+}
+
+// the requested range, from the start of the first interval to the end of the last
+fn window(query: &HistoryQuery) -> (i64, i64) {
+    (query.from, query.to.unwrap_or_else(|| query.from + query.limit * query.seconds_per_interval as i64))
+}
+
+// midgard answers a range without intervals with zeros over the requested window
+fn empty_meta<T: Default + Serialize + DeserializeOwned>(query: &HistoryQuery) -> Result<T, CustomError> {
+    let Ok(Value::Object(mut meta)) = serde_json::to_value(T::default()) else {
+        return Err(CustomError::StandardError("Midgard meta is not an object".to_string()));
+    };
+    for value in meta.values_mut() {
+        if value.as_str() == Some("") {
+            *value = Value::String("0".to_string());
+        }
+    }
+    let (start_time, end_time) = window(query);
+    meta.insert("startTime".to_string(), Value::String(start_time.to_string()));
+    meta.insert("endTime".to_string(), Value::String(end_time.to_string()));
+    midgard_struct(meta)
+}
+
+fn midgard_intervals<T: DeserializeOwned>(intervals: &[Document]) -> Result<Vec<T>, CustomError> {
+    intervals.iter().map(|interval| midgard_struct(midgard_object(interval))).collect()
+}
+
+// copies (response field, interval field) of the first or last interval into the meta
+fn copy_meta(meta: &mut Map<String, Value>, interval: &Document, fields: &[(&str, &str)]) {
+    for (name, source) in fields {
+        if let Some(value) = interval.get(*source).and_then(midgard_string) {
+            meta.insert(name.to_string(), Value::String(value));
+        }
+    }
+}
+
+// buckets of the query in midgard order with the query, earnings are limited by the range instead of their pool rows
+pub async fn midgard_history_intervals(store: &dyn MetricsStore, collection: HistoryCollection, params: QueryParams) -> Result<(Vec<Document>, HistoryQuery), CustomError> {
+    let params = QueryParams { sort_by: Some("startTime".to_string()), sort_order: Some(1), page: None, limit: None, ..params };
+    let mut query = store.history_query(collection, params).await?;
+    if collection == HistoryCollection::Earnings {
+        // every pool row of an interval is needed, the range is capped at MAX_INTERVALS intervals instead
+        let last_end_time = query.from + MAX_INTERVALS * query.seconds_per_interval as i64;
+        query.to = Some(window(&query).1.min(last_end_time));
+        query.limit = i64::MAX;
+    }
+    let intervals = store.history_intervals(collection, &query).await?;
+    Ok((intervals, query))
+}
+
+// /v2/history/depths/{pool}
+pub fn midgard_depths_response(intervals: &[Document], query: &HistoryQuery) -> Result<depth_history_service::ApiResponse, CustomError> {
+    let (Some(first), Some(last)) = (intervals.first(), intervals.last()) else {
+        return Ok(depth_history_service::ApiResponse { meta: empty_meta(query)?, intervals: Vec::new() });
+    };
+    let mut meta = Map::new();
+    copy_meta(&mut meta, last, &[
+        ("endAssetDepth", "assetDepth"),
+        ("endLPUnits", "units"),
+        ("endMemberCount", "membersCount"),
+        ("endRuneDepth", "runeDepth"),
+        ("endSynthUnits", "synthUnits"),
+        ("endTime", "endTime"),
+    ]);
+    copy_meta(&mut meta, first, &[
+        ("startAssetDepth", "assetDepth"),
+        ("startLPUnits", "units"),
+        ("startMemberCount", "membersCount"),
+        ("startRuneDepth", "runeDepth"),
+        ("startSynthUnits", "synthUnits"),
+        ("startTime", "startTime"),
+    ]);
+    // midgard reports the luvi growth and the impermanent loss of the price change as ratios
+    insert_number(&mut meta, "luviIncrease", ratio(field(last, "luvi").unwrap_or(0.0), field(first, "luvi").unwrap_or(0.0)));
+    let price_ratio = ratio(field(last, "assetPrice").unwrap_or(0.0), field(first, "assetPrice").unwrap_or(0.0));
+    insert_number(&mut meta, "priceShiftLoss", 2.0 * price_ratio.sqrt() / (1.0 + price_ratio));
+    Ok(depth_history_service::ApiResponse { meta: midgard_struct(meta)?, intervals: midgard_intervals(intervals)? })
+}
+
+// /v2/history/swaps, counts, volumes and fees are summed, slips weighted by their swap counts
+pub fn midgard_swaps_response(intervals: &[Document], query: &HistoryQuery) -> Result<swap_history_service::ApiResponse, CustomError> {
+    let (Some(first), Some(last)) = (intervals.first(), intervals.last()) else {
+        return Ok(swap_history_service::ApiResponse { meta: empty_meta(query)?, intervals: Vec::new() });
+    };
+    let sum = |name: &str| intervals.iter().filter_map(|interval| field(interval, name)).sum::<f64>();
+    let mut meta = Map::new();
+    copy_meta(&mut meta, first, &[("startTime", "startTime")]);
+    copy_meta(&mut meta, last, &[("endTime", "endTime")]);
+    for (name, _) in SWAP_FIELDS {
+        let slip_count = match name.strip_suffix("AverageSlip") {
+            Some(swap_type) => Some(format!("{}Count", swap_type)),
+            None if *name == "averageSlip" => Some("totalCount".to_string()),
+            None => None,
+        };
+        match slip_count {
+            Some(count) => {
+                let weighted = intervals.iter().filter_map(|interval| Some(field(interval, name)? * field(interval, &count)?)).sum();
+                insert_number(&mut meta, name, ratio(weighted, sum(&count)));
+            }
+            None if *name == "runePriceUSD" => insert_number(&mut meta, name, field(last, name).unwrap_or(0.0)),
+            None => insert_total(&mut meta, name, &total(intervals.iter().filter_map(|interval| interval.get(*name)))),
+        }
+    }
+    Ok(swap_history_service::ApiResponse { meta: midgard_struct(meta)?, intervals: midgard_intervals(intervals)? })
+}
+
+// midgard calls the stored earning of a pool earnings
+fn midgard_earning_name(name: &str) -> &str {
+    if name == "earning" { "earnings" } else { name }
+}
+
+// /v2/history/earnings, the pool rows of a bucket become one interval with its summary
+pub fn midgard_earnings_response(intervals: &[Document], query: &HistoryQuery) -> Result<earnings_history_service::ApiResponse, CustomError> {
+    let seconds_per_interval = query.seconds_per_interval as i64;
+    let mut buckets: Vec<(i64, Vec<&Document>)> = Vec::new();
+    for record in intervals {
+        let interval_start = field(record, "interval_start").unwrap_or(0.0) as i64;
+        match buckets.last_mut() {
+            Some((start, records)) if *start == interval_start => records.push(record),
+            _ => buckets.push((interval_start, vec![record])),
+        }
+    }
+    let (Some((first_start, _)), Some((last_start, _))) = (buckets.first(), buckets.last()) else {
+        return Ok(earnings_history_service::ApiResponse { meta: empty_meta(query)?, intervals: Vec::new() });
+    };
+    let (start_time, end_time) = (first_start - first_start % seconds_per_interval, last_start - last_start % seconds_per_interval + seconds_per_interval);
+
+    let mut summaries = Vec::new();
+    let mut pool_totals: BTreeMap<String, BTreeMap<&str, Bson>> = BTreeMap::new();
+    let mut midgard_intervals = Vec::new();
+    for (interval_start, records) in &buckets {
+        let summary = records[0].get_document("earnings_summary").cloned().unwrap_or_default();
+        let mut interval = midgard_object(&summary);
+        let interval_start = interval_start - interval_start % seconds_per_interval;
+        interval.insert("startTime".to_string(), Value::String(interval_start.to_string()));
+        interval.insert("endTime".to_string(), Value::String((interval_start + seconds_per_interval).to_string()));
+        let mut pools = Vec::new();
+        for record in records {
+            let Ok(pool) = record.get_str("pool") else {
+                continue;
+            };
+            let totals = pool_totals.entry(pool.to_string()).or_default();
+            for (name, _) in EARNING_FIELDS {
+                if let Some(value) = record.get(*name) {
+                    let name = midgard_earning_name(name);
+                    let total = totals.remove(name).unwrap_or(Bson::Int64(0));
+                    totals.insert(name, add(total, value));
+                }
+            }
+            let mut pool = midgard_object(record);
+            if let Some(earning) = pool.remove("earning") {
+                pool.insert("earnings".to_string(), earning);
+            }
+            pools.push(Value::Object(pool));
+        }
+        interval.insert("pools".to_string(), Value::Array(pools));
+        midgard_intervals.push(midgard_struct(interval)?);
+        summaries.push(summary);
+    }
+
+    let mut meta = Map::new();
+    meta.insert("startTime".to_string(), Value::String(start_time.to_string()));
+    meta.insert("endTime".to_string(), Value::String(end_time.to_string()));
+    for (name, _) in EARNING_SUMMARY_FIELDS.iter().filter(|(name, _)| *name != "startTime" && *name != "endTime") {
+        let total = total(summaries.iter().filter_map(|summary| summary.get(*name)));
+        match *name {
+            "avgNodeCount" => insert_number(&mut meta, name, number(&total).unwrap_or(0.0) / summaries.len() as f64),
+            "runePriceUSD" => insert_number(&mut meta, name, summaries.last().and_then(|summary| field(summary, name)).unwrap_or(0.0)),
+            _ => insert_total(&mut meta, name, &total),
+        }
+    }
+    let pools = pool_totals
+        .into_iter()
+        .map(|(pool, totals)| {
+            let mut object = Map::new();
+            object.insert("pool".to_string(), Value::String(pool));
+            for (name, total) in totals {
+                insert_total(&mut object, name, &total);
+            }
+            Value::Object(object)
+        })
+        .collect();
+    meta.insert("pools".to_string(), Value::Array(pools));
+    Ok(earnings_history_service::ApiResponse { meta: midgard_struct(meta)?, intervals: midgard_intervals })
+}
+
+// /v2/history/runepool
+pub fn midgard_rune_pool_response(intervals: &[Document], query: &HistoryQuery) -> Result<rune_pool_service::ApiResponse, CustomError> {
+    let (Some(first), Some(last)) = (intervals.first(), intervals.last()) else {
+        return Ok(rune_pool_service::ApiResponse { meta: empty_meta(query)?, intervals: Vec::new() });
+    };
+    let mut meta = Map::new();
+    copy_meta(&mut meta, last, &[("endCount", "count"), ("endTime", "endTime"), ("endUnits", "units")]);
+    copy_meta(&mut meta, first, &[("startCount", "count"), ("startTime", "startTime"), ("startUnits", "units")]);
+    Ok(rune_pool_service::ApiResponse { meta: midgard_struct(meta)?, intervals: midgard_intervals(intervals)? })
+}
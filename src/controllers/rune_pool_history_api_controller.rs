@@ -1,58 +1,20 @@
-use chrono::Utc;
 use futures_util::StreamExt;
 use mongodb::bson::{doc, Document};
 
-use crate::{models::{api_request_param_model::QueryParams, custom_error_model::CustomError}, services::db::DataBase, utils::db_helper_utils::{build_query_sort_skip, get_seconds_per_interval}};
+use crate::{
+    models::custom_error_model::CustomError,
+    services::metrics_service::timed_aggregation,
+    stores::{metrics_store::HistoryQuery, mongo_store::MongoStore},
+};
+use tracing::warn;
 
-impl DataBase{
-    pub async fn get_rune_pool_history_api(
-        &self,
-        params: QueryParams,
-    ) -> Result<Document, CustomError> {
-        let mut query = doc! {};
-        let QueryParams {
-            pool,
-            interval,
-            count,
-            to,
-            from,
-            page,
-            sort_by,
-            sort_order,
-            limit,
-        } = params;
-
-        let seconds_per_interval = get_seconds_per_interval(interval.as_ref().unwrap_or(&"hour".to_string()).as_str());
-
-        if let Some(_pool) = pool {
-            return Err(CustomError::InvalidInput(
-                "Invalid parameter pool!".to_string(),
-            ));
-        }
-        
-        // as per midgard api if from is not specified the from has to be fixed back relative to either current timestamp or "to" timestamp (if given) or w.r.t the latest record in the collection
-        if let Some(from) = from {
-            query.insert("start_time", doc! { "$gte": from as i64 });
-        } else {
-            let calc_start = if let Some(to) = to {
-                to as i64
-            } else {
-                Utc::now().timestamp() as i64
-            };
-            let count = count.unwrap_or(400) as i64;
-            let queried_interval_duration = seconds_per_interval as i64;
-            query.insert(
-                "start_time",
-                doc! {"$gte": calc_start-(count*queried_interval_duration) as i64},
-            );
-        }
-        // common query building code part has been moved to a helper function
-        let (query_part, sort_filter, skip_size, _limit) = build_query_sort_skip(to, sort_by, sort_order, page, limit, count).await;
-        // update the actual query with the query_part from builder
-        query.extend(query_part.clone());
-        println!("{}",query);
+impl MongoStore {
+    // /runepool intervals, the meta is added by rune_pool_history_response
+    #[tracing::instrument(skip(self))]
+    pub(crate) async fn rune_pool_history_intervals(&self, query: &HistoryQuery) -> Result<Vec<Document>, CustomError> {
+        let seconds_per_interval = query.seconds_per_interval;
         let pipeline = vec![
-            doc! { "$match": query }, // Match stage
+            doc! { "$match": query.filter() },
             doc! {
                 "$group": {
                     "_id": {
@@ -84,13 +46,12 @@ impl DataBase{
                 "count" : 1,
                 "units" : 1
             }},
-            doc! { "$sort": sort_filter },
-            doc! { "$skip": skip_size as i64 },
-            doc! { "$limit": count.unwrap_or(400) as i64 },
+            doc! { "$sort": query.sort_filter() },
+            doc! { "$skip": query.skip },
+            doc! { "$limit": query.limit },
         ];
 
-        
-        let mut cursor = self.rune_pool_history.aggregate(pipeline).await?;
+        let mut cursor = timed_aggregation("rune_pool_history", self.rune_pool_history.aggregate(pipeline)).await?;
         let mut query_response = Vec::new();
         while let Some(result) = cursor.next().await {
             match result {
@@ -98,23 +59,27 @@ impl DataBase{
                     record.remove("_id");
                     query_response.push(record);
                 }
-                Err(e) => eprintln!("Error fetching document: {:?}", e),
+                Err(e) => warn!(error = ?e, "Error fetching document"),
             }
         }
-        let first = query_response.first().unwrap();
-        let last = query_response.last().unwrap();
-        let response = doc! {
-            "meta": {
-                "endCount" : last.get("count").unwrap().to_string(),
-                "endTime" : last.get("endTime").unwrap().to_string(),
-                "endUnits" : last.get("units").unwrap().to_string(),
-                "startCount" : first.get("count").unwrap().to_string(),
-                "startTime" : first.get("startTime").unwrap().to_string(),
-                "startUnits" : first.get("units").unwrap().to_string()
-            },
-            "intervals": query_response
-        };
-        // println!("{:?}",query_response);
-        Ok(response)
+        Ok(query_response)
     }
-}
\ No newline at end of file
+}
+
+pub fn rune_pool_history_response(intervals: Vec<Document>) -> Result<Document, CustomError> {
+    let (Some(first), Some(last)) = (intervals.first(), intervals.last()) else {
+        return Err(CustomError::InvalidInput("No intervals found for the requested range".to_string()));
+    };
+    let response = doc! {
+        "meta": {
+            "endCount" : last.get("count").unwrap().to_string(),
+            "endTime" : last.get("endTime").unwrap().to_string(),
+            "endUnits" : last.get("units").unwrap().to_string(),
+            "startCount" : first.get("count").unwrap().to_string(),
+            "startTime" : first.get("startTime").unwrap().to_string(),
+            "startUnits" : first.get("units").unwrap().to_string()
+        },
+        "intervals": intervals
+    };
+    Ok(response)
+}
@@ -0,0 +1,70 @@
+use crate::{
+    models::{
+        custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory, earning_history_model::PoolEarningHistory, rune_pool_model::RunePool,
+        stream_event_model::{StreamCursor, StreamEvent, Subscription}, swap_history_model::SwapHistory,
+    },
+    services::db::DataBase,
+    stores::metrics_store::{records_as, HistoryCollection, RecordQuery},
+};
+
+// replay is bounded like every other history response, past it the client gets a gap event to resume from
+const MAX_REPLAY_EVENTS: i64 = 400;
+
+impl DataBase {
+    // stored intervals after the cursor for the subscriptions, oldest first, used to resume a /stream connection,
+    // a cut backlog ends with a gap event whose id is where the client resumes
+    #[tracing::instrument(skip(self))]
+    pub async fn get_stream_backlog_api(&self, subscriptions: &[Subscription], cursor: &StreamCursor) -> Result<Vec<StreamEvent>, CustomError> {
+        // an event id resumes within its end_time
+        let after = if cursor.event.is_some() { cursor.end_time - 1 } else { cursor.end_time };
+        // one more than replayed tells whether the backlog was cut
+        let limit = MAX_REPLAY_EVENTS + 1;
+        let mut events = Vec::new();
+        // the end_time from which a subscription's records were left out
+        let mut horizon: Option<i64> = None;
+        for subscription in subscriptions {
+            let query = RecordQuery { pool: subscription.pool.clone(), after: Some(after), limit, ..RecordQuery::default() };
+            let records = match subscription.topic.as_str() {
+                "swaps" => {
+                    let records: Vec<SwapHistory> = records_as(self.store.records(HistoryCollection::Swaps, &query).await?)?;
+                    records.iter().map(|record| StreamEvent::from_record("swaps", Some(&record.pool), record.end_time, record)).collect()
+                }
+                "depths" => {
+                    let records: Vec<PoolDepthPriceHistory> = records_as(self.store.records(HistoryCollection::Depths, &query).await?)?;
+                    records.iter().map(|record| StreamEvent::from_record("depths", Some(&record.pool), record.end_time, record)).collect()
+                }
+                "earnings" => {
+                    let records: Vec<PoolEarningHistory> = records_as(self.store.records(HistoryCollection::Earnings, &query).await?)?;
+                    records.iter().map(|record| StreamEvent::from_record("earnings", Some(&record.pool), record.end_time, record)).collect()
+                }
+                "runepool" => {
+                    let records: Vec<RunePool> = records_as(self.store.records(HistoryCollection::RunePool, &query).await?)?;
+                    records.iter().map(|record| StreamEvent::from_record("runepool", None, record.end_time, record)).collect()
+                }
+                _ => Vec::new(),
+            };
+            if records.len() as i64 == limit {
+                let last = records.last().map_or(i64::MAX, |event: &StreamEvent| event.end_time);
+                horizon = Some(horizon.map_or(last, |horizon| horizon.min(last)));
+            }
+            events.extend(records.into_iter().filter(|event| cursor.precedes(event)));
+        }
+        events.sort_by(|a, b| a.order_key().cmp(&b.order_key()));
+        // the pools of one end_time are not fetched in replay order, so a cut subscription is only replayed for whole end_times
+        let mut replayed = match horizon {
+            Some(horizon) => events.partition_point(|event| event.end_time < horizon),
+            None => events.len(),
+        };
+        if replayed == 0 {
+            replayed = events.len();
+        }
+        let replayed = replayed.min(MAX_REPLAY_EVENTS as usize);
+        if horizon.is_some() || replayed < events.len() {
+            events.truncate(replayed);
+            if let Some(last) = events.last() {
+                events.push(StreamEvent::gap(last, MAX_REPLAY_EVENTS));
+            }
+        }
+        Ok(events)
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    models::{custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory, swap_history_model::SwapHistory, swap_quote_model::{SwapQuote, SwapQuoteParams}},
+    services::db::DataBase,
+    stores::metrics_store::{records_as, HistoryCollection, RecordQuery},
+    utils::{constants::RUNE_ASSET, pool_math_utils::get_swap_leg},
+};
+
+impl DataBase {
+    // latest stored record of the pool at or before the given timestamp
+    async fn get_record_at<T: DeserializeOwned>(&self, collection: HistoryCollection, pool: &str, at: i64) -> Result<Option<T>, CustomError> {
+        let query = RecordQuery { pool: Some(pool.to_string()), to: Some(at), descending: true, limit: 1, ..RecordQuery::default() };
+        Ok(records_as(self.store.records(collection, &query).await?)?.pop())
+    }
+
+    async fn get_pool_depth_at(&self, pool: &str, at: i64) -> Result<PoolDepthPriceHistory, CustomError> {
+        match self.get_record_at(HistoryCollection::Depths, pool, at).await? {
+            Some(depth) => Ok(depth),
+            None => Err(CustomError::InvalidInput(format!("No depth history available for pool {} at {}", pool, at))),
+        }
+    }
+
+    // /tools/swap-quote
+    #[tracing::instrument(skip(self))]
+    pub async fn get_swap_quote_api(&self, params: SwapQuoteParams) -> Result<SwapQuote, CustomError> {
+        let SwapQuoteParams { from, to, amount, at } = params;
+        let at = at.map(|at| at as i64).unwrap_or(Utc::now().timestamp());
+
+        // rune -> asset and asset -> rune are single swaps, asset -> asset is routed through rune as a double swap
+        let (expected_output, fees, slip, depth_end_time) = if from == RUNE_ASSET {
+            let depth = self.get_pool_depth_at(&to, at).await?;
+            let leg = get_swap_leg(amount, depth.rune_depth as f64, depth.asset_depth as f64);
+            (leg.output, leg.fee, leg.slip, depth.end_time)
+        } else if to == RUNE_ASSET {
+            let depth = self.get_pool_depth_at(&from, at).await?;
+            let leg = get_swap_leg(amount, depth.asset_depth as f64, depth.rune_depth as f64);
+            (leg.output, leg.fee, leg.slip, depth.end_time)
+        } else {
+            let from_depth = self.get_pool_depth_at(&from, at).await?;
+            let to_depth = self.get_pool_depth_at(&to, at).await?;
+            let first_leg = get_swap_leg(amount, from_depth.asset_depth as f64, from_depth.rune_depth as f64);
+            let second_leg = get_swap_leg(first_leg.output, to_depth.rune_depth as f64, to_depth.asset_depth as f64);
+            // first leg fee is in rune, convert it to the output asset with the second pool's price
+            let first_leg_fee = if to_depth.rune_depth == 0 {
+                0.0
+            } else {
+                first_leg.fee * to_depth.asset_depth as f64 / to_depth.rune_depth as f64
+            };
+            (
+                second_leg.output,
+                first_leg_fee + second_leg.fee,
+                first_leg.slip + second_leg.slip,
+                from_depth.end_time.min(to_depth.end_time),
+            )
+        };
+
+        // midgard reports average_slip in basis points for the non rune side of the swap
+        let observed_pool = if from == RUNE_ASSET { &to } else { &from };
+        let observed_average_slip_bps = self
+            .get_record_at::<SwapHistory>(HistoryCollection::Swaps, observed_pool, at)
+            .await?
+            .map(|swap| swap.average_slip);
+
+        Ok(SwapQuote {
+            from,
+            to,
+            amount,
+            depth_end_time,
+            expected_output,
+            fees,
+            slip_bps: slip * 10_000.0,
+            observed_average_slip_bps,
+        })
+    }
+}
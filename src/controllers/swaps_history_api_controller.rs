@@ -1,71 +1,20 @@
-use chrono::Utc;
 use futures_util::StreamExt;
 use mongodb::bson::{doc, Document};
 
-use crate::{models::{api_request_param_model::QueryParams, custom_error_model::CustomError}, services::db::DataBase, utils::db_helper_utils::{build_query_sort_skip, get_seconds_per_interval}};
+use crate::{
+    models::custom_error_model::CustomError,
+    services::metrics_service::timed_aggregation,
+    stores::{metrics_store::HistoryQuery, mongo_store::MongoStore},
+};
+use tracing::warn;
 
-// /swaps
-impl DataBase{
-    pub async fn get_swaps_history_api(
-        &self,
-        params: QueryParams,
-    ) -> Result<Document, CustomError> {
-        let mut query = doc! {};
-        let QueryParams {
-            pool,
-            interval,
-            count,
-            to,
-            from,
-            page,
-            sort_by,
-            sort_order,
-            limit,
-        } = params;
-    
-        let seconds_per_interval = get_seconds_per_interval(interval.as_ref().unwrap_or(&"hour".to_string()).as_str());
-    
-    
-        if let Some(pool) = pool {
-            // for now due to volume and computation constraints our swap history is confined to only BTC.BTC pool
-            if pool != "BTC.BTC" {
-                return Err(CustomError::InvalidInput(
-                    "Swap history for only BTC.BTC available".to_string(),
-                ));
-            }
-            query.insert("pool", pool);
-        }
-    
-        // as per midgard api if from is not specified the from has to be fixed back relative to either current timestamp or "to" timestamp (if given) or w.r.t the latest record in the collection
-        if let Some(from) = from {
-            query.insert("start_time", doc! { "$gte": from as i64 });
-        } else {
-            let calc_start = if let Some(to) = to {
-                to as i64
-            } else {
-                self.get_max_end_time(&self.swap_history)
-                    .await
-                    .unwrap_or(Utc::now().timestamp())
-            };
-            let count = count.unwrap_or(400) as i64;
-            let queried_interval_duration = seconds_per_interval as i64;
-            println!(
-                "{} {} {}",
-                calc_start,
-                count * queried_interval_duration,
-                calc_start - (count * queried_interval_duration)
-            );
-            query.insert(
-                "start_time",
-                doc! {"$gte": calc_start-(count*queried_interval_duration) as i64},
-            );
-        }
-        // common query building code part has been moved to a helper function
-        let (query_part, sort_filter, skip_size, _limit) = build_query_sort_skip(to, sort_by, sort_order, page, limit, count).await;
-        // update the actual query with the query_part from builder
-        query.extend(query_part.clone());
+impl MongoStore {
+    // /swaps intervals, the meta is added by swaps_history_response
+    #[tracing::instrument(skip(self))]
+    pub(crate) async fn swaps_history_intervals(&self, query: &HistoryQuery) -> Result<Vec<Document>, CustomError> {
+        let seconds_per_interval = query.seconds_per_interval;
         let pipeline = vec![
-            doc! { "$match": query }, // Match stage
+            doc! { "$match": query.filter() },
             doc! {
                 "$group": {
                     "_id": {
@@ -167,12 +116,12 @@ impl DataBase{
                 "totalVolume": 1,
                 "totalVolumeUSD": 1
             }},
-            doc! { "$sort": sort_filter },
-            doc! { "$skip": skip_size as i64 },
-            doc! { "$limit": count.unwrap_or(400) as i64 },
+            doc! { "$sort": query.sort_filter() },
+            doc! { "$skip": query.skip },
+            doc! { "$limit": query.limit },
         ];
     
-        let mut cursor = self.swap_history.aggregate(pipeline).await?;
+        let mut cursor = timed_aggregation("swaps_history", self.swap_history.aggregate(pipeline)).await?;
         let mut query_response = Vec::new();
         while let Some(result) = cursor.next().await {
             match result {
@@ -180,55 +129,58 @@ impl DataBase{
                     record.remove("_id");
                     query_response.push(record);
                 }
-                Err(e) => eprintln!("Error fetching document: {:?}", e),
+                Err(e) => warn!(error = ?e, "Error fetching document"),
             }
         }
-        // no graceful error handling since any unwrap_or would result in wrong meta results
-        let first = query_response.first().unwrap();
-        let last = query_response.last().unwrap();
-        let response = doc! {
-            "meta": {
-                "endTime": last.get("endTime"),
-                "fromTradeAverageSlip": last.get("fromTradeAverageSlip"),
-                "fromTradeCount": last.get("fromTradeCount"),
-                "fromTradeFees": last.get("fromTradeFees"),
-                "fromTradeVolume": last.get("fromTradeVolume"),
-                "fromTradeVolumeUSD": last.get("fromTradeVolumeUSD"),
-                "runePriceUSD": last.get("runePriceUSD"),
-                "startTime": first.get("startTime"),
-                "synthMintAverageSlip": last.get("synthRedeemAverageSlip"),
-                "synthMintCount": last.get("synthMintCount"),
-                "synthMintFees": last.get("synthMintFees"),
-                "synthMintVolume": last.get("synthMintVolume"),
-                "synthMintVolumeUSD": last.get("synthMintVolumeUSD"),
-                "synthRedeemAverageSlip": last.get("synthRedeemAverageSlip"),
-                "synthRedeemCount": last.get("synthRedeemCount"),
-                "synthRedeemFees": last.get("synthRedeemFees"),
-                "synthRedeemVolume": last.get("synthRedeemVolume"),
-                "synthRedeemVolumeUSD": last.get("synthRedeemVolumeUSD"),
-                "toAssetAverageSlip": last.get("toAssetAverageSlip"),
-                "toAssetCount": last.get("toAssetCount"),
-                "toAssetFees": last.get("toAssetFees"),
-                "toAssetVolume": last.get("toAssetVolume"),
-                "toAssetVolumeUSD": last.get("toAssetVolumeUSD"),
-                "toRuneAverageSlip": last.get("toRuneAverageSlip"),
-                "toRuneCount": last.get("toRuneCount"),
-                "toRuneFees": last.get("toRuneFees"),
-                "toRuneVolume": last.get("toRuneVolume"),
-                "toRuneVolumeUSD": last.get("toRuneVolumeUSD"),
-                "toTradeAverageSlip": last.get("toTradeAverageSlip"),
-                "toTradeCount": last.get("toTradeCount"),
-                "toTradeFees": last.get("toTradeFees"),
-                "toTradeVolume": last.get("toTradeVolume"),
-                "toTradeVolumeUSD": last.get("toTradeVolumeUSD"),
-                "totalCount": last.get("totalCount"),
-                "totalFees": last.get("totalFees"),
-                "totalVolume": last.get("totalVolume"),
-                "totalVolumeUSD": last.get("totalVolumeUSD")
-            },
-            "intervals": query_response
-        };
-        // println!("{:?}",query_response);
-        Ok(response)
+        Ok(query_response)
     }
-}
\ No newline at end of file
+}
+
+pub fn swaps_history_response(intervals: Vec<Document>) -> Result<Document, CustomError> {
+    let (Some(first), Some(last)) = (intervals.first(), intervals.last()) else {
+        return Err(CustomError::InvalidInput("No intervals found for the requested range".to_string()));
+    };
+    let response = doc! {
+        "meta": {
+            "endTime": last.get("endTime"),
+            "fromTradeAverageSlip": last.get("fromTradeAverageSlip"),
+            "fromTradeCount": last.get("fromTradeCount"),
+            "fromTradeFees": last.get("fromTradeFees"),
+            "fromTradeVolume": last.get("fromTradeVolume"),
+            "fromTradeVolumeUSD": last.get("fromTradeVolumeUSD"),
+            "runePriceUSD": last.get("runePriceUSD"),
+            "startTime": first.get("startTime"),
+            "synthMintAverageSlip": last.get("synthMintAverageSlip"),
+            "synthMintCount": last.get("synthMintCount"),
+            "synthMintFees": last.get("synthMintFees"),
+            "synthMintVolume": last.get("synthMintVolume"),
+            "synthMintVolumeUSD": last.get("synthMintVolumeUSD"),
+            "synthRedeemAverageSlip": last.get("synthRedeemAverageSlip"),
+            "synthRedeemCount": last.get("synthRedeemCount"),
+            "synthRedeemFees": last.get("synthRedeemFees"),
+            "synthRedeemVolume": last.get("synthRedeemVolume"),
+            "synthRedeemVolumeUSD": last.get("synthRedeemVolumeUSD"),
+            "toAssetAverageSlip": last.get("toAssetAverageSlip"),
+            "toAssetCount": last.get("toAssetCount"),
+            "toAssetFees": last.get("toAssetFees"),
+            "toAssetVolume": last.get("toAssetVolume"),
+            "toAssetVolumeUSD": last.get("toAssetVolumeUSD"),
+            "toRuneAverageSlip": last.get("toRuneAverageSlip"),
+            "toRuneCount": last.get("toRuneCount"),
+            "toRuneFees": last.get("toRuneFees"),
+            "toRuneVolume": last.get("toRuneVolume"),
+            "toRuneVolumeUSD": last.get("toRuneVolumeUSD"),
+            "toTradeAverageSlip": last.get("toTradeAverageSlip"),
+            "toTradeCount": last.get("toTradeCount"),
+            "toTradeFees": last.get("toTradeFees"),
+            "toTradeVolume": last.get("toTradeVolume"),
+            "toTradeVolumeUSD": last.get("toTradeVolumeUSD"),
+            "totalCount": last.get("totalCount"),
+            "totalFees": last.get("totalFees"),
+            "totalVolume": last.get("totalVolume"),
+            "totalVolumeUSD": last.get("totalVolumeUSD")
+        },
+        "intervals": intervals
+    };
+    Ok(response)
+}
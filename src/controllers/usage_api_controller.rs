@@ -0,0 +1,50 @@
+use chrono::{Duration, Utc};
+use futures_util::StreamExt;
+use mongodb::bson::{doc, Document};
+
+use crate::{models::{custom_error_model::CustomError, usage_model::UsageParams}, services::{db::DataBase, metrics_service::timed_aggregation}};
+use tracing::warn;
+
+// days reported when no from is given
+const DEFAULT_USAGE_DAYS: i64 = 30;
+
+impl DataBase {
+    // /usage, client None reports every client
+    #[tracing::instrument(skip(self))]
+    pub async fn get_usage_api(&self, client: Option<String>, params: UsageParams) -> Result<Vec<Document>, CustomError> {
+        let from = params
+            .from
+            .unwrap_or_else(|| (Utc::now() - Duration::days(DEFAULT_USAGE_DAYS - 1)).format("%Y-%m-%d").to_string());
+        let mut day_range = doc! { "$gte": from };
+        if let Some(to) = params.to {
+            day_range.insert("$lte", to);
+        }
+        let mut query = doc! { "day": day_range };
+        if let Some(client) = client {
+            query.insert("client", client);
+        }
+
+        let pipeline = vec![
+            doc! { "$match": query },
+            doc! { "$sort": { "day": -1, "client": 1, "route_group": 1 } },
+            doc! { "$project": {
+                "_id": 0,
+                "client": 1,
+                "day": 1,
+                "routeGroup": "$route_group",
+                "requests": 1,
+                "throttled": 1,
+                "cost": 1
+            }},
+        ];
+        let mut cursor = timed_aggregation("usage", self.mongodb()?.api_usage.aggregate(pipeline)).await?;
+        let mut query_response = Vec::new();
+        while let Some(result) = cursor.next().await {
+            match result {
+                Ok(record) => query_response.push(record),
+                Err(e) => warn!(error = ?e, "Error fetching document"),
+            }
+        }
+        Ok(query_response)
+    }
+}
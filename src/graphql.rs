@@ -0,0 +1,3 @@
+pub mod earnings_summary_loader;
+pub mod query_root;
+pub mod schema;
@@ -0,0 +1,27 @@
+use std::{collections::HashMap, sync::Arc};
+
+use actix_web::web::Data;
+use async_graphql::dataloader::Loader;
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId};
+
+use crate::{models::{custom_error_model::CustomError, earning_history_model::PoolEarningSummary}, services::db::DataBase};
+
+// batches the earningsSummary of every pool record in a response into one $in query
+pub struct EarningsSummaryLoader {
+    pub db: Data<DataBase>,
+}
+
+impl Loader<ObjectId> for EarningsSummaryLoader {
+    type Value = PoolEarningSummary;
+    type Error = Arc<CustomError>;
+
+    async fn load(&self, ids: &[ObjectId]) -> Result<HashMap<ObjectId, PoolEarningSummary>, Self::Error> {
+        let summaries = find_summaries(&self.db, ids).await.map_err(Arc::new)?;
+        Ok(summaries.into_iter().map(|summary| (summary._id, summary)).collect())
+    }
+}
+
+async fn find_summaries(db: &DataBase, ids: &[ObjectId]) -> Result<Vec<PoolEarningSummary>, CustomError> {
+    Ok(db.mongodb()?.earnings_summary.find(doc! { "_id": { "$in": ids } }).await?.try_collect().await?)
+}
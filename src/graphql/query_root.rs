@@ -0,0 +1,193 @@
+use actix_web::web::Data;
+use async_graphql::{dataloader::DataLoader, ComplexObject, Context, Object, Result};
+use futures_util::TryStreamExt;
+use mongodb::{bson::{doc, Document}, Collection};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    models::{
+        api_request_param_model::{validate_query, QueryParams},
+        depth_history_model::PoolDepthPriceHistory,
+        earning_history_model::{PoolEarningHistory, PoolEarningSummary},
+        rune_pool_model::RunePool,
+        swap_history_model::SwapHistory,
+    },
+    services::{db::DataBase, metrics_service::timed_aggregation},
+    utils::db_helper_utils::get_seconds_per_interval,
+};
+
+use super::earnings_summary_loader::EarningsSummaryLoader;
+
+pub struct QueryRoot;
+
+// same arguments and from fallback as the REST history endpoints
+struct HistoryArgs {
+    pool: Option<String>,
+    interval: Option<String>,
+    from: Option<u64>,
+    to: Option<u64>,
+    count: Option<u32>,
+}
+
+impl HistoryArgs {
+    fn validate(&self) -> Result<()> {
+        validate_query(&QueryParams {
+            pool: self.pool.clone(),
+            interval: self.interval.clone(),
+            count: self.count,
+            to: self.to,
+            from: self.from,
+            page: None,
+            sort_by: None,
+            sort_order: None,
+            limit: None,
+        })?;
+        Ok(())
+    }
+}
+
+// the last stored record of every interval bucket (per pool when group_by_pool), with start/end_time
+// moved to the bucket bounds, which is what the REST pipelines return through their $last accumulators
+async fn find_history<T>(db: &DataBase, collection: &Collection<T>, args: HistoryArgs, group_by_pool: bool) -> Result<Vec<T>>
+where
+    T: DeserializeOwned + Send + Sync,
+{
+    args.validate()?;
+    // the pipelines below read the mongodb collections, a postgres or sqlite history is not in them
+    if !db.store.is_mongodb() {
+        return Err("GraphQL serves the history from MongoDB only, it is unavailable with database.history_uri".into());
+    }
+    let seconds_per_interval = get_seconds_per_interval(args.interval.as_deref().unwrap_or("hour")) as i64;
+    let count = args.count.unwrap_or(400) as i64;
+
+    let mut query = doc! {};
+    if let Some(pool) = args.pool {
+        query.insert("pool", pool);
+    }
+    let from = match args.from {
+        Some(from) => from as i64,
+        None => {
+            let calc_start = match args.to {
+                Some(to) => to as i64,
+                None => db.get_max_end_time(collection).await?,
+            };
+            calc_start - count * seconds_per_interval
+        }
+    };
+    query.insert("start_time", doc! { "$gte": from });
+    if let Some(to) = args.to {
+        query.insert("end_time", doc! { "$lte": to as i64 });
+    }
+
+    let mut group_id = doc! {
+        "interval_start": {
+            "$subtract": [
+                { "$add": ["$end_time", 1] },
+                { "$mod": [
+                    { "$subtract": ["$end_time", 1] },
+                    seconds_per_interval
+                ]}
+            ]
+        }
+    };
+    if group_by_pool {
+        group_id.insert("pool", "$pool");
+    }
+    let bucket_start = doc! {
+        "$subtract": [ "$_id.interval_start", { "$mod": [ "$_id.interval_start", seconds_per_interval ] }]
+    };
+    let pipeline = vec![
+        doc! { "$match": query },
+        doc! { "$sort": { "end_time": 1 } },
+        doc! { "$group": { "_id": group_id, "record": { "$last": "$$ROOT" } } },
+        doc! { "$replaceRoot": { "newRoot": { "$mergeObjects": [
+            "$record",
+            { "start_time": bucket_start.clone(), "end_time": { "$add": [bucket_start, seconds_per_interval] } }
+        ]}}},
+        doc! { "$sort": { "end_time": 1, "pool": 1 } },
+        doc! { "$limit": count },
+    ];
+    let records: Vec<Document> = timed_aggregation("graphql", collection.aggregate(pipeline)).await?.try_collect().await?;
+    Ok(records
+        .into_iter()
+        .filter_map(|record| mongodb::bson::from_document::<T>(record).ok())
+        .collect())
+}
+
+#[Object]
+impl QueryRoot {
+    // /depths
+    async fn depths(
+        &self,
+        ctx: &Context<'_>,
+        pool: Option<String>,
+        interval: Option<String>,
+        from: Option<u64>,
+        to: Option<u64>,
+        count: Option<u32>,
+    ) -> Result<Vec<PoolDepthPriceHistory>> {
+        let db = ctx.data::<Data<DataBase>>()?;
+        find_history(db, &db.mongodb()?.depth_history, HistoryArgs { pool, interval, from, to, count }, true).await
+    }
+
+    // /swaps
+    async fn swaps(
+        &self,
+        ctx: &Context<'_>,
+        pool: Option<String>,
+        interval: Option<String>,
+        from: Option<u64>,
+        to: Option<u64>,
+        count: Option<u32>,
+    ) -> Result<Vec<SwapHistory>> {
+        let db = ctx.data::<Data<DataBase>>()?;
+        find_history(db, &db.mongodb()?.swap_history, HistoryArgs { pool, interval, from, to, count }, true).await
+    }
+
+    // /earnings per pool, count bounds the number of pool intervals returned
+    async fn earnings(
+        &self,
+        ctx: &Context<'_>,
+        pool: Option<String>,
+        interval: Option<String>,
+        from: Option<u64>,
+        to: Option<u64>,
+        count: Option<u32>,
+    ) -> Result<Vec<PoolEarningHistory>> {
+        let db = ctx.data::<Data<DataBase>>()?;
+        find_history(db, &db.mongodb()?.earnings, HistoryArgs { pool, interval, from, to, count }, true).await
+    }
+
+    async fn earnings_summary(
+        &self,
+        ctx: &Context<'_>,
+        interval: Option<String>,
+        from: Option<u64>,
+        to: Option<u64>,
+        count: Option<u32>,
+    ) -> Result<Vec<PoolEarningSummary>> {
+        let db = ctx.data::<Data<DataBase>>()?;
+        find_history(db, &db.mongodb()?.earnings_summary, HistoryArgs { pool: None, interval, from, to, count }, false).await
+    }
+
+    // /runepool
+    async fn rune_pool(
+        &self,
+        ctx: &Context<'_>,
+        interval: Option<String>,
+        from: Option<u64>,
+        to: Option<u64>,
+        count: Option<u32>,
+    ) -> Result<Vec<RunePool>> {
+        let db = ctx.data::<Data<DataBase>>()?;
+        find_history(db, &db.mongodb()?.rune_pool_history, HistoryArgs { pool: None, interval, from, to, count }, false).await
+    }
+}
+
+#[ComplexObject]
+impl PoolEarningHistory {
+    // the network wide summary of the hour this pool record was ingested with
+    async fn earnings_summary(&self, ctx: &Context<'_>) -> Result<Option<PoolEarningSummary>> {
+        Ok(ctx.data::<DataLoader<EarningsSummaryLoader>>()?.load_one(self.earnings_summary).await?)
+    }
+}
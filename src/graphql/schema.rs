@@ -0,0 +1,21 @@
+use actix_web::web::Data;
+use async_graphql::{dataloader::DataLoader, EmptyMutation, EmptySubscription, Schema};
+
+use crate::services::db::DataBase;
+
+use super::{earnings_summary_loader::EarningsSummaryLoader, query_root::QueryRoot};
+
+// nested earnings -> earningsSummary is the deepest legit query, anything much deeper is abuse
+const MAX_QUERY_DEPTH: usize = 10;
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema(db: Data<DataBase>) -> ApiSchema {
+    // without a cache, the loader only batches the lookups of one query and never serves stale summaries
+    let earnings_summaries = DataLoader::new(EarningsSummaryLoader { db: db.clone() }, tokio::spawn);
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(db)
+        .data(earnings_summaries)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .finish()
+}
@@ -0,0 +1,103 @@
+#![recursion_limit = "256"]
+
+use actix_web::{
+    self,
+    body::MessageBody,
+    dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+    middleware::from_fn,
+    web::{scope, Data},
+    App, HttpResponse, HttpServer, Responder,
+};
+use api_docs::ApiDoc;
+use config::Config;
+use graphql::schema::{build_schema, ApiSchema};
+use middlewares::{api_key_middleware::{require_admin_key, require_read_key}, metrics_middleware::track_requests, rate_limit_middleware::{rate_limit, RateLimiter}, request_id_middleware::trace_requests};
+use routes::{admin_route, alert_route, anomaly_route, candle_route, depth_route, earning_route, indicator_route, graphql_route, health_route, metrics_route, midgard_route, rune_pool_route, stream_route, swap_route, tools_route, usage_route};
+use services::{db::DataBase, fetch_all_cron_service::run_cron_job};
+use tracing::info;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+pub mod controllers;
+pub mod services;
+pub mod models;
+pub mod routes;
+pub mod utils;
+pub mod api_docs;
+pub mod graphql;
+pub mod middlewares;
+pub mod cli;
+pub mod config;
+pub mod stores;
+
+#[actix_web::get("/")]
+pub async fn lander() -> impl Responder{
+    HttpResponse::Ok().body("Welcome to crypto-token-metrics-api")
+}
+
+// built once and cloned into every worker, so workers share the rate limit buckets
+#[derive(Clone)]
+pub struct AppState{
+    pub config : Data<Config>,
+    pub db : Data<DataBase>,
+    pub graphql_schema : Data<ApiSchema>,
+    pub rate_limiter : Data<RateLimiter>
+}
+
+impl AppState {
+    pub fn new(config: Config, db: DataBase) -> Self {
+        let db = Data::new(db);
+        AppState {
+            graphql_schema: Data::new(build_schema(db.clone())),
+            rate_limiter: Data::new(RateLimiter::new(config.limits.rate_limits.clone())),
+            config: Data::new(config),
+            db,
+        }
+    }
+}
+
+// every route and middleware of the server, usable with HttpServer or actix_web::test::init_service
+pub fn build_app(state: &AppState) -> App<
+    impl ServiceFactory<ServiceRequest, Config = (), Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error, InitError = ()>,
+> {
+    App::new().app_data(state.db.clone())
+    .app_data(state.graphql_schema.clone())
+    .app_data(state.rate_limiter.clone())
+    .app_data(state.config.clone())
+    .wrap(from_fn(rate_limit))
+    // outside rate_limit so throttled requests are counted too
+    .wrap(from_fn(track_requests))
+    .wrap(from_fn(trace_requests))
+    .service(SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", ApiDoc::openapi()))
+    .service(scope("/depths").configure(depth_route::init))
+    .service(scope("/earnings").configure(earning_route::init))
+    .service(scope("/swaps").configure(swap_route::init))
+    .service(scope("/runepool").configure(rune_pool_route::init))
+    // the midgard paths and response format, for clients written against midgard
+    .service(scope("/v2").configure(midgard_route::init))
+    .service(scope("/tools").configure(tools_route::init))
+    .service(scope("/candles").configure(candle_route::init))
+    .service(scope("/indicators").configure(indicator_route::init))
+    .service(scope("/anomalies").configure(anomaly_route::init))
+    // read keys list rules and events, creating and deleting rules also needs an admin key
+    .service(scope("/alerts").wrap(from_fn(require_read_key)).configure(alert_route::init))
+    .service(scope("/stream").configure(stream_route::init))
+    .service(scope("/graphql").configure(graphql_route::init))
+    .service(scope("/usage").wrap(from_fn(require_read_key)).configure(usage_route::init))
+    .service(scope("/admin").wrap(from_fn(require_admin_key)).configure(admin_route::init))
+    .service(metrics_route::get_metrics)
+    .configure(health_route::init)
+    .service(lander)
+}
+
+// connects to the database, starts the ingestion cron when enabled and serves until shutdown
+pub async fn serve(config: Config) -> std::io::Result<()>{
+    let data_base = DataBase::init(&config.database).await.map_err(|e| std::io::Error::other(e.to_string()))?;
+    info!("Connected to DB");
+    let bind_address = config.bind_address();
+    let state = AppState::new(config, data_base);
+    if state.config.ingestion.enabled {
+        actix_web::rt::spawn(run_cron_job(state.db.clone(), state.config.clone()));
+    }
+
+    HttpServer::new(move || build_app(&state)).bind(bind_address)?.run().await
+}
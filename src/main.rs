@@ -1,41 +1,40 @@
-#![recursion_limit = "256"]
+use clap::Parser;
+use tokenmetrics::{cli::{self, Cli, Command}, config::Config, serve, services::db::DataBase, utils::tracing_utils::init_tracing};
 
-use actix_web::{self, web::{scope, Data}, App, HttpResponse, HttpServer, Responder};
-use api_docs::ApiDoc;
-use utoipa::OpenApi;
-pub mod controllers;
-use routes::{depth_route, earning_route::{self}, rune_pool_route, swap_route::{self}};
-use services::{db::DataBase, fetch_all_cron_service::run_cron_job};
-use utoipa_swagger_ui::SwaggerUi;
-pub mod services;
-pub mod models;
-pub mod routes;
-pub mod utils;
-pub mod api_docs;
-
-
-#[actix_web::get("/")]
-pub async fn lander() -> impl Responder{
-    HttpResponse::Ok().body("Welcome to crypto-token-metrics-api")
-}
 #[actix_web::main]
 async fn main() -> std::io::Result<()>{
-    let data_base = DataBase::init().await;
-    let db_data = Data::new(data_base);
-    actix_web::rt::spawn(run_cron_job(db_data.clone(), "BTC.BTC"));
-    println!("Connected to DB\n");
-
-    let openapi = ApiDoc::openapi();
-
-    HttpServer::new(
-        move || {
-            App::new().app_data(db_data.clone())
-            .service(SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi.clone()))
-            .service(scope("/depths").configure(depth_route::init))
-            .service(scope("/earnings").configure(earning_route::init))
-            .service(scope("/swaps").configure(swap_route::init))
-            .service(scope("/runepool").configure(rune_pool_route::init))
-            .service(lander)
+    let cli = Cli::parse();
+    let config = match Config::load(cli.db.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    init_tracing();
+    match cli.command {
+        None | Some(Command::Serve) => {
+            if let Err(e) = serve(config).await {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            Ok(())
         }
-    ).bind(("0.0.0.0",3000))?.run().await
-}
\ No newline at end of file
+        Some(command) => {
+            let data_base = match DataBase::init(&config.database).await {
+                Ok(data_base) => data_base,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            match cli::run_command(&data_base, &config, command).await {
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod api_key_middleware;
+pub mod rate_limit_middleware;
+pub mod metrics_middleware;
+pub mod request_id_middleware;
\ No newline at end of file
@@ -0,0 +1,50 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web::Data,
+    Error, HttpResponse,
+};
+
+use crate::{
+    models::{api_key_model::{ApiKey, ADMIN_ROLE, READ_ROLE}, custom_error_model::CustomError},
+    services::db::DataBase,
+};
+use tracing::error;
+
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+// the error response to send instead of calling the route, None when the key carries the role
+async fn check_api_key(req: &ServiceRequest, role: &str) -> Option<HttpResponse> {
+    let Some(key) = req.headers().get(API_KEY_HEADER).and_then(|key| key.to_str().ok()) else {
+        return Some(HttpResponse::Unauthorized().json(CustomError::InvalidInput(format!("Missing {} header", API_KEY_HEADER))));
+    };
+    let Some(db) = req.app_data::<Data<DataBase>>() else {
+        return Some(HttpResponse::InternalServerError().json(CustomError::StandardError("Database not configured".to_string())));
+    };
+    match ApiKey::find_active_api_key(db, key).await {
+        Ok(Some(api_key)) if api_key.has_role(role) => None,
+        Ok(Some(_)) => Some(HttpResponse::Forbidden().json(CustomError::InvalidInput(format!("API key lacks the {} role", role)))),
+        Ok(None) => Some(HttpResponse::Unauthorized().json(CustomError::InvalidInput("Invalid or revoked API key".to_string()))),
+        Err(e) => {
+            error!(error = ?e, "Error checking api key");
+            Some(HttpResponse::InternalServerError().json(e))
+        }
+    }
+}
+
+async fn require_role<B: MessageBody>(req: ServiceRequest, next: Next<B>, role: &str) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    match check_api_key(&req, role).await {
+        None => next.call(req).await.map(ServiceResponse::map_into_left_body),
+        Some(response) => Ok(req.into_response(response).map_into_right_body()),
+    }
+}
+
+// wrap with actix_web::middleware::from_fn
+pub async fn require_admin_key<B: MessageBody>(req: ServiceRequest, next: Next<B>) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    require_role(req, next, ADMIN_ROLE).await
+}
+
+pub async fn require_read_key<B: MessageBody>(req: ServiceRequest, next: Next<B>) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    require_role(req, next, READ_ROLE).await
+}
@@ -0,0 +1,28 @@
+use std::time::Instant;
+
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    Error,
+};
+
+use crate::services::metrics_service::METRICS;
+
+// wrap the whole app with actix_web::middleware::from_fn, routes are labelled by their pattern
+// so path parameters do not create new series
+pub async fn track_requests<B: MessageBody>(req: ServiceRequest, next: Next<B>) -> Result<ServiceResponse<B>, Error> {
+    let started = Instant::now();
+    let method = req.method().to_string();
+    let res = next.call(req).await?;
+    let route = res.request().match_pattern().unwrap_or_else(|| "unmatched".to_string());
+    METRICS
+        .http_requests
+        .with_label_values(&[&method, &route, res.status().as_str()])
+        .inc();
+    METRICS
+        .http_request_duration
+        .with_label_values(&[&method, &route])
+        .observe(started.elapsed().as_secs_f64());
+    Ok(res)
+}
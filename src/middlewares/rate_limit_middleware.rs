@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    web::Data,
+    Error, HttpResponse,
+};
+use chrono::Utc;
+use mongodb::bson::doc;
+use serde::Deserialize;
+
+use crate::{
+    models::{api_key_model::ApiKey, custom_error_model::CustomError},
+    services::{api_key_service::hash_api_key, db::DataBase},
+};
+
+use super::api_key_middleware::API_KEY_HEADER;
+use tracing::{error, warn};
+
+// how long a presented key keeps resolving to the same client without another db lookup
+const KEY_CACHE_SECS: u64 = 60;
+// past this many fresh entries unknown keys are no longer cached, issued keys always are
+const MAX_CACHED_KEYS: usize = 10_000;
+// buckets idle for this long are full again and can be dropped
+const IDLE_BUCKET_SECS: u64 = 3600;
+const MAX_TRACKED_BUCKETS: usize = 100_000;
+
+// token bucket shared by every route starting with one of the prefixes,
+// each request takes `cost` tokens out of `capacity` refilled at `refill_per_sec`
+#[derive(Debug,Clone,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteGroup{
+    pub name : String,
+    pub prefixes : Vec<String>,
+    pub capacity : f64,
+    pub refill_per_sec : f64,
+    pub cost : f64
+}
+
+impl RouteGroup {
+    pub fn new(name: &str, prefixes: &[&str], capacity: f64, refill_per_sec: f64, cost: f64) -> Self {
+        RouteGroup {
+            name: name.to_string(),
+            prefixes: prefixes.iter().map(|prefix| prefix.to_string()).collect(),
+            capacity,
+            refill_per_sec,
+            cost,
+        }
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        self.prefixes.iter().any(|prefix| {
+            path.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+// the earnings pipelines join the summary collection and are the most expensive per call
+pub fn default_route_groups() -> Vec<RouteGroup> {
+    vec![
+        RouteGroup::new("earnings", &["/earnings", "/v2/history/earnings"], 60.0, 0.5, 10.0),
+        RouteGroup::new("history", &["/depths", "/swaps", "/v2/history/depths", "/v2/history/swaps", "/candles", "/indicators", "/anomalies", "/graphql"], 60.0, 1.0, 4.0),
+        RouteGroup::new("runepool", &["/runepool", "/v2/history/runepool"], 60.0, 1.0, 1.0),
+        RouteGroup::new("default", &[], 120.0, 2.0, 1.0),
+    ]
+}
+
+#[derive(Debug)]
+struct Bucket{
+    tokens : f64,
+    updated_at : Instant
+}
+
+pub struct RateLimiter{
+    // the last group catches every path not matched by the others
+    groups : Vec<RouteGroup>,
+    buckets : Mutex<HashMap<(String, String), Bucket>>,
+    // hashed key -> client id of the key, None for unknown or revoked keys
+    keys : Mutex<HashMap<String, (Option<String>, Instant)>>
+}
+
+impl RateLimiter {
+    pub fn new(groups: Vec<RouteGroup>) -> Self {
+        assert!(!groups.is_empty(), "at least one route group is required");
+        RateLimiter {
+            groups,
+            buckets: Mutex::new(HashMap::new()),
+            keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn route_group(&self, path: &str) -> &RouteGroup {
+        self.groups
+            .iter()
+            .find(|group| group.matches(path))
+            .unwrap_or(&self.groups[self.groups.len() - 1])
+    }
+
+    // takes the group cost from the client bucket, Err holds the seconds until it can be afforded
+    pub fn try_acquire(&self, client: &str, group: &RouteGroup) -> Result<f64, u64> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_BUCKETS {
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated_at) < Duration::from_secs(IDLE_BUCKET_SECS));
+        }
+        let bucket = buckets
+            .entry((client.to_string(), group.name.clone()))
+            .or_insert(Bucket { tokens: group.capacity, updated_at: now });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * group.refill_per_sec).min(group.capacity);
+        bucket.updated_at = now;
+        if bucket.tokens >= group.cost {
+            bucket.tokens -= group.cost;
+            Ok(bucket.tokens)
+        } else {
+            Err(((group.cost - bucket.tokens) / group.refill_per_sec).ceil().max(1.0) as u64)
+        }
+    }
+
+    // "key:<prefix>" for an active API key, None when the key has to be looked up
+    fn cached_key_client(&self, key_hash: &str) -> Option<Option<String>> {
+        let keys = self.keys.lock().unwrap();
+        keys.get(key_hash)
+            .filter(|(_, cached_at)| cached_at.elapsed() < Duration::from_secs(KEY_CACHE_SECS))
+            .map(|(client, _)| client.clone())
+    }
+
+    async fn key_client(&self, db: &DataBase, key: &str) -> Option<String> {
+        // the keys are stored in mongodb, without it every request is limited per address
+        db.mongodb.as_ref()?;
+        let key_hash = hash_api_key(key);
+        if let Some(client) = self.cached_key_client(&key_hash) {
+            return client;
+        }
+        let client = match ApiKey::find_active_api_key(db, key).await {
+            Ok(api_key) => api_key.map(|api_key| format!("key:{}", api_key.prefix)),
+            Err(e) => {
+                error!(error = ?e, "Error resolving api key for rate limiting");
+                return None;
+            }
+        };
+        let mut keys = self.keys.lock().unwrap();
+        keys.retain(|_, (_, cached_at)| cached_at.elapsed() < Duration::from_secs(KEY_CACHE_SECS));
+        // random keys would otherwise grow the cache without bound
+        if client.is_some() || keys.len() < MAX_CACHED_KEYS {
+            keys.insert(key_hash, (client.clone(), Instant::now()));
+        }
+        client
+    }
+
+    // requests without a valid key are limited per peer address, forwarded headers are not trusted
+    async fn client_id(&self, req: &ServiceRequest, db: &DataBase) -> String {
+        if let Some(key) = req.headers().get(API_KEY_HEADER).and_then(|key| key.to_str().ok()) {
+            if let Some(client) = self.key_client(db, key).await {
+                return client;
+            }
+        }
+        match req.peer_addr() {
+            Some(addr) => format!("ip:{}", addr.ip()),
+            None => "ip:unknown".to_string(),
+        }
+    }
+}
+
+// bumps the daily counters off the request path, a failed write only loses accounting and
+// nothing is recorded without mongodb
+fn record_usage(db: Data<DataBase>, client: String, route_group: String, cost: f64, throttled: bool) {
+    actix_web::rt::spawn(async move {
+        let day = Utc::now().format("%Y-%m-%d").to_string();
+        let increments = if throttled {
+            doc! { "requests": 1_i64, "throttled": 1_i64, "cost": 0_i64 }
+        } else {
+            doc! { "requests": 1_i64, "throttled": 0_i64, "cost": cost as i64 }
+        };
+        let Ok(mongodb) = db.mongodb() else {
+            return;
+        };
+        let result = mongodb
+            .api_usage
+            .update_one(
+                doc! { "client": &client, "day": day, "route_group": route_group },
+                doc! { "$inc": increments },
+            )
+            .upsert(true)
+            .await;
+        if let Err(e) = result {
+            warn!(client, error = ?e, "Failed recording usage");
+        }
+    });
+}
+
+// wrap the whole app with actix_web::middleware::from_fn, needs Data<RateLimiter> and Data<DataBase>
+pub async fn rate_limit<B: MessageBody>(req: ServiceRequest, next: Next<B>) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let (Some(limiter), Some(db)) = (req.app_data::<Data<RateLimiter>>().cloned(), req.app_data::<Data<DataBase>>().cloned()) else {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    };
+    let client = limiter.client_id(&req, &db).await;
+    let group = limiter.route_group(req.path()).clone();
+
+    match limiter.try_acquire(&client, &group) {
+        Ok(remaining) => {
+            record_usage(db, client, group.name.clone(), group.cost, false);
+            let mut res = next.call(req).await?;
+            res.headers_mut().insert(
+                HeaderName::from_static("x-ratelimit-remaining"),
+                HeaderValue::from((remaining / group.cost).floor() as u64),
+            );
+            Ok(res.map_into_left_body())
+        }
+        Err(retry_after) => {
+            record_usage(db, client, group.name.clone(), group.cost, true);
+            let response = HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after.to_string()))
+                .json(CustomError::InvalidInput(format!("Rate limit of the {} routes exceeded, retry in {}s", group.name, retry_after)));
+            Ok(req.into_response(response).map_into_right_body())
+        }
+    }
+}
@@ -3,4 +3,14 @@ pub mod earning_history_model;
 pub mod swap_history_model;
 pub mod rune_pool_model;
 pub mod custom_error_model;
-pub mod api_request_param_model;
\ No newline at end of file
+pub mod api_request_param_model;
+pub mod swap_quote_model;
+pub mod candle_model;
+pub mod indicator_model;
+pub mod anomaly_model;
+pub mod alert_rule_model;
+pub mod stream_event_model;
+pub mod api_key_model;
+pub mod usage_model;
+pub mod health_model;
+pub mod quarantine_model;
\ No newline at end of file
@@ -0,0 +1,121 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{custom_error_model::CustomError, indicator_model::resolve_metric};
+
+pub const VALID_CONDITIONS:[&str; 4] = ["above", "below", "change_pct_above", "change_pct_below"];
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[schema(rename_all="camelCase")]
+pub struct AlertRule{
+    #[schema(value_type = String, example = "60d5ec49a1c4b5048c0e5c70")]
+    pub _id : ObjectId,
+    #[schema(example="BTC depth drop")]
+    pub name : String,
+    #[schema(example="BTC.BTC")]
+    pub pool : String,
+    // any metric supported by /indicators
+    #[schema(example="assetDepth")]
+    pub metric : String,
+    #[schema(example="change_pct_below")]
+    pub condition : String,
+    #[schema(example=-10.0)]
+    pub threshold : f64,
+    // lookback of the change_pct conditions
+    #[schema(example=3600)]
+    pub window_secs : i64,
+    // minimum time between two deliveries of the same rule
+    #[schema(example=3600)]
+    pub cooldown_secs : i64,
+    #[schema(example="http://localhost:4000/hooks/alerts")]
+    pub webhook_url : String,
+    // HMAC-SHA256 key of the X-Signature header, never exposed in responses
+    #[schema(example="change-me")]
+    pub secret : String,
+    #[schema(example=true)]
+    pub enabled : bool,
+    #[schema(example=1647914400)]
+    pub last_triggered_at : Option<i64>,
+    // end_time of the interval that last fired, the same interval never fires twice
+    #[schema(example=1647914400)]
+    pub last_triggered_end_time : Option<i64>,
+    #[schema(example=1647914400)]
+    pub created_at : i64
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[serde(rename_all="camelCase")]
+pub struct AlertRuleRequest{
+    #[schema(example="BTC depth drop")]
+    pub name : String,
+    #[schema(example="BTC.BTC")]
+    pub pool : String,
+    #[schema(example="assetDepth")]
+    pub metric : String,
+    #[schema(example="change_pct_below")]
+    pub condition : String,
+    #[schema(example=-10.0)]
+    pub threshold : f64,
+    #[schema(example=3600)]
+    pub window_secs : Option<i64>,
+    #[schema(example=3600)]
+    pub cooldown_secs : Option<i64>,
+    #[schema(example="http://localhost:4000/hooks/alerts")]
+    pub webhook_url : String,
+    #[schema(example="change-me")]
+    pub secret : String
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[schema(rename_all="camelCase")]
+pub struct AlertEvent{
+    #[schema(value_type = String, example = "60d5ec49a1c4b5048c0e5c70")]
+    pub _id : ObjectId,
+    #[schema(value_type = String, example = "60d5ec49a1c4b5048c0e5c70")]
+    pub rule_id : ObjectId,
+    #[schema(example="BTC.BTC")]
+    pub pool : String,
+    #[schema(example="assetDepth")]
+    pub metric : String,
+    #[schema(example=-12.4)]
+    pub value : f64,
+    #[schema(example=1647914400)]
+    pub end_time : i64,
+    #[schema(example=1647914500)]
+    pub triggered_at : i64,
+    #[schema(example=true)]
+    pub delivered : bool,
+    #[schema(example=1)]
+    pub attempts : i32,
+    #[schema(example="webhook responded 500")]
+    pub last_error : Option<String>
+}
+
+pub fn validate_alert_rule(rule: &AlertRuleRequest) -> Result<(), CustomError> {
+    if rule.name.trim().is_empty() {
+        return Err(CustomError::InvalidInput("name is required".to_string()));
+    }
+    if resolve_metric(&rule.metric).is_none() {
+        return Err(CustomError::InvalidInput(format!("Unsupported metric {}", rule.metric)));
+    }
+    if !VALID_CONDITIONS.contains(&rule.condition.as_str()) {
+        return Err(CustomError::InvalidInput(format!("condition must be in {:?}",VALID_CONDITIONS)));
+    }
+    if !rule.threshold.is_finite() {
+        return Err(CustomError::InvalidInput("threshold must be a number".to_string()));
+    }
+    if rule.condition.starts_with("change_pct") && rule.window_secs.unwrap_or(0) < 3600 {
+        return Err(CustomError::InvalidInput("windowSecs of at least 3600 is required for change_pct conditions".to_string()));
+    }
+    if rule.cooldown_secs.unwrap_or(0) < 0 {
+        return Err(CustomError::InvalidInput("cooldownSecs must not be negative".to_string()));
+    }
+    if !rule.webhook_url.starts_with("http://") && !rule.webhook_url.starts_with("https://") {
+        return Err(CustomError::InvalidInput("webhookUrl must be an http(s) url".to_string()));
+    }
+    if rule.secret.len() < 8 {
+        return Err(CustomError::InvalidInput("secret must be at least 8 characters".to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::custom_error_model::CustomError;
+
+pub const VALID_SEVERITIES:[&str; 3] = ["low", "medium", "high"];
+pub const VALID_ANOMALY_KINDS:[&str; 3] = ["volume_spike", "depth_drop", "slip_spike"];
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[schema(rename_all="camelCase")]
+pub struct Anomaly{
+    #[schema(value_type = String, example = "60d5ec49a1c4b5048c0e5c70",rename="._id not exposed in the response")]
+    pub _id : ObjectId,
+    #[schema(example="BTC.BTC")]
+    pub pool : String,
+    // source collection of the scored interval
+    #[schema(example="swap_history")]
+    pub collection : String,
+    #[schema(example="volume_spike")]
+    pub kind : String,
+    #[schema(example="totalVolumeUSD")]
+    pub metric : String,
+    #[schema(example=5401234567.0)]
+    pub value : f64,
+    #[schema(example=120004567.0)]
+    pub baseline_median : f64,
+    #[schema(example=40001234.0)]
+    pub baseline_mad : f64,
+    #[schema(example=9.2)]
+    pub score : f64,
+    #[schema(example="high")]
+    pub severity : String,
+    #[schema(example=1647910800)]
+    pub start_time : i64,
+    #[schema(example=1647914400)]
+    pub end_time : i64,
+    #[schema(example=1647914500)]
+    pub detected_at : i64
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+pub struct AnomalyParams{
+    #[schema(example = "BTC.BTC")]
+    pub pool : Option<String>,
+    #[schema(example = "volume_spike")]
+    pub kind : Option<String>,
+    #[schema(example = "medium,high")]
+    pub severity : Option<String>,
+    #[schema(example = 1653373410)]
+    pub from : Option<u64>,
+    #[schema(example = 1653373410)]
+    pub to : Option<u64>,
+    #[schema(example="2")]
+    pub page : Option<u64>,
+    #[schema(example = "100")]
+    pub count : Option<u32>
+}
+
+// |score| thresholds for the severity buckets, below the lowest one an interval is not an anomaly
+pub fn severity_for_score(score: f64) -> Option<&'static str> {
+    let score = score.abs();
+    if score >= 8.0 {
+        Some("high")
+    } else if score >= 5.0 {
+        Some("medium")
+    } else if score >= 3.5 {
+        Some("low")
+    } else {
+        None
+    }
+}
+
+pub fn validate_anomaly_query(query: &AnomalyParams) -> Result<(), CustomError> {
+    if let (Some(start), Some(end)) = (query.from, query.to) {
+        if start >= end {
+            return Err(CustomError::InvalidInput("start_time must be less than end_time.".to_string()));
+        }
+    }
+
+    if let Some(page) = query.page {
+        if page < 1{
+            return Err(CustomError::InvalidInput("page must be positive number".to_string()));
+        }
+    }
+
+    if let Some(count) = query.count{
+        if !(1..=400).contains(&count){
+            return Err(CustomError::InvalidInput("Count has to be [1..400]".to_string()));
+        }
+    }
+
+    if let Some(ref kind) = query.kind {
+        if !VALID_ANOMALY_KINDS.contains(&kind.as_str()) {
+            return Err(CustomError::InvalidInput(format!("kind must be in {:?}",VALID_ANOMALY_KINDS)));
+        }
+    }
+
+    if let Some(ref severity) = query.severity {
+        if severity.split(',').any(|severity| !VALID_SEVERITIES.contains(&severity.trim())) {
+            return Err(CustomError::InvalidInput(format!("severity must be a comma separated list of {:?}",VALID_SEVERITIES)));
+        }
+    }
+
+    Ok(())
+}
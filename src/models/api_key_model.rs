@@ -0,0 +1,34 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use super::custom_error_model::CustomError;
+
+pub const ADMIN_ROLE: &str = "admin";
+pub const READ_ROLE: &str = "read";
+
+// only the sha256 of the key is stored, the plain key is shown once when minted
+#[derive(Debug,Serialize,Deserialize)]
+pub struct ApiKey{
+    pub _id : ObjectId,
+    pub name : String,
+    // first characters of the plain key to tell keys apart when listing or revoking
+    pub prefix : String,
+    pub key_hash : String,
+    pub role : String,
+    pub created_at : i64,
+    pub revoked_at : Option<i64>
+}
+
+impl ApiKey {
+    // admin keys can do everything read keys can
+    pub fn has_role(&self, role: &str) -> bool {
+        self.role == ADMIN_ROLE || self.role == role
+    }
+}
+
+pub fn validate_role(role: &str) -> Result<(), CustomError> {
+    if role != ADMIN_ROLE && role != READ_ROLE {
+        return Err(CustomError::InvalidInput(format!("role must be {} or {}", ADMIN_ROLE, READ_ROLE)));
+    }
+    Ok(())
+}
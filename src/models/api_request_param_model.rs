@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::utils::constants::VALID_INTERVALS;
+
 use super::custom_error_model::CustomError;
 
 #[derive(Debug,Serialize,Deserialize,ToSchema)]
@@ -53,12 +55,18 @@ pub fn validate_query(query: &QueryParams) -> Result<(), CustomError> {
     }
 
     // Validate interval
-    let valid_interval = vec!["hour", "day", "week", "month", "quarter", "year"];
     if let Some(ref interval) = query.interval {
-        if !valid_interval.contains(&interval.as_str()) {
-            return Err(CustomError::InvalidInput(format!("Interval must be in {:?}",valid_interval)));
+        if !VALID_INTERVALS.contains(&interval.as_str()) {
+            return Err(CustomError::InvalidInput(format!("Interval must be in {:?}",VALID_INTERVALS)));
         }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+// depths and swaps are only ingested for the configured pools
+pub fn validate_pool(query: &QueryParams, pools: &[String]) -> Result<(), CustomError> {
+    match &query.pool {
+        Some(pool) if !pools.contains(pool) => Err(CustomError::InvalidInput(format!("pool must be in {:?}", pools))),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::constants::{RUNE_ASSET, VALID_INTERVALS};
+
+use super::custom_error_model::CustomError;
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+pub struct CandleParams{
+    #[schema(example = "BTC.BTC")]
+    pub pool : String,
+    #[schema(example = "day")]
+    pub interval : Option<String>,
+    #[schema(example = "usd")]
+    pub quote : Option<String>,
+    #[schema(example = 1653373410)]
+    pub from : Option<u64>,
+    #[schema(example = 1653373410)]
+    pub to : Option<u64>,
+    #[schema(example = "100")]
+    pub count : Option<u32>
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[serde(rename_all="camelCase")]
+pub struct Candle{
+    // candle open time, matches startTime and is what charting libraries expect as `time`
+    #[schema(example = 1653350400)]
+    pub time : i64,
+    #[schema(example = 1653350400)]
+    pub start_time : i64,
+    #[schema(example = 1653436800)]
+    pub end_time : i64,
+    #[schema(example = 29150.2)]
+    pub open : f64,
+    #[schema(example = 29800.7)]
+    pub high : f64,
+    #[schema(example = 28900.1)]
+    pub low : f64,
+    #[schema(example = 29620.4)]
+    pub close : f64
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+pub struct CandleMeta{
+    #[schema(example = "BTC.BTC")]
+    pub pool : String,
+    #[schema(example = "day")]
+    pub interval : String,
+    #[schema(example = "usd")]
+    pub quote : String
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+pub struct CandleSeries{
+    pub meta : CandleMeta,
+    pub candles : Vec<Candle>
+}
+
+pub fn validate_candle_query(query: &CandleParams) -> Result<(), CustomError> {
+    if let (Some(start), Some(end)) = (query.from, query.to) {
+        if start >= end {
+            return Err(CustomError::InvalidInput("start_time must be less than end_time.".to_string()));
+        }
+    }
+
+    if let Some(count) = query.count{
+        if !(1..=400).contains(&count){
+            return Err(CustomError::InvalidInput("Count has to be [1..400]".to_string()));
+        }
+    }
+
+    if let Some(ref interval) = query.interval {
+        if !VALID_INTERVALS.contains(&interval.as_str()) {
+            return Err(CustomError::InvalidInput(format!("Interval must be in {:?}",VALID_INTERVALS)));
+        }
+    }
+
+    // rune price history only exists in usd (runePriceUSD of swaps)
+    match query.quote.as_deref() {
+        None | Some("usd") => (),
+        Some("rune") if query.pool != RUNE_ASSET => (),
+        Some(_) => return Err(CustomError::InvalidInput(format!("quote must be usd{}", if query.pool == RUNE_ASSET { "" } else { " or rune" }))),
+    }
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use mongodb::error::Error as mongoError;
 use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize)]
@@ -10,4 +12,13 @@ impl From<mongoError> for CustomError {
     fn from(err: mongoError) -> Self {
         CustomError::DatabaseError(err.to_string())
     }
+}
+impl fmt::Display for CustomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomError::InvalidInput(e) => write!(f, "Invalid input: {}", e),
+            CustomError::DatabaseError(e) => write!(f, "Database error: {}", e),
+            CustomError::StandardError(e) => write!(f, "{}", e),
+        }
+    }
 }
\ No newline at end of file
@@ -1,57 +1,92 @@
 use mongodb::bson::oid::ObjectId;
+use async_graphql::SimpleObject;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::utils::parser_utils::deserialize_amount;
 
-#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[derive(Debug,Clone,Serialize,Deserialize,ToSchema,SimpleObject)]
 #[schema(rename_all="camelCase")]
 pub struct PoolEarningSummary{
     #[schema(value_type = String, example = "60d5ec49a1c4b5048c0e5c70",rename="._id not exposed in the response")]
+    #[graphql(skip)]
     pub _id : ObjectId,
     #[schema(example=36.58)]
     pub avg_node_count : f64,
     #[schema(example=268913207)]
-    pub block_rewards : f64,
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub block_rewards : i64,
     #[schema(example=90350204)]
-    pub bonding_earnings : f64,
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub bonding_earnings : i64,
     #[schema(example=268913207)]
-    pub earnings : u64,
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub earnings : i64,
     #[schema(example=1647914400)]
     pub end_time : i64,
     #[schema(example=178563003)]
-    pub liquidity_earnings : f64,
-    #[schema(value_type=u64,example=15949748490.0)]
-    pub liquidity_fees : u64,
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub liquidity_earnings : i64,
+    #[schema(example=15949748490_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub liquidity_fees : i64,
     #[schema(example=1647914400)]
     pub start_time : i64,
     #[schema(example=8.508409670179631,rename="runePriceUSD")]
+    #[graphql(name="runePriceUSD")]
     pub rune_price_usd : f64,
 }  
 
-#[derive(Debug,Deserialize,Serialize,ToSchema)]
+#[derive(Debug,Deserialize,Serialize,ToSchema,SimpleObject)]
 #[schema(rename_all="camelCase")]
+// earningsSummary is resolved from the earnings_summary id in the graphql module
+#[graphql(complex)]
 pub struct PoolEarningHistory{
     #[schema(value_type = String, example = "60d5ec49a1c4b5048c0e5c70",rename="._id not exposed in the response")]
+    #[graphql(skip)]
     pub _id : ObjectId,
     #[schema(example="TERRA.LUNA")]
     pub pool : String,
-    #[schema(example=9756653557.0)]
-    pub asset_liquidity_fees : f64,
-    #[schema(example=4405821942.0)]
-    pub earning : u64,
-    #[schema(example=747444314.0)]
-    pub rewards : f64,
-    #[schema(example=2591263713.0)]
-    pub rune_liquidity_fees : f64,
+    #[schema(example=9756653557_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub asset_liquidity_fees : i64,
+    #[schema(example=4405821942_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub earning : i64,
+    #[schema(example=747444314)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub rewards : i64,
+    #[schema(example=2591263713_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub rune_liquidity_fees : i64,
     #[schema(example=0)]
-    pub saver_earning : f64,
-    #[schema(example=36583776280.0)]
-    pub total_liquidity_fees_rune : f64,
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub saver_earning : i64,
+    #[schema(example=36583776280_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub total_liquidity_fees_rune : i64,
     #[schema(example=1647921600)]
     pub start_time : i64,
     #[schema(example=1647925200)]
     pub end_time : i64,
     #[schema(value_type=String,example="67186c6f8a3d488dd6050676")]
+    #[graphql(skip)]
     pub earnings_summary : ObjectId
 }
 
+
+// a midgard earnings interval as stored, its summary and the pools referencing it
+pub type EarningsInterval = (PoolEarningSummary, Vec<PoolEarningHistory>);
+
+// an earnings summary whose stored pools do not add up to its liquidity earnings, reported by `verify`
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub struct EarningsMismatch{
+    // id of the summary
+    pub summary : String,
+    pub start_time : i64,
+    pub end_time : i64,
+    pub liquidity_earnings : i64,
+    // pools stored with the summary and the sum of their earnings
+    pub pools : i64,
+    pub pool_earnings : i64
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// cron runs hourly and stores hour intervals, so an interval is normally at most about two hours old
+pub const DEFAULT_MAX_DATA_LAG_SECS: i64 = 3 * 3_600;
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[serde(rename_all="camelCase")]
+pub struct CollectionFreshness{
+    #[schema(example = "depth_history")]
+    pub collection : String,
+    // None for collections without pools like rune_pool_history
+    #[schema(example = "BTC.BTC")]
+    pub pool : Option<String>,
+    #[schema(example = 1653373410)]
+    pub latest_end_time : i64,
+    #[schema(example = 1800)]
+    pub lag_secs : i64,
+    #[schema(example = false)]
+    pub stale : bool
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[serde(rename_all="camelCase")]
+pub struct DataFreshness{
+    // "ok", or "stale" when a collection is empty or lags more than maxLagSecs
+    #[schema(example = "ok")]
+    pub status : String,
+    #[schema(example = 10800)]
+    pub max_lag_secs : i64,
+    #[schema(example = 1653375210)]
+    pub checked_at : i64,
+    // collections without any record
+    #[schema(example = json!(["earnings"]))]
+    pub empty_collections : Vec<String>,
+    pub collections : Vec<CollectionFreshness>
+}
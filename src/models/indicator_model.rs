@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{stores::metrics_store::HistoryCollection, utils::constants::VALID_INTERVALS};
+
+use super::custom_error_model::CustomError;
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+pub struct IndicatorParams{
+    #[schema(example = "assetPriceUSD")]
+    pub metric : String,
+    #[schema(example = "BTC.BTC")]
+    pub pool : String,
+    #[schema(example = "day")]
+    pub interval : Option<String>,
+    #[schema(example = "sma:20,ema:12,rsi:14,bollinger:20,volatility:20")]
+    pub indicators : String,
+    #[schema(example = 1653373410)]
+    pub from : Option<u64>,
+    #[schema(example = 1653373410)]
+    pub to : Option<u64>,
+    #[schema(example = "100")]
+    pub count : Option<u32>
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Indicator {
+    Sma(usize),
+    Ema(usize),
+    Rsi(usize),
+    Bollinger(usize),
+    Volatility(usize),
+}
+
+impl Indicator {
+    pub fn period(&self) -> usize {
+        match self {
+            Indicator::Sma(period)
+            | Indicator::Ema(period)
+            | Indicator::Rsi(period)
+            | Indicator::Bollinger(period)
+            | Indicator::Volatility(period) => *period,
+        }
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum MetricCollection {
+    Depths,
+    Swaps,
+}
+
+impl MetricCollection {
+    pub fn history_collection(&self) -> HistoryCollection {
+        match self {
+            MetricCollection::Depths => HistoryCollection::Depths,
+            MetricCollection::Swaps => HistoryCollection::Swaps,
+        }
+    }
+}
+
+// metric name as exposed by /depths and /swaps -> (collection, stored field, interval accumulator)
+// levels (depths, prices, slips) take the last value of the interval, flows (volumes, counts, fees) are summed
+pub fn resolve_metric(metric: &str) -> Option<(MetricCollection, &'static str, &'static str)> {
+    let resolved = match metric {
+        "assetDepth" => (MetricCollection::Depths, "asset_depth", "$last"),
+        "assetPrice" => (MetricCollection::Depths, "asset_price", "$last"),
+        "assetPriceUSD" => (MetricCollection::Depths, "asset_price_usd", "$last"),
+        "liquidityUnits" => (MetricCollection::Depths, "liquidity_units", "$last"),
+        "luvi" => (MetricCollection::Depths, "luvi", "$last"),
+        "membersCount" => (MetricCollection::Depths, "members_count", "$last"),
+        "runeDepth" => (MetricCollection::Depths, "rune_depth", "$last"),
+        "synthSupply" => (MetricCollection::Depths, "synth_supply", "$last"),
+        "synthUnits" => (MetricCollection::Depths, "synth_units", "$last"),
+        "units" => (MetricCollection::Depths, "units", "$last"),
+        "averageSlip" => (MetricCollection::Swaps, "average_slip", "$last"),
+        "runePriceUSD" => (MetricCollection::Swaps, "rune_price_usd", "$last"),
+        "totalCount" => (MetricCollection::Swaps, "total_count", "$sum"),
+        "totalFees" => (MetricCollection::Swaps, "total_fees", "$sum"),
+        "totalVolume" => (MetricCollection::Swaps, "total_volume", "$sum"),
+        "totalVolumeUSD" => (MetricCollection::Swaps, "total_volume_usd", "$sum"),
+        "toAssetVolume" => (MetricCollection::Swaps, "to_asset_volume", "$sum"),
+        "toAssetVolumeUSD" => (MetricCollection::Swaps, "to_asset_volume_usd", "$sum"),
+        "toRuneVolume" => (MetricCollection::Swaps, "to_rune_volume", "$sum"),
+        "toRuneVolumeUSD" => (MetricCollection::Swaps, "to_rune_volume_usd", "$sum"),
+        _ => return None,
+    };
+    Some(resolved)
+}
+
+// "sma:20,ema" -> [Sma(20), Ema(20)], periods default to 14 for rsi and 20 otherwise
+pub fn parse_indicators(indicators: &str) -> Result<Vec<Indicator>, CustomError> {
+    let mut parsed = Vec::new();
+    for spec in indicators.split(',').map(str::trim).filter(|spec| !spec.is_empty()) {
+        let (name, period) = match spec.split_once(':') {
+            Some((name, period)) => {
+                let period = period.parse::<usize>().map_err(|_| {
+                    CustomError::InvalidInput(format!("Invalid period in indicator {}", spec))
+                })?;
+                (name, Some(period))
+            }
+            None => (spec, None),
+        };
+        let indicator = match name {
+            "sma" => Indicator::Sma(period.unwrap_or(20)),
+            "ema" => Indicator::Ema(period.unwrap_or(20)),
+            "rsi" => Indicator::Rsi(period.unwrap_or(14)),
+            "bollinger" => Indicator::Bollinger(period.unwrap_or(20)),
+            "volatility" => Indicator::Volatility(period.unwrap_or(20)),
+            _ => return Err(CustomError::InvalidInput(format!("Unknown indicator {}, expected sma, ema, rsi, bollinger or volatility", name))),
+        };
+        if !(2..=200).contains(&indicator.period()) {
+            return Err(CustomError::InvalidInput("Indicator period has to be [2..200]".to_string()));
+        }
+        parsed.push(indicator);
+    }
+    if parsed.is_empty() {
+        return Err(CustomError::InvalidInput("At least one indicator is required".to_string()));
+    }
+    Ok(parsed)
+}
+
+pub fn validate_indicator_query(query: &IndicatorParams) -> Result<(), CustomError> {
+    if let (Some(start), Some(end)) = (query.from, query.to) {
+        if start >= end {
+            return Err(CustomError::InvalidInput("start_time must be less than end_time.".to_string()));
+        }
+    }
+
+    if let Some(count) = query.count{
+        if !(1..=400).contains(&count){
+            return Err(CustomError::InvalidInput("Count has to be [1..400]".to_string()));
+        }
+    }
+
+    if let Some(ref interval) = query.interval {
+        if !VALID_INTERVALS.contains(&interval.as_str()) {
+            return Err(CustomError::InvalidInput(format!("Interval must be in {:?}",VALID_INTERVALS)));
+        }
+    }
+
+    if resolve_metric(&query.metric).is_none() {
+        return Err(CustomError::InvalidInput(format!("Unsupported metric {}", query.metric)));
+    }
+
+    parse_indicators(&query.indicators)?;
+
+    Ok(())
+}
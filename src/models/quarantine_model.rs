@@ -0,0 +1,85 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::utils::constants::INGESTED_COLLECTIONS;
+
+use super::custom_error_model::CustomError;
+
+// a midgard interval the ingestion rejected, kept as received so it can be inspected and replayed
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub struct QuarantinedInterval{
+    pub _id : ObjectId,
+    // depths, swaps, earnings or runepool
+    pub collection : String,
+    // the pool of a depths or swaps page
+    pub pool : Option<String>,
+    pub interval : Value,
+    pub reasons : Vec<String>,
+    pub quarantined_at : i64
+}
+
+// /admin/quarantine entries, the id is what /admin/quarantine/{id}/replay takes
+#[derive(Debug,Serialize,ToSchema)]
+#[serde(rename_all="camelCase")]
+pub struct QuarantineEntry{
+    #[schema(example = "60d5ec49a1c4b5048c0e5c70")]
+    pub id : String,
+    #[schema(example = "depths")]
+    pub collection : String,
+    #[schema(example = "BTC.BTC")]
+    pub pool : Option<String>,
+    // the midgard interval as received
+    #[schema(value_type = Object)]
+    pub interval : Value,
+    #[schema(example = json!(["assetDepth \"abc\" is not a number"]))]
+    pub reasons : Vec<String>,
+    #[schema(example = 1647914500)]
+    pub quarantined_at : i64
+}
+
+impl From<QuarantinedInterval> for QuarantineEntry {
+    fn from(record: QuarantinedInterval) -> Self {
+        QuarantineEntry {
+            id: record._id.to_hex(),
+            collection: record.collection,
+            pool: record.pool,
+            interval: record.interval,
+            reasons: record.reasons,
+            quarantined_at: record.quarantined_at,
+        }
+    }
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+pub struct QuarantineParams{
+    #[schema(example = "depths")]
+    pub collection : Option<String>,
+    #[schema(example = "100")]
+    pub count : Option<u32>
+}
+
+pub fn validate_quarantine_query(query: &QuarantineParams) -> Result<(), CustomError> {
+    if let Some(ref collection) = query.collection {
+        if !INGESTED_COLLECTIONS.contains(&collection.as_str()) {
+            return Err(CustomError::InvalidInput(format!("collection must be in {:?}", INGESTED_COLLECTIONS)));
+        }
+    }
+    if let Some(count) = query.count {
+        if !(1..=400).contains(&count) {
+            return Err(CustomError::InvalidInput("Count has to be [1..400]".to_string()));
+        }
+    }
+    Ok(())
+}
+
+// outcome of /admin/quarantine/{id}/replay, an interval that still fails is quarantined again with its new reasons
+#[derive(Debug,Serialize,ToSchema)]
+#[serde(rename_all="camelCase")]
+pub struct ReplayReport{
+    #[schema(example = "60d5ec49a1c4b5048c0e5c70")]
+    pub id : String,
+    pub stored : bool,
+    pub quarantined : Option<QuarantineEntry>
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::custom_error_model::CustomError;
+
+pub const STREAM_TOPICS:[&str; 4] = ["swaps", "depths", "earnings", "runepool"];
+// the replay was cut, its id is the resume point for the rest of the backlog
+pub const GAP_TOPIC:&str = "gap";
+
+// one freshly stored interval, published by the ingestion services
+#[derive(Debug,Clone,Serialize,Deserialize,ToSchema)]
+#[serde(rename_all="camelCase")]
+pub struct StreamEvent{
+    // `<end_time>:<topic>:<pool>`, unique per event and the SSE id a reconnect resumes after
+    #[schema(example = "1653373410:swaps:BTC.BTC")]
+    pub id : String,
+    #[schema(example = "swaps")]
+    pub topic : String,
+    #[schema(example = "BTC.BTC")]
+    pub pool : Option<String>,
+    #[schema(example = 1653373410)]
+    pub end_time : i64,
+    // the stored record as written to its collection (without _id)
+    #[schema(value_type = Object)]
+    pub data : serde_json::Value
+}
+
+impl StreamEvent {
+    pub fn from_record<T: Serialize>(topic: &str, pool: Option<&str>, end_time: i64, record: &T) -> Self {
+        let mut data = serde_json::to_value(record).unwrap_or_default();
+        if let Some(data) = data.as_object_mut() {
+            data.remove("_id");
+        }
+        StreamEvent {
+            id: format!("{}:{}:{}", end_time, topic, pool.unwrap_or_default()),
+            topic: topic.to_string(),
+            pool: pool.map(str::to_string),
+            end_time,
+            data,
+        }
+    }
+
+    // resumes after the last replayed event
+    pub fn gap(last_replayed: &StreamEvent, replay_limit: i64) -> Self {
+        StreamEvent {
+            id: last_replayed.id.clone(),
+            topic: GAP_TOPIC.to_string(),
+            pool: None,
+            end_time: last_replayed.end_time,
+            data: serde_json::json!({ "replayLimit": replay_limit }),
+        }
+    }
+
+    pub fn is_gap(&self) -> bool {
+        self.topic == GAP_TOPIC
+    }
+
+    // the replay order, events of one end_time by topic and pool
+    pub fn order_key(&self) -> (i64, &str, &str) {
+        (self.end_time, &self.topic, self.pool.as_deref().unwrap_or_default())
+    }
+}
+
+// where a stream resumes, after every event of an earlier end_time and with an event id also after the events
+// of its end_time that order before it
+#[derive(Debug,Clone,PartialEq)]
+pub struct StreamCursor{
+    pub end_time : i64,
+    // (topic, pool) of the event id, None for a bare end_time
+    pub event : Option<(String, String)>
+}
+
+impl StreamCursor {
+    // an event id or a bare end_time
+    pub fn parse(id: &str) -> Result<Self, CustomError> {
+        let invalid = || CustomError::InvalidInput(format!("{} is not an event id or an end_time", id));
+        let mut parts = id.trim().splitn(3, ':');
+        let end_time = parts.next().and_then(|end_time| end_time.parse::<i64>().ok()).ok_or_else(invalid)?;
+        let event = match (parts.next(), parts.next()) {
+            (None, _) => None,
+            (Some(topic), Some(pool)) => Some((topic.to_string(), pool.to_string())),
+            (Some(_), None) => return Err(invalid()),
+        };
+        Ok(StreamCursor { end_time, event })
+    }
+
+    // the event comes after the cursor and is replayed
+    pub fn precedes(&self, event: &StreamEvent) -> bool {
+        let (end_time, topic, pool) = event.order_key();
+        match &self.event {
+            None => end_time > self.end_time,
+            Some((cursor_topic, cursor_pool)) => (end_time, topic, pool) > (self.end_time, cursor_topic.as_str(), cursor_pool.as_str()),
+        }
+    }
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+pub struct StreamParams{
+    #[schema(example = "swaps:BTC.BTC,depths:*")]
+    pub topics : String,
+    // resume point, an end_time or an event id, every stored interval after it is replayed before live events
+    #[schema(example = "1653373410:swaps:BTC.BTC")]
+    pub since : Option<String>
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Subscription{
+    pub topic : String,
+    // None subscribes to every pool of the topic
+    pub pool : Option<String>
+}
+
+impl Subscription {
+    pub fn matches(&self, event: &StreamEvent) -> bool {
+        self.topic == event.topic && (self.pool.is_none() || self.pool == event.pool)
+    }
+}
+
+// "swaps:BTC.BTC,depths:*,runepool" -> subscriptions
+pub fn parse_topics(topics: &str) -> Result<Vec<Subscription>, CustomError> {
+    let mut subscriptions = Vec::new();
+    for topic in topics.split(',').map(str::trim).filter(|topic| !topic.is_empty()) {
+        let (name, pool) = match topic.split_once(':') {
+            Some((name, "*")) => (name, None),
+            Some((name, pool)) => (name, Some(pool.to_string())),
+            None => (topic, None),
+        };
+        if !STREAM_TOPICS.contains(&name) {
+            return Err(CustomError::InvalidInput(format!("topic must be in {:?}", STREAM_TOPICS)));
+        }
+        if name == "runepool" && pool.is_some() {
+            return Err(CustomError::InvalidInput("runepool topic has no pools".to_string()));
+        }
+        subscriptions.push(Subscription { topic: name.to_string(), pool });
+    }
+    if subscriptions.is_empty() {
+        return Err(CustomError::InvalidInput("At least one topic is required".to_string()));
+    }
+    Ok(subscriptions)
+}
@@ -1,13 +1,14 @@
 use mongodb::bson::oid::ObjectId;
+use async_graphql::SimpleObject;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
-use std::error::Error as stdError;
-use crate::{parse_field, services::swap_history_service::Interval};
+use crate::{models::custom_error_model::CustomError, parse_field, utils::parser_utils::deserialize_amount, services::swap_history_service::Interval};
 
-#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[derive(Debug,Serialize,Deserialize,ToSchema,SimpleObject)]
 #[schema(rename_all="camelCase")]
 pub struct SwapHistory {
     #[schema(value_type = String, example = "60d5ec49a1c4b5048c0e5c70",rename="._id not exposed in the response")]
+    #[graphql(skip)]
     pub _id: ObjectId,
     #[schema(example="BTC.BTC")]
     pub pool: String,
@@ -19,77 +20,99 @@ pub struct SwapHistory {
     pub from_trade_average_slip: f64,
     #[schema(example=8967017)]
     pub from_trade_count: i64,
-    #[schema(example=8.508409670179631)]
-    pub from_trade_fees: f64,
-    #[schema(example=8.508409670179631)]
-    pub from_trade_volume: f64,
-    #[schema(example=8.508409670179631)]
+    #[schema(example=1507436)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub from_trade_fees: i64,
+    #[schema(example=3452179212_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub from_trade_volume: i64,
+    #[schema(example=8.508409670179631)]
+    #[graphql(name="fromTradeVolumeUSD")]
     pub from_trade_volume_usd: f64,
     #[schema(example=8.508409670179631)]
+    #[graphql(name="runePriceUSD")]
     pub rune_price_usd: f64,
     pub start_time: i64,
     #[schema(example=8.508409670179631)]
     pub synth_mint_average_slip: f64,
     pub synth_mint_count: i64,
-    #[schema(example=8.508409670179631)]
-    pub synth_mint_fees: f64,
-    #[schema(example=8.508409670179631)]
-    pub synth_mint_volume: f64,
-    #[schema(example=8.508409670179631)]
+    #[schema(example=1507436)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub synth_mint_fees: i64,
+    #[schema(example=3452179212_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub synth_mint_volume: i64,
+    #[schema(example=8.508409670179631)]
+    #[graphql(name="synthMintVolumeUSD")]
     pub synth_mint_volume_usd: f64,
     #[schema(example=8.508409670179631)]
     pub synth_redeem_average_slip: f64,
-    #[schema(example=8.508409670179631)]
+    #[schema(example=12)]
     pub synth_redeem_count: i64,
-    #[schema(example=8.508409670179631)]
-    pub synth_redeem_fees: f64,
-    #[schema(example=8.508409670179631)]
-    pub synth_redeem_volume: f64,
+    #[schema(example=1507436)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub synth_redeem_fees: i64,
+    #[schema(example=3452179212_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub synth_redeem_volume: i64,
     #[schema(example=8.508409670179631,rename="synthRedeemVolumeUSD")]
+    #[graphql(name="synthRedeemVolumeUSD")]
     pub synth_redeem_volume_usd: f64,
     #[schema(example=8.508409670179631)]
     pub to_asset_average_slip: f64,
-    #[schema(example=8.508409670179631)]
+    #[schema(example=12)]
     pub to_asset_count: i64,
-    #[schema(example=8.508409670179631)]
-    pub to_asset_fees: f64,
-    #[schema(example=8.508409670179631)]
-    pub to_asset_volume: f64,
+    #[schema(example=1507436)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub to_asset_fees: i64,
+    #[schema(example=3452179212_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub to_asset_volume: i64,
     #[schema(example=8.508409670179631,rename="toAssetVolumeUSD")]
+    #[graphql(name="toAssetVolumeUSD")]
     pub to_asset_volume_usd: f64,
     #[schema(example=8.508409670179631)]
     pub to_rune_average_slip: f64,
-    #[schema(example=8.508409670179631)]
+    #[schema(example=12)]
     pub to_rune_count: i64,
-    #[schema(example=8.508409670179631)]
-    pub to_rune_fees: f64,
-    #[schema(example=8.508409670179631)]
-    pub to_rune_volume: f64,
+    #[schema(example=1507436)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub to_rune_fees: i64,
+    #[schema(example=3452179212_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub to_rune_volume: i64,
     #[schema(example=8.508409670179631,rename="toRuneVolumeUSD")]
+    #[graphql(name="toRuneVolumeUSD")]
     pub to_rune_volume_usd: f64,
     #[schema(example=8.508409670179631)]
     pub to_trade_average_slip: f64,
-    #[schema(example=8.508409670179631)]
+    #[schema(example=12)]
     pub to_trade_count: i64,
-    #[schema(example=8.508409670179631)]
-    pub to_trade_fees: f64,
-    #[schema(example=8.508409670179631)]
-    pub to_trade_volume: f64,
+    #[schema(example=1507436)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub to_trade_fees: i64,
+    #[schema(example=3452179212_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub to_trade_volume: i64,
     #[schema(example=8.508409670179631,rename="toTradeVolumeUSD")]
+    #[graphql(name="toTradeVolumeUSD")]
     pub to_trade_volume_usd: f64,
-    #[schema(example=8.508409670179631)]
+    #[schema(example=12)]
     pub total_count: i64,
-    #[schema(example=8.508409670179631)]
-    pub total_fees: f64,
-    #[schema(example=8.508409670179631)]
-    pub total_volume: f64,
+    #[schema(example=1507436)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub total_fees: i64,
+    #[schema(example=3452179212_i64)]
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub total_volume: i64,
     #[schema(example=8.508409670179631,rename="totalVolumeUSD")]
+    #[graphql(name="totalVolumeUSD")]
     pub total_volume_usd: f64,
 }
 
 
 impl SwapHistory {
-    pub fn to_swap_history(interval: Interval, pool: &str) -> Result<Self, Box<dyn stdError>> {
+    pub fn to_swap_history(interval: Interval, pool: &str) -> Result<Self, CustomError> {
         let _id = ObjectId::new();
         let pool = pool.to_string();
         
@@ -100,39 +123,39 @@ impl SwapHistory {
             end_time: parse_field!(interval, end_time, i64),
             from_trade_average_slip: parse_field!(interval, from_trade_average_slip, f64),
             from_trade_count: parse_field!(interval, from_trade_count, i64),
-            from_trade_fees: parse_field!(interval, from_trade_fees, f64),
-            from_trade_volume: parse_field!(interval, from_trade_volume, f64),
+            from_trade_fees: parse_field!(interval, from_trade_fees, i64),
+            from_trade_volume: parse_field!(interval, from_trade_volume, i64),
             from_trade_volume_usd: parse_field!(interval, from_trade_volume_usd, f64),
             rune_price_usd: parse_field!(interval, rune_price_usd, f64),
             start_time: parse_field!(interval, start_time, i64),
             synth_mint_average_slip: parse_field!(interval, synth_mint_average_slip, f64),
             synth_mint_count: parse_field!(interval, synth_mint_count, i64),
-            synth_mint_fees: parse_field!(interval, synth_mint_fees, f64),
-            synth_mint_volume: parse_field!(interval, synth_mint_volume, f64),
+            synth_mint_fees: parse_field!(interval, synth_mint_fees, i64),
+            synth_mint_volume: parse_field!(interval, synth_mint_volume, i64),
             synth_mint_volume_usd: parse_field!(interval, synth_mint_volume_usd, f64),
             synth_redeem_average_slip: parse_field!(interval, synth_redeem_average_slip, f64),
             synth_redeem_count: parse_field!(interval, synth_redeem_count, i64),
-            synth_redeem_fees: parse_field!(interval, synth_redeem_fees, f64),
-            synth_redeem_volume: parse_field!(interval, synth_redeem_volume, f64),
+            synth_redeem_fees: parse_field!(interval, synth_redeem_fees, i64),
+            synth_redeem_volume: parse_field!(interval, synth_redeem_volume, i64),
             synth_redeem_volume_usd: parse_field!(interval, synth_redeem_volume_usd, f64),
             to_asset_average_slip: parse_field!(interval, to_asset_average_slip, f64),
             to_asset_count: parse_field!(interval, to_asset_count, i64),
-            to_asset_fees: parse_field!(interval, to_asset_fees, f64),
-            to_asset_volume: parse_field!(interval, to_asset_volume, f64),
+            to_asset_fees: parse_field!(interval, to_asset_fees, i64),
+            to_asset_volume: parse_field!(interval, to_asset_volume, i64),
             to_asset_volume_usd: parse_field!(interval, to_asset_volume_usd, f64),
             to_rune_average_slip: parse_field!(interval, to_rune_average_slip, f64),
             to_rune_count: parse_field!(interval, to_rune_count, i64),
-            to_rune_fees: parse_field!(interval, to_rune_fees, f64),
-            to_rune_volume: parse_field!(interval, to_rune_volume, f64),
+            to_rune_fees: parse_field!(interval, to_rune_fees, i64),
+            to_rune_volume: parse_field!(interval, to_rune_volume, i64),
             to_rune_volume_usd: parse_field!(interval, to_rune_volume_usd, f64),
             to_trade_average_slip: parse_field!(interval, to_trade_average_slip, f64),
             to_trade_count: parse_field!(interval, to_trade_count, i64),
-            to_trade_fees: parse_field!(interval, to_trade_fees, f64),
-            to_trade_volume: parse_field!(interval, to_trade_volume, f64),
+            to_trade_fees: parse_field!(interval, to_trade_fees, i64),
+            to_trade_volume: parse_field!(interval, to_trade_volume, i64),
             to_trade_volume_usd: parse_field!(interval, to_trade_volume_usd, f64),
             total_count: parse_field!(interval, total_count, i64),
-            total_fees: parse_field!(interval, total_fees, f64),
-            total_volume: parse_field!(interval, total_volume, f64),
+            total_fees: parse_field!(interval, total_fees, i64),
+            total_volume: parse_field!(interval, total_volume, i64),
             total_volume_usd: parse_field!(interval, total_volume_usd, f64),
         })
     }
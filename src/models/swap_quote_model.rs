@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::custom_error_model::CustomError;
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+pub struct SwapQuoteParams{
+    #[schema(example = "BTC.BTC")]
+    pub from : String,
+    #[schema(example = "THOR.RUNE")]
+    pub to : String,
+    #[schema(example = 100000000.0)]
+    pub amount : f64,
+    #[schema(example = 1653373410)]
+    pub at : Option<u64>
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[serde(rename_all="camelCase")]
+pub struct SwapQuote{
+    #[schema(example = "BTC.BTC")]
+    pub from : String,
+    #[schema(example = "THOR.RUNE")]
+    pub to : String,
+    #[schema(example = 100000000.0)]
+    pub amount : f64,
+    #[schema(example = 1653373410)]
+    pub depth_end_time : i64,
+    #[schema(example = 2850312345.12)]
+    pub expected_output : f64,
+    #[schema(example = 1234567.0)]
+    pub fees : f64,
+    #[schema(example = 12.5)]
+    pub slip_bps : f64,
+    #[schema(example = 8.0)]
+    pub observed_average_slip_bps : Option<f64>
+}
+
+pub fn validate_swap_quote_query(query: &SwapQuoteParams) -> Result<(), CustomError> {
+    if query.from == query.to {
+        return Err(CustomError::InvalidInput("from and to assets must differ".to_string()));
+    }
+    if !query.amount.is_finite() || query.amount <= 0.0 {
+        return Err(CustomError::InvalidInput("amount must be a positive 1e8 scaled number".to_string()));
+    }
+    Ok(())
+}
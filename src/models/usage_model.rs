@@ -0,0 +1,56 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::custom_error_model::CustomError;
+
+// one document per client, day and route group, counters are bumped with $inc
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+#[schema(rename_all="camelCase")]
+pub struct ApiUsage{
+    #[schema(value_type = String, example = "60d5ec49a1c4b5048c0e5c70",rename="._id not exposed in the response")]
+    pub _id : ObjectId,
+    // "key:<prefix>" for API key clients, "ip:<address>" otherwise
+    #[schema(example="key:tm_AbC12345")]
+    pub client : String,
+    #[schema(example="2024-10-19")]
+    pub day : String,
+    #[schema(example="earnings")]
+    pub route_group : String,
+    #[schema(example=120)]
+    pub requests : i64,
+    #[schema(example=4)]
+    pub throttled : i64,
+    // sum of the route group costs of the allowed requests
+    #[schema(example=1200)]
+    pub cost : i64
+}
+
+#[derive(Debug,Serialize,Deserialize,ToSchema)]
+pub struct UsageParams{
+    // only honoured for admin keys, other keys always get their own usage
+    #[schema(example = "key:tm_AbC12345")]
+    pub client : Option<String>,
+    #[schema(example = "2024-10-01")]
+    pub from : Option<String>,
+    #[schema(example = "2024-10-19")]
+    pub to : Option<String>
+}
+
+fn is_day(day: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").is_ok()
+}
+
+pub fn validate_usage_query(query: &UsageParams) -> Result<(), CustomError> {
+    for day in [&query.from, &query.to].into_iter().flatten() {
+        if !is_day(day) {
+            return Err(CustomError::InvalidInput("from and to must be YYYY-MM-DD days".to_string()));
+        }
+    }
+    if let (Some(from), Some(to)) = (&query.from, &query.to) {
+        if from > to {
+            return Err(CustomError::InvalidInput("from must not be after to".to_string()));
+        }
+    }
+    Ok(())
+}
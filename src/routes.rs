@@ -1,4 +1,16 @@
 pub mod depth_route;
 pub mod earning_route;
 pub mod swap_route;
-pub mod rune_pool_route;
\ No newline at end of file
+pub mod rune_pool_route;
+pub mod tools_route;
+pub mod candle_route;
+pub mod indicator_route;
+pub mod anomaly_route;
+pub mod alert_route;
+pub mod stream_route;
+pub mod graphql_route;
+pub mod admin_route;
+pub mod usage_route;
+pub mod metrics_route;
+pub mod health_route;
+pub mod midgard_route;
\ No newline at end of file
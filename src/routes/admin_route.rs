@@ -0,0 +1,206 @@
+use actix_web::{web::{self, ServiceConfig}, HttpResponse, Responder};
+use chrono::Utc;
+use crate::{
+    config::Config,
+    models::{custom_error_model::CustomError, quarantine_model::{validate_quarantine_query, QuarantineEntry, QuarantineParams}},
+    services::{backfill_service::backfill, db::DataBase, validation_service::replay_quarantined},
+};
+use serde_json::Value;
+use tracing::error;
+
+// every route here requires an admin X-API-Key (see main.rs)
+
+// Expensive function
+#[utoipa::path(
+    post,
+    path = "/admin/fetch-depths-all",
+    responses(
+        (status = 200, description = "Backfilled the depth history of every configured pool from ingestion.start_time, returns a report per pool"),
+        (status = 401, description = "Missing, invalid or revoked API key"),
+        (status = 403, description = "API key is not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Admin"
+)]
+#[actix_web::post("/fetch-depths-all")]
+pub async fn fetch_all_depths_to_db(db:web::Data<DataBase>,config:web::Data<Config>) -> impl Responder{
+    // backfill skips failed pages, the reports tell how many were skipped
+    match backfill(db.get_ref(), &config, "depths", None, config.ingestion.start_time, Utc::now().timestamp()).await {
+        Ok(reports) => HttpResponse::Ok().json(reports),
+        Err(e) => {
+            error!(error = ?e, "Error at /admin backfill");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+// Expensive function
+#[utoipa::path(
+    post,
+    path = "/admin/fetch-swaps-all",
+    responses(
+        (status = 200, description = "Backfilled the swap history of every configured pool from ingestion.start_time, returns a report per pool"),
+        (status = 401, description = "Missing, invalid or revoked API key"),
+        (status = 403, description = "API key is not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Admin"
+)]
+#[actix_web::post("/fetch-swaps-all")]
+pub async fn fetch_all_swaps_to_db(db:web::Data<DataBase>,config:web::Data<Config>) -> impl Responder{
+    match backfill(db.get_ref(), &config, "swaps", None, config.ingestion.start_time, Utc::now().timestamp()).await {
+        Ok(reports) => HttpResponse::Ok().json(reports),
+        Err(e) => {
+            error!(error = ?e, "Error at /admin backfill");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+// Expensive function
+#[utoipa::path(
+    post,
+    path = "/admin/fetch-earnings-all",
+    responses(
+        (status = 200, description = "Backfilled earnings of all pools from ingestion.start_time, returns a report per pool"),
+        (status = 401, description = "Missing, invalid or revoked API key"),
+        (status = 403, description = "API key is not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Admin"
+)]
+#[actix_web::post("/fetch-earnings-all")]
+pub async fn fetch_all_earnings_to_db(db:web::Data<DataBase>,config:web::Data<Config>) -> impl Responder{
+    match backfill(db.get_ref(), &config, "earnings", None, config.ingestion.start_time, Utc::now().timestamp()).await {
+        Ok(reports) => HttpResponse::Ok().json(reports),
+        Err(e) => {
+            error!(error = ?e, "Error at /admin backfill");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+// Expensive function
+#[utoipa::path(
+    post,
+    path = "/admin/fetch-rune-pools-all",
+    responses(
+        (status = 200, description = "Backfilled rune pool history from ingestion.start_time, returns a report per pool"),
+        (status = 401, description = "Missing, invalid or revoked API key"),
+        (status = 403, description = "API key is not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Admin"
+)]
+#[actix_web::post("/fetch-rune-pools-all")]
+pub async fn fetch_all_rune_pools_to_db(db:web::Data<DataBase>,config:web::Data<Config>) -> impl Responder{
+    match backfill(db.get_ref(), &config, "runepool", None, config.ingestion.start_time, Utc::now().timestamp()).await {
+        Ok(reports) => HttpResponse::Ok().json(reports),
+        Err(e) => {
+            error!(error = ?e, "Error at /admin backfill");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/quarantine",
+    params(
+        ("collection" = Option<String>, Query, description = "Ingested collection `(depths, swaps, earnings, runepool)`"),
+        ("count" = Option<u32>, Query, description = "Entries to return `(1-400)`, defaults to `100`")
+    ),
+    responses(
+        (status = 200, description = "Midgard intervals that failed validation with their reasons, oldest first", body = Vec<QuarantineEntry>),
+        (status = 400, description = "Bad request - Invalid parameters"),
+        (status = 401, description = "Missing, invalid or revoked API key"),
+        (status = 403, description = "API key is not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Admin"
+)]
+#[actix_web::get("/quarantine")]
+pub async fn get_quarantine(db:web::Data<DataBase>,params:web::Query<QuarantineParams>) -> HttpResponse{
+    if let Err(validation_err) = validate_quarantine_query(&params) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    match db.store.quarantined(params.collection.as_deref(), params.count.unwrap_or(100) as i64).await {
+        Ok(records) => HttpResponse::Ok().json(records.into_iter().map(QuarantineEntry::from).collect::<Vec<_>>()),
+        Err(e) => {
+            error!(error = ?e, "Error at /admin/quarantine");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/quarantine/{id}",
+    params(("id" = String, Path, description = "Quarantine entry id")),
+    responses(
+        (status = 200, description = "The quarantined interval with its reasons", body = QuarantineEntry),
+        (status = 404, description = "No quarantined interval with the id"),
+        (status = 401, description = "Missing, invalid or revoked API key"),
+        (status = 403, description = "API key is not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Admin"
+)]
+#[actix_web::get("/quarantine/{id}")]
+pub async fn get_quarantined_interval(db:web::Data<DataBase>,id:web::Path<String>) -> HttpResponse{
+    match db.store.quarantined_interval(&id).await {
+        Ok(Some(record)) => HttpResponse::Ok().json(QuarantineEntry::from(record)),
+        Ok(None) => HttpResponse::NotFound().json(CustomError::InvalidInput(format!("No quarantined interval {}", id))),
+        Err(e) => {
+            error!(error = ?e, "Error at /admin/quarantine");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/quarantine/{id}/replay",
+    params(("id" = String, Path, description = "Quarantine entry id")),
+    request_body(content = Option<Object>, description = "Corrected midgard interval replacing the received one, replays the received interval when empty"),
+    responses(
+        (status = 200, description = "The interval went through validation again, stored or quarantined anew with its reasons", body = ReplayReport),
+        (status = 400, description = "Bad request - The body is not a midgard interval"),
+        (status = 404, description = "No quarantined interval with the id"),
+        (status = 401, description = "Missing, invalid or revoked API key"),
+        (status = 403, description = "API key is not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Admin"
+)]
+#[actix_web::post("/quarantine/{id}/replay")]
+pub async fn replay_quarantined_interval(db:web::Data<DataBase>,id:web::Path<String>,body:web::Bytes) -> HttpResponse{
+    let interval = if body.is_empty() {
+        None
+    } else {
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(interval) => Some(interval),
+            Err(e) => return HttpResponse::BadRequest().json(CustomError::InvalidInput(format!("Invalid interval {}", e))),
+        }
+    };
+    match replay_quarantined(db.get_ref(), &id, interval).await {
+        Ok(Some(report)) => HttpResponse::Ok().json(report),
+        Ok(None) => HttpResponse::NotFound().json(CustomError::InvalidInput(format!("No quarantined interval {}", id))),
+        Err(CustomError::InvalidInput(e)) => HttpResponse::BadRequest().json(CustomError::InvalidInput(e)),
+        Err(e) => {
+            error!(error = ?e, "Error at /admin/quarantine replay");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config
+        .service(fetch_all_depths_to_db)
+        .service(fetch_all_swaps_to_db)
+        .service(fetch_all_earnings_to_db)
+        .service(fetch_all_rune_pools_to_db)
+        .service(get_quarantine)
+        .service(get_quarantined_interval)
+        .service(replay_quarantined_interval);
+}
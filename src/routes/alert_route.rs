@@ -0,0 +1,107 @@
+use actix_web::{middleware::from_fn, web::{self, ServiceConfig}, HttpResponse};
+use crate::{middlewares::api_key_middleware::require_admin_key, models::{alert_rule_model::{validate_alert_rule, AlertRuleRequest}, custom_error_model::CustomError}, services::db::DataBase};
+use tracing::error;
+
+#[utoipa::path(
+    post,
+    path = "/alerts",
+    request_body = AlertRuleRequest,
+    responses(
+        (status = 201, description = "Rule created, returns its id"),
+        (status = 400, description = "Bad request - Invalid rule"),
+        (status = 403, description = "The API key is not an admin key"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("api_key" = [])),
+    tag = "Alerts"
+)]
+// rules carry webhook urls the server posts to, only admins add them, read keys list them
+#[actix_web::post("", wrap = "from_fn(require_admin_key)")]
+pub async fn create_alert_rule(db:web::Data<DataBase>,rule:web::Json<AlertRuleRequest>) -> HttpResponse{
+    if let Err(validation_err) = validate_alert_rule(&rule) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    match db.create_alert_rule_api(rule.into_inner()).await {
+        Ok(result) => HttpResponse::Created().json(result),
+        Err(e) => {
+            error!(error = ?e, "Error at POST /alerts");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/alerts",
+    responses(
+        (status = 200, description = "Alert rules without their secrets", body = Vec<AlertRule>),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("api_key" = [])),
+    tag = "Alerts"
+)]
+#[actix_web::get("")]
+pub async fn get_alert_rules(db:web::Data<DataBase>) -> HttpResponse{
+    match db.get_alert_rules_api().await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => {
+            error!(error = ?e, "Error at /alerts");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/alerts/{id}",
+    params(("id" = String, Path, description = "Rule id")),
+    responses(
+        (status = 204, description = "Rule deleted"),
+        (status = 400, description = "Bad request - Invalid id"),
+        (status = 403, description = "The API key is not an admin key"),
+        (status = 404, description = "Rule not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("api_key" = [])),
+    tag = "Alerts"
+)]
+#[actix_web::delete("/{id}", wrap = "from_fn(require_admin_key)")]
+pub async fn delete_alert_rule(db:web::Data<DataBase>,id:web::Path<String>) -> HttpResponse{
+    match db.delete_alert_rule_api(&id).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => HttpResponse::NotFound().json(CustomError::InvalidInput(format!("No alert rule {}", id))),
+        Err(CustomError::InvalidInput(e)) => HttpResponse::BadRequest().json(CustomError::InvalidInput(e)),
+        Err(e) => {
+            error!(error = ?e, "Error at DELETE /alerts");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/alerts/{id}/events",
+    params(("id" = String, Path, description = "Rule id")),
+    responses(
+        (status = 200, description = "Latest firings of the rule with their webhook delivery status", body = Vec<AlertEvent>),
+        (status = 400, description = "Bad request - Invalid id"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("api_key" = [])),
+    tag = "Alerts"
+)]
+#[actix_web::get("/{id}/events")]
+pub async fn get_alert_events(db:web::Data<DataBase>,id:web::Path<String>) -> HttpResponse{
+    match db.get_alert_events_api(&id).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(CustomError::InvalidInput(e)) => HttpResponse::BadRequest().json(CustomError::InvalidInput(e)),
+        Err(e) => {
+            error!(error = ?e, "Error at /alerts/events");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(create_alert_rule).service(get_alert_rules).service(delete_alert_rule).service(get_alert_events);
+}
@@ -0,0 +1,40 @@
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use crate::{models::anomaly_model::{validate_anomaly_query, AnomalyParams}, services::db::DataBase};
+use tracing::error;
+
+#[utoipa::path(
+    get,
+    path = "/anomalies",
+    params(
+        ("pool" = Option<String>, Query, description = "Pool identifier like `BTC.BTC`"),
+        ("kind" = Option<String>, Query, description = "Anomaly kind `(volume_spike, depth_drop, slip_spike)`"),
+        ("severity" = Option<String>, Query, description = "Comma separated severities to include `(low, medium, high)`"),
+        ("from" = Option<u64>, Query, description = "Start time Unix timestamp of the scored interval"),
+        ("to" = Option<u64>, Query, description = "End time Unix timestamp of the scored interval"),
+        ("page" = Option<u64>, Query, description = "Page number (minimum: `1`)"),
+        ("count" = Option<u32>, Query, description = "Anomalies per page `(1-400)`, defaults to `100`")
+    ),
+    responses(
+        (status = 200, description = "Recorded anomalies, latest first", body = Vec<Anomaly>),
+        (status = 400, description = "Bad request - Invalid parameters"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Anomalies"
+)]
+#[actix_web::get("")]
+pub async fn get_anomalies(db:web::Data<DataBase>,params:web::Query<AnomalyParams>) -> HttpResponse{
+    if let Err(validation_err) = validate_anomaly_query(&params) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    match db.get_anomalies_api(params.into_inner()).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => {
+            error!(error = ?e, "Error at /anomalies");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(get_anomalies);
+}
@@ -0,0 +1,39 @@
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use crate::{models::candle_model::{validate_candle_query, CandleParams}, services::db::DataBase};
+use tracing::error;
+
+#[utoipa::path(
+    get,
+    path = "/candles",
+    params(
+        ("pool" = String, Query, description = "Pool identifier like `BTC.BTC`, use `THOR.RUNE` for RUNE/USD candles built from the swaps `runePriceUSD`"),
+        ("interval" = Option<String>, Query, description = "Candle interval `(hour, day, week, month, quarter, year)`"),
+        ("quote" = Option<String>, Query, description = "`usd` (default) for `assetPriceUSD` or `rune` for `assetPrice`"),
+        ("from" = Option<u64>, Query, description = "Start time Unix timestamp, if not specified, from = `(latest record (or) to_time - interval_dur*count)`"),
+        ("to" = Option<u64>, Query, description = "End time Unix timestamp"),
+        ("count" = Option<u32>, Query, description = "Number of candles `(1-400)`")
+    ),
+    responses(
+        (status = 200, description = "Open/high/low/close series in ascending time order", body = CandleSeries),
+        (status = 400, description = "Bad request - Invalid parameters"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Candles"
+)]
+#[actix_web::get("")]
+pub async fn get_candles(db:web::Data<DataBase>,params:web::Query<CandleParams>) -> HttpResponse{
+    if let Err(validation_err) = validate_candle_query(&params) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    match db.get_candles_api(params.into_inner()).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => {
+            error!(error = ?e, "Error at /candles");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(get_candles);
+}
@@ -1,6 +1,6 @@
-use actix_web::{web::{self, ServiceConfig}, HttpResponse, Responder};
-use chrono::Utc;
-use crate::{models::{api_request_param_model::{validate_query, QueryParams}, depth_history_model::PoolDepthPriceHistory}, services::db::DataBase};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use crate::{config::Config, models::api_request_param_model::{validate_pool, validate_query, QueryParams}, services::db::DataBase};
+use tracing::error;
 
 #[utoipa::path(
     get,
@@ -8,7 +8,7 @@ use crate::{models::{api_request_param_model::{validate_query, QueryParams}, dep
     params(
         ("from" = Option<u64>, Query, description = "Start time Unix timestamp, if not specified, from = `(current_time (or) to_time - interval_dur*count)`"),
         ("to" = Option<u64>, Query, description = "End time Unix timestamp"),
-        ("pool" = Option<String>, Query, description = "Pool identifier - only `BTC.BTC` for now"),
+        ("pool" = Option<String>, Query, description = "Pool identifier, one of the configured `pools`"),
         ("page" = Option<u64>, Query, description = "Page number (minimum: `1`)"),
         ("limit" = Option<u32>, Query, description = "Items per page `(1-400)`"),
         ("sort_by" = Option<String>, Query, description = "Field to sort by"),
@@ -18,52 +18,26 @@ use crate::{models::{api_request_param_model::{validate_query, QueryParams}, dep
     ),
     responses(
         (status = 200, description = "List of pool depth price history", body = Vec<PoolDepthPriceHistory>),
-        (status = 400, description = "Bad request - Invalid parameters(for now pools except BTC are also considered bad requests)"),
+        (status = 400, description = "Bad request - Invalid parameters or a pool that is not configured"),
         (status = 500, description = "Internal server error")
     ),
     tag = "Depth and Price History"
 )]
 #[actix_web::get("")]
-pub async fn get_depth_price_history(db:web::Data<DataBase>,params:web::Query<QueryParams>) -> HttpResponse{
-    if let Err(validation_err) = validate_query(&params) {
-        println!("{:?}",&validation_err);
+pub async fn get_depth_price_history(db:web::Data<DataBase>,config:web::Data<Config>,params:web::Query<QueryParams>) -> HttpResponse{
+    if let Err(validation_err) = validate_query(&params).and_then(|_| validate_pool(&params, &config.pools)) {
         return HttpResponse::BadRequest().json(validation_err);
     }
-    match db.get_depth_price_history_api(params.into_inner()).await {
+    match db.store.get_depth_price_history_api(params.into_inner()).await {
         Ok(result) => HttpResponse::Ok().json(result),
         Err(e) => {
-            eprint!("Error at /depths {:?}",e);
+            error!(error = ?e, "Error at /depths");
             HttpResponse::InternalServerError().json(e)
         }
     }
 }
 
-// Protected route
-// Expensive function
-#[actix_web::get("/fetch-depths-all")]
-pub async fn fetch_all_depths_to_db(db:web::Data<DataBase>) -> impl Responder{
-    let current_time_stamp = Utc::now().timestamp();
-    // epoch value of the api's start derived from the midgard metadata
-    let mut start = 1647913096;
-    loop {
-        let end_time = match PoolDepthPriceHistory::fetch_price_history(db.get_ref(), "BTC.BTC", "hour", "400", &start.to_string()).await {
-            Ok(response) => response,
-            Err(e) => {
-                println!("Failed to fetch and update db with fetch price history! {:?}\n",e);
-                current_time_stamp+10
-            }
-        };
-        if end_time >= current_time_stamp{
-            break;
-        }
-        start = end_time;
-    }
-    // adapting non-strict fetch, error at a bucket doesn't stop whole fetch process
-    // because observed failure due to network multiple times with midgard while testing
-    HttpResponse::Ok().body(format!("Fetched and added depth records to database"))
-}
-
 pub fn init(config:&mut ServiceConfig){
-    config.service(fetch_all_depths_to_db).service(get_depth_price_history);
+    config.service(get_depth_price_history);
     ()
 }
\ No newline at end of file
@@ -0,0 +1,30 @@
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use async_graphql::http::GraphiQLSource;
+
+use crate::graphql::schema::ApiSchema;
+
+#[utoipa::path(
+    post,
+    path = "/graphql",
+    request_body(content = Object, description = "GraphQL request `{ query, variables, operationName }` over depths, swaps, earnings (with nested earningsSummary), earningsSummary and runePool"),
+    responses(
+        (status = 200, description = "GraphQL response, errors are reported in its `errors` field")
+    ),
+    tag = "GraphQL"
+)]
+#[actix_web::post("")]
+pub async fn graphql_query(schema:web::Data<ApiSchema>,request:web::Json<async_graphql::Request>) -> HttpResponse{
+    HttpResponse::Ok().json(schema.execute(request.into_inner()).await)
+}
+
+// GraphiQL explorer for the schema
+#[actix_web::get("")]
+pub async fn graphql_playground() -> HttpResponse{
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(graphql_query).service(graphql_playground);
+}
@@ -0,0 +1,63 @@
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use serde_json::json;
+use tracing::error;
+use crate::{config::Config, services::db::DataBase};
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, description = "Process is up, does not touch the database")
+    ),
+    tag = "Health"
+)]
+#[actix_web::get("/health")]
+pub async fn get_health() -> HttpResponse{
+    HttpResponse::Ok().json(json!({ "status": "ok" }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/ready",
+    responses(
+        (status = 200, description = "MongoDB answers a ping"),
+        (status = 503, description = "MongoDB is unreachable")
+    ),
+    tag = "Health"
+)]
+#[actix_web::get("/ready")]
+pub async fn get_ready(db:web::Data<DataBase>) -> HttpResponse{
+    match db.ping_api().await {
+        Ok(()) => HttpResponse::Ok().json(json!({ "status": "ready" })),
+        Err(e) => {
+            error!(error = ?e, "Error at /ready");
+            HttpResponse::ServiceUnavailable().json(json!({ "status": "unavailable", "error": e.to_string() }))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/data",
+    responses(
+        (status = 200, description = "Every ingested collection and configured pool is within limits.max_data_lag_secs", body = DataFreshness),
+        (status = 503, description = "A collection is empty or lags more than limits.max_data_lag_secs", body = DataFreshness),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Health"
+)]
+#[actix_web::get("/health/data")]
+pub async fn get_data_health(db:web::Data<DataBase>,config:web::Data<Config>) -> HttpResponse{
+    match db.get_data_freshness_api(config.limits.max_data_lag_secs, &config.pools).await {
+        Ok(result) if result.status == "ok" => HttpResponse::Ok().json(result),
+        Ok(result) => HttpResponse::ServiceUnavailable().json(result),
+        Err(e) => {
+            error!(error = ?e, "Error at /health/data");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(get_health).service(get_ready).service(get_data_health);
+}
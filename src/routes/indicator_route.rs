@@ -0,0 +1,40 @@
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use crate::{models::indicator_model::{validate_indicator_query, IndicatorParams}, services::db::DataBase};
+use tracing::error;
+
+#[utoipa::path(
+    get,
+    path = "/indicators",
+    params(
+        ("metric" = String, Query, description = "Metric to compute on, depths fields like `assetPriceUSD`, `assetDepth`, `runeDepth` or swaps fields like `totalVolumeUSD`, `totalCount`, `averageSlip`, `runePriceUSD`"),
+        ("pool" = String, Query, description = "Pool identifier like `BTC.BTC`"),
+        ("interval" = Option<String>, Query, description = "Time interval for aggregation `(hour, day, week, month, quarter, year)`"),
+        ("indicators" = String, Query, description = "Comma separated `name:period` list of `sma`, `ema`, `rsi`, `bollinger` (2 std dev bands) and `volatility` (std dev of log returns), e.g. `sma:20,rsi:14,bollinger`"),
+        ("from" = Option<u64>, Query, description = "Start time Unix timestamp, if not specified, from = `(latest record (or) to_time - interval_dur*count)`"),
+        ("to" = Option<u64>, Query, description = "End time Unix timestamp"),
+        ("count" = Option<u32>, Query, description = "Number of intervals returned `(1-400)`")
+    ),
+    responses(
+        (status = 200, description = "Metric values with the indicator series aligned per interval, `null` while an indicator has too few points"),
+        (status = 400, description = "Bad request - Invalid parameters"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Indicators"
+)]
+#[actix_web::get("")]
+pub async fn get_indicators(db:web::Data<DataBase>,params:web::Query<IndicatorParams>) -> HttpResponse{
+    if let Err(validation_err) = validate_indicator_query(&params) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    match db.get_indicators_api(params.into_inner()).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => {
+            error!(error = ?e, "Error at /indicators");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(get_indicators);
+}
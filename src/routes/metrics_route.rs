@@ -0,0 +1,23 @@
+use actix_web::HttpResponse;
+use crate::services::metrics_service::METRICS;
+use tracing::error;
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Request, aggregation and ingestion metrics in Prometheus text format", content_type = "text/plain"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Monitoring"
+)]
+#[actix_web::get("/metrics")]
+pub async fn get_metrics() -> HttpResponse{
+    match METRICS.render() {
+        Ok(result) => HttpResponse::Ok().content_type(prometheus::TEXT_FORMAT).body(result),
+        Err(e) => {
+            error!(error = ?e, "Error at /metrics");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
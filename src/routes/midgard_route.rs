@@ -0,0 +1,145 @@
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use crate::{
+    config::Config,
+    controllers::midgard_compat_api_controller::{
+        midgard_depths_response, midgard_earnings_response, midgard_history_intervals, midgard_rune_pool_response, midgard_swaps_response,
+    },
+    models::{api_request_param_model::{validate_pool, validate_query, QueryParams}, custom_error_model::CustomError},
+    services::db::DataBase,
+    stores::metrics_store::HistoryCollection,
+};
+use serde::Serialize;
+use tracing::error;
+
+// midgard ignores the paging and sorting params of this api
+fn midgard_params(params: QueryParams, pool: Option<String>) -> QueryParams {
+    QueryParams { pool, page: None, limit: None, sort_by: None, sort_order: None, ..params }
+}
+
+fn midgard_response<T: Serialize>(path: &str, result: Result<T, CustomError>) -> HttpResponse {
+    match result {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => {
+            error!(error = ?e, "Error at {}", path);
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/history/depths/{pool}",
+    params(
+        ("pool" = String, Path, description = "Pool identifier, one of the configured `pools`"),
+        ("interval" = Option<String>, Query, description = "Time interval for aggregation `(hour, day, week, month, quarter, year)`"),
+        ("count" = Option<u32>, Query, description = "Number of intervals `(1-400)`"),
+        ("from" = Option<u64>, Query, description = "Start time Unix timestamp"),
+        ("to" = Option<u64>, Query, description = "End time Unix timestamp")
+    ),
+    responses(
+        (status = 200, description = "Depth and price history in the midgard format, every number as a string"),
+        (status = 400, description = "Bad request - Invalid parameters or a pool that is not configured"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Midgard Compatible"
+)]
+#[actix_web::get("/history/depths/{pool}")]
+pub async fn get_midgard_depths(db:web::Data<DataBase>,config:web::Data<Config>,pool:web::Path<String>,params:web::Query<QueryParams>) -> HttpResponse{
+    let params = midgard_params(params.into_inner(), Some(pool.into_inner()));
+    if let Err(validation_err) = validate_query(&params).and_then(|_| validate_pool(&params, &config.pools)) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    let result = midgard_history_intervals(db.store.as_ref(), HistoryCollection::Depths, params).await;
+    midgard_response("/v2/history/depths", result.and_then(|(intervals, query)| midgard_depths_response(&intervals, &query)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/history/swaps",
+    params(
+        ("pool" = Option<String>, Query, description = "Pool identifier, one of the configured `pools`"),
+        ("interval" = Option<String>, Query, description = "Time interval for aggregation `(hour, day, week, month, quarter, year)`"),
+        ("count" = Option<u32>, Query, description = "Number of intervals `(1-400)`"),
+        ("from" = Option<u64>, Query, description = "Start time Unix timestamp"),
+        ("to" = Option<u64>, Query, description = "End time Unix timestamp")
+    ),
+    responses(
+        (status = 200, description = "Swap history in the midgard format, every number as a string"),
+        (status = 400, description = "Bad request - Invalid parameters or a pool that is not configured"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Midgard Compatible"
+)]
+#[actix_web::get("/history/swaps")]
+pub async fn get_midgard_swaps(db:web::Data<DataBase>,config:web::Data<Config>,params:web::Query<QueryParams>) -> HttpResponse{
+    let params = params.into_inner();
+    let pool = params.pool.clone();
+    let params = midgard_params(params, pool);
+    if let Err(validation_err) = validate_query(&params).and_then(|_| validate_pool(&params, &config.pools)) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    let result = midgard_history_intervals(db.store.as_ref(), HistoryCollection::Swaps, params).await;
+    midgard_response("/v2/history/swaps", result.and_then(|(intervals, query)| midgard_swaps_response(&intervals, &query)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/history/earnings",
+    params(
+        ("interval" = Option<String>, Query, description = "Time interval for aggregation `(hour, day, week, month, quarter, year)`"),
+        ("count" = Option<u32>, Query, description = "Number of intervals `(1-400)`"),
+        ("from" = Option<u64>, Query, description = "Start time Unix timestamp"),
+        ("to" = Option<u64>, Query, description = "End time Unix timestamp")
+    ),
+    responses(
+        (status = 200, description = "Earnings history of every pool in the midgard format, every number as a string"),
+        (status = 400, description = "Bad request - Invalid parameters"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Midgard Compatible"
+)]
+#[actix_web::get("/history/earnings")]
+pub async fn get_midgard_earnings(db:web::Data<DataBase>,params:web::Query<QueryParams>) -> HttpResponse{
+    let params = midgard_params(params.into_inner(), None);
+    if let Err(validation_err) = validate_query(&params) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    let result = midgard_history_intervals(db.store.as_ref(), HistoryCollection::Earnings, params).await;
+    midgard_response(
+        "/v2/history/earnings",
+        result.and_then(|(intervals, query)| midgard_earnings_response(&intervals, &query)),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/history/runepool",
+    params(
+        ("interval" = Option<String>, Query, description = "Time interval for aggregation `(hour, day, week, month, quarter, year)`"),
+        ("count" = Option<u32>, Query, description = "Number of intervals `(1-400)`"),
+        ("from" = Option<u64>, Query, description = "Start time Unix timestamp"),
+        ("to" = Option<u64>, Query, description = "End time Unix timestamp")
+    ),
+    responses(
+        (status = 200, description = "Rune pool history in the midgard format, every number as a string"),
+        (status = 400, description = "Bad request - Invalid parameters"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Midgard Compatible"
+)]
+#[actix_web::get("/history/runepool")]
+pub async fn get_midgard_rune_pool(db:web::Data<DataBase>,params:web::Query<QueryParams>) -> HttpResponse{
+    let params = midgard_params(params.into_inner(), None);
+    if let Err(validation_err) = validate_query(&params) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    let result = midgard_history_intervals(db.store.as_ref(), HistoryCollection::RunePool, params).await;
+    midgard_response("/v2/history/runepool", result.and_then(|(intervals, query)| midgard_rune_pool_response(&intervals, &query)))
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(get_midgard_depths);
+    config.service(get_midgard_swaps);
+    config.service(get_midgard_earnings);
+    config.service(get_midgard_rune_pool);
+}
@@ -1,6 +1,6 @@
-use actix_web::{web::{self, ServiceConfig}, HttpResponse, Responder};
-use chrono::Utc;
-use crate::{models::{api_request_param_model::{validate_query, QueryParams}, rune_pool_model::RunePool}, services::db::DataBase};
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use crate::{models::api_request_param_model::{validate_query, QueryParams}, services::db::DataBase};
+use tracing::error;
 
 #[utoipa::path(
     get,
@@ -27,42 +27,16 @@ pub async fn get_rune_pool_history(db:web::Data<DataBase>,params:web::Query<Quer
     if let Err(validation_err) = validate_query(&params) {
         return HttpResponse::BadRequest().json(validation_err);
     }
-    match db.get_rune_pool_history_api(params.into_inner()).await {
+    match db.store.get_rune_pool_history_api(params.into_inner()).await {
         Ok(result) => HttpResponse::Ok().json(result),
         Err(e) => {
-            eprint!("Error at /runepool {:?}",e);
+            error!(error = ?e, "Error at /runepool");
             HttpResponse::InternalServerError().json(e)
         }
     }
 }
 
-
-// Protected route
-// Expensive function
-#[actix_web::get("/fetch-rune-pools-all")]
-async fn fetch_all_rune_pools_to_db(db:web::Data<DataBase>) -> impl Responder{
-    let current_time_stamp = Utc::now().timestamp();
-    // epoch value of the api's start derived from the midgard metadata
-    let mut start = 1647913096;
-    loop{
-        let end_time = match RunePool::fetch_rune_pool(&db, "hour", "400", &start.to_string()).await {
-            Ok(response) => response,
-            Err(e) => {
-                println!("Failed to fetch and update db with rune pool history! {:?}\n",e);
-                current_time_stamp+10
-            }
-        };
-        if end_time >= current_time_stamp{
-            break;
-        }
-        start = end_time;
-    }
-    // adapting non-strict fetch, error at a bucket doesn't stop whole fetch process
-    // because observed failure due to network multiple times with midgard while testing
-    HttpResponse::Ok().body(format!("Fetched and added rune pool records to database"))
-}
-
 pub fn init(config:&mut ServiceConfig){
-    config.service(fetch_all_rune_pools_to_db).service(get_rune_pool_history);
+    config.service(get_rune_pool_history);
     ()
 }
\ No newline at end of file
@@ -0,0 +1,175 @@
+use std::{collections::HashSet, future::ready, time::Duration};
+
+use actix_web::{web::{self, Bytes, ServiceConfig}, HttpRequest, HttpResponse};
+use actix_ws::{CloseCode, CloseReason, Message};
+use futures_util::StreamExt;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, IntervalStream};
+
+use crate::{models::{custom_error_model::CustomError, stream_event_model::{parse_topics, StreamCursor, StreamEvent, StreamParams, Subscription}}, services::db::DataBase};
+use tracing::error;
+
+const KEEP_ALIVE_SECS: u64 = 15;
+
+fn to_sse(event: &StreamEvent) -> Bytes {
+    // browsers resume through Last-Event-ID on reconnect
+    Bytes::from(format!(
+        "id: {}\nevent: {}\ndata: {}\n\n",
+        event.id,
+        event.topic,
+        serde_json::to_string(event).unwrap_or_default()
+    ))
+}
+
+fn last_event_id(req: &HttpRequest) -> Option<&str> {
+    req.headers().get("Last-Event-ID").and_then(|id| id.to_str().ok())
+}
+
+fn event_key(event: &StreamEvent) -> (String, Option<String>, i64) {
+    (event.topic.clone(), event.pool.clone(), event.end_time)
+}
+
+// subscribes before replaying so nothing stored in between is lost, returns the replay and the keys to skip live
+async fn subscribe(db: &DataBase, params: &StreamParams, last_event_id: Option<&str>) -> Result<(Vec<Subscription>, Vec<StreamEvent>, HashSet<(String, Option<String>, i64)>), CustomError> {
+    let subscriptions = parse_topics(&params.topics)?;
+    // a reconnect repeats the url, its Last-Event-ID is the newer resume point
+    let backlog = match last_event_id.or(params.since.as_deref()) {
+        Some(since) => db.get_stream_backlog_api(&subscriptions, &StreamCursor::parse(since)?).await?,
+        None => Vec::new(),
+    };
+    let replayed = backlog.iter().map(event_key).collect();
+    Ok((subscriptions, backlog, replayed))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stream",
+    params(
+        ("topics" = String, Query, description = "Comma separated `topic:pool` subscriptions of `swaps`, `depths`, `earnings` and `runepool`, `*` or no pool for every pool, e.g. `swaps:BTC.BTC,depths:*`"),
+        ("since" = Option<String>, Query, description = "Resume point, an `end_time` or an event id, the stored intervals after it are replayed first (a `Last-Event-ID` header takes precedence)")
+    ),
+    responses(
+        (status = 200, description = "`text/event-stream` of newly ingested intervals, the event name is the topic and the id `<end_time>:<topic>:<pool>`. A replay past 400 intervals ends with a `gap` event and `X-Replay-Truncated: true` and the response ends there, reconnecting with its id replays the rest", body = StreamEvent),
+        (status = 400, description = "Bad request - Invalid topics"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Stream"
+)]
+#[actix_web::get("")]
+pub async fn get_event_stream(db:web::Data<DataBase>,params:web::Query<StreamParams>,req:HttpRequest) -> HttpResponse{
+    let receiver = db.stream.subscribe();
+    let (subscriptions, backlog, replayed) = match subscribe(&db, &params, last_event_id(&req)).await {
+        Ok(result) => result,
+        Err(CustomError::InvalidInput(e)) => return HttpResponse::BadRequest().json(CustomError::InvalidInput(e)),
+        Err(e) => {
+            error!(error = ?e, "Error at /stream");
+            return HttpResponse::InternalServerError().json(e);
+        }
+    };
+
+    let truncated = backlog.last().is_some_and(StreamEvent::is_gap);
+    let replay = futures_util::stream::iter(backlog.iter().map(to_sse).collect::<Vec<Bytes>>());
+    let live = BroadcastStream::new(receiver).filter_map(move |event| {
+        ready(match event {
+            Ok(event) if subscriptions.iter().any(|subscription| subscription.matches(&event)) && !replayed.contains(&event_key(&event)) => Some(to_sse(&event)),
+            Ok(_) => None,
+            // the client missed events, it can reconnect with its last id to replay them
+            Err(BroadcastStreamRecvError::Lagged(missed)) => Some(Bytes::from(format!(": lagged {} events\n\n", missed))),
+        })
+    });
+    let keep_alive = IntervalStream::new(tokio::time::interval(Duration::from_secs(KEEP_ALIVE_SECS))).map(|_| Bytes::from(": keep-alive\n\n"));
+    // a cut replay ends the response, live events would move the client's Last-Event-ID past the rest of the backlog
+    let live = if truncated {
+        futures_util::stream::empty().boxed_local()
+    } else {
+        futures_util::stream::select(live, keep_alive).boxed_local()
+    };
+    let body = replay.chain(live).map(Ok::<Bytes, actix_web::Error>);
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Replay-Truncated", truncated.to_string()))
+        .streaming(body)
+}
+
+#[utoipa::path(
+    get,
+    path = "/stream/ws",
+    params(
+        ("topics" = String, Query, description = "Same subscriptions as `/stream`"),
+        ("since" = Option<String>, Query, description = "Resume point, an `end_time` or an event id, the stored intervals after it are replayed first (a `Last-Event-ID` header takes precedence)")
+    ),
+    responses(
+        (status = 101, description = "WebSocket upgrade, every text frame is one JSON StreamEvent. A replay past 400 intervals ends with a `gap` event and the socket is closed, reconnecting with its `id` as `since` replays the rest", body = StreamEvent),
+        (status = 400, description = "Bad request - Invalid topics or not a WebSocket request"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Stream"
+)]
+#[actix_web::get("/ws")]
+pub async fn get_websocket_stream(db:web::Data<DataBase>,params:web::Query<StreamParams>,req:HttpRequest,body:web::Payload) -> HttpResponse{
+    let mut receiver = db.stream.subscribe();
+    let (subscriptions, backlog, replayed) = match subscribe(&db, &params, last_event_id(&req)).await {
+        Ok(result) => result,
+        Err(CustomError::InvalidInput(e)) => return HttpResponse::BadRequest().json(CustomError::InvalidInput(e)),
+        Err(e) => {
+            error!(error = ?e, "Error at /stream/ws");
+            return HttpResponse::InternalServerError().json(e);
+        }
+    };
+    let (response, mut session, mut messages) = match actix_ws::handle(&req, body) {
+        Ok(result) => result,
+        Err(e) => return HttpResponse::BadRequest().json(CustomError::InvalidInput(e.to_string())),
+    };
+
+    actix_web::rt::spawn(async move {
+        for event in &backlog {
+            if session.text(serde_json::to_string(event).unwrap_or_default()).await.is_err() {
+                return;
+            }
+        }
+        // the client reconnects from the gap before it gets live events
+        if backlog.last().is_some_and(StreamEvent::is_gap) {
+            let reason = CloseReason { code: CloseCode::Normal, description: Some("replay truncated, reconnect with the gap id as since".to_string()) };
+            let _ = session.close(Some(reason)).await;
+            return;
+        }
+        loop {
+            tokio::select! {
+                message = messages.next() => match message {
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(Ok(Message::Close(reason))) => {
+                        let _ = session.close(reason).await;
+                        return;
+                    }
+                    Some(Ok(_)) => (),
+                    Some(Err(_)) | None => return,
+                },
+                event = receiver.recv() => match event {
+                    Ok(event) => {
+                        if subscriptions.iter().any(|subscription| subscription.matches(&event)) && !replayed.contains(&event_key(&event))
+                            && session.text(serde_json::to_string(&event).unwrap_or_default()).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => (),
+                    Err(RecvError::Closed) => {
+                        let _ = session.close(None).await;
+                        return;
+                    }
+                },
+            }
+        }
+    });
+
+    response
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(get_websocket_stream).service(get_event_stream);
+}
@@ -1,7 +1,7 @@
-use actix_web::{web::{self, ServiceConfig}, HttpResponse, Responder};
-use chrono::Utc;
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
 
-use crate::{models::{api_request_param_model::{validate_query, QueryParams}, swap_history_model::SwapHistory}, services::db::DataBase};
+use crate::{config::Config, models::api_request_param_model::{validate_pool, validate_query, QueryParams}, services::db::DataBase};
+use tracing::error;
 
 #[utoipa::path(
     get,
@@ -9,7 +9,7 @@ use crate::{models::{api_request_param_model::{validate_query, QueryParams}, swa
     params(
         ("from" = Option<u64>, Query, description = "Start time Unix timestamp, if not specified, from = `(current_time (or) to_time - interval_dur*count)`"),
         ("to" = Option<u64>, Query, description = "End time Unix timestamp"),
-        ("pool" = Option<String>, Query, description = "Pool identifier - only BTC.BTC for now"),
+        ("pool" = Option<String>, Query, description = "Pool identifier, one of the configured `pools`"),
         ("page" = Option<u64>, Query, description = "Page number (minimum: `1`)"),
         ("limit" = Option<u32>, Query, description = "Items per page `(1-400)`"),
         ("sort_by" = Option<String>, Query, description = "Field to sort by"),
@@ -25,45 +25,20 @@ use crate::{models::{api_request_param_model::{validate_query, QueryParams}, swa
     tag = "Swaps History"
 )]
 #[actix_web::get("")]
-pub async fn get_swaps_history(db:web::Data<DataBase>,params:web::Query<QueryParams>) -> HttpResponse{
-    if let Err(validation_err) = validate_query(&params) {
+pub async fn get_swaps_history(db:web::Data<DataBase>,config:web::Data<Config>,params:web::Query<QueryParams>) -> HttpResponse{
+    if let Err(validation_err) = validate_query(&params).and_then(|_| validate_pool(&params, &config.pools)) {
         return HttpResponse::BadRequest().json(validation_err);
     }
-    match db.get_swaps_history_api(params.into_inner()).await {
+    match db.store.get_swaps_history_api(params.into_inner()).await {
         Ok(result) => HttpResponse::Ok().json(result),
         Err(e) => {
-            eprint!("Error at /swaps");
+            error!(error = ?e, "Error at /swaps");
             HttpResponse::InternalServerError().json(e)
         }
     }
 }
 
-// Protected route
-// Expensive function
-#[actix_web::get("/fetch-swaps-all")]
-pub async fn fetch_all_swaps_to_db(db:web::Data<DataBase>) -> impl Responder{
-    let current_time_stamp = Utc::now().timestamp();
-    // epoch value of the api's start derived from the midgard metadata
-    let mut start = 1647913096;
-    loop {
-        let end_time = match SwapHistory::fetch_swap_history(db.get_ref(), "BTC.BTC", "hour", "400", &start.to_string()).await {
-            Ok(response) => response,
-            Err(e) => {
-                println!("Failed to fetch and update db with swap history! {:?}\n",e);
-                current_time_stamp+10
-            }
-        };
-        if end_time >= current_time_stamp{
-            break;
-        }
-        start = end_time;
-    }
-    // adapting non-strict fetch, error at a bucket doesn't stop whole fetch process
-    // because observed failure due to network multiple times with midgard while testing
-    HttpResponse::Ok().body(format!("Fetched and added swap records to database"))
-}
-
 pub fn init(config:&mut ServiceConfig){
-    config.service(fetch_all_swaps_to_db).service(get_swaps_history);
+    config.service(get_swaps_history);
     ()
 }
\ No newline at end of file
@@ -0,0 +1,38 @@
+use actix_web::{web::{self, ServiceConfig}, HttpResponse};
+use crate::{models::{custom_error_model::CustomError, swap_quote_model::{validate_swap_quote_query, SwapQuoteParams}}, services::db::DataBase};
+use tracing::error;
+
+#[utoipa::path(
+    get,
+    path = "/tools/swap-quote",
+    params(
+        ("from" = String, Query, description = "Input asset like `BTC.BTC` or `THOR.RUNE`"),
+        ("to" = String, Query, description = "Output asset like `ETH.ETH` or `THOR.RUNE`, asset to asset swaps are routed through RUNE"),
+        ("amount" = f64, Query, description = "Input amount in 1e8 units"),
+        ("at" = Option<u64>, Query, description = "Unix timestamp of the depths to quote against, defaults to the latest stored depths")
+    ),
+    responses(
+        (status = 200, description = "Estimated output, fees and slip from the stored pool depths", body = SwapQuote),
+        (status = 400, description = "Bad request - Invalid parameters or no depth history for the pool"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Tools"
+)]
+#[actix_web::get("/swap-quote")]
+pub async fn get_swap_quote(db:web::Data<DataBase>,params:web::Query<SwapQuoteParams>) -> HttpResponse{
+    if let Err(validation_err) = validate_swap_quote_query(&params) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    match db.get_swap_quote_api(params.into_inner()).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(CustomError::InvalidInput(e)) => HttpResponse::BadRequest().json(CustomError::InvalidInput(e)),
+        Err(e) => {
+            error!(error = ?e, "Error at /tools/swap-quote");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(get_swap_quote);
+}
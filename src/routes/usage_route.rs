@@ -0,0 +1,52 @@
+use actix_web::{web::{self, ServiceConfig}, HttpRequest, HttpResponse};
+use crate::{middlewares::api_key_middleware::API_KEY_HEADER, models::{api_key_model::{ApiKey, ADMIN_ROLE}, custom_error_model::CustomError, usage_model::{validate_usage_query, UsageParams}}, services::db::DataBase};
+use tracing::error;
+
+#[utoipa::path(
+    get,
+    path = "/usage",
+    params(
+        ("client" = Option<String>, Query, description = "Client like `key:tm_AbC12345` or `ip:10.0.0.1`, admin keys only, defaults to every client"),
+        ("from" = Option<String>, Query, description = "First day `YYYY-MM-DD`, defaults to 30 days ago"),
+        ("to" = Option<String>, Query, description = "Last day `YYYY-MM-DD`")
+    ),
+    responses(
+        (status = 200, description = "Daily requests, throttled requests and cost per route group, read keys only see their own usage", body = Vec<ApiUsage>),
+        (status = 400, description = "Bad request - Invalid parameters"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("api_key" = [])),
+    tag = "Usage"
+)]
+#[actix_web::get("")]
+pub async fn get_usage(db:web::Data<DataBase>,req:HttpRequest,params:web::Query<UsageParams>) -> HttpResponse{
+    if let Err(validation_err) = validate_usage_query(&params) {
+        return HttpResponse::BadRequest().json(validation_err);
+    }
+    let key = req.headers().get(API_KEY_HEADER).and_then(|key| key.to_str().ok()).unwrap_or_default();
+    let api_key = match ApiKey::find_active_api_key(&db, key).await {
+        Ok(Some(api_key)) => api_key,
+        Ok(None) => return HttpResponse::Unauthorized().json(CustomError::InvalidInput("Invalid or revoked API key".to_string())),
+        Err(e) => {
+            error!(error = ?e, "Error at /usage");
+            return HttpResponse::InternalServerError().json(e);
+        }
+    };
+    let mut params = params.into_inner();
+    let client = if api_key.has_role(ADMIN_ROLE) {
+        params.client.take()
+    } else {
+        Some(format!("key:{}", api_key.prefix))
+    };
+    match db.get_usage_api(client, params).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => {
+            error!(error = ?e, "Error at /usage");
+            HttpResponse::InternalServerError().json(e)
+        }
+    }
+}
+
+pub fn init(config:&mut ServiceConfig){
+    config.service(get_usage);
+}
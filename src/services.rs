@@ -3,4 +3,12 @@ pub mod depth_history_service;
 pub mod earnings_history_service;
 pub mod swap_history_service;
 pub mod rune_pool_service;
-pub mod fetch_all_cron_service;
\ No newline at end of file
+pub mod fetch_all_cron_service;
+pub mod anomaly_detection_service;
+pub mod alert_service;
+pub mod api_key_service;
+pub mod metrics_service;
+pub mod backfill_service;
+pub mod maintenance_service;pub mod midgard_verify_service;
+pub mod validation_service;
+pub mod midgard_page_service;
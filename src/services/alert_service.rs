@@ -0,0 +1,171 @@
+use std::sync::LazyLock;
+
+use chrono::Utc;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use hmac::{Hmac, Mac};
+use mongodb::bson::{doc, oid::ObjectId};
+use serde_json::json;
+use sha2::Sha256;
+use tokio::time::{sleep, Duration};
+
+use crate::{
+    models::{
+        alert_rule_model::{AlertEvent, AlertRule},
+        custom_error_model::CustomError,
+        indicator_model::resolve_metric,
+    },
+    stores::metrics_store::RecordQuery,
+    utils::parser_utils::bson_to_f64,
+};
+
+use super::db::DataBase;
+use tracing::error;
+
+const MAX_DELIVERY_ATTEMPTS: i32 = 3;
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+// rules evaluated at once, so a slow webhook only holds up its own delivery
+const CONCURRENT_RULES: usize = 16;
+
+// one client for every delivery so connections to the same receiver are kept alive
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+// hex HMAC-SHA256 of "{timestamp}.{body}", receivers recompute it with the rule secret
+// and reject stale timestamps to guard against replays
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// posts the payload, retrying with exponential backoff, returns the attempts made and the last error
+async fn deliver_webhook(url: &str, secret: &str, body: String) -> (i32, Option<String>) {
+    let mut last_error = None;
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        let timestamp = Utc::now().timestamp();
+        let result = CLIENT
+            .post(url)
+            .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .header("Content-Type", "application/json")
+            .header("X-Timestamp", timestamp.to_string())
+            .header("X-Signature", format!("sha256={}", sign_payload(secret, timestamp, &body)))
+            .body(body.clone())
+            .send()
+            .await;
+        match result {
+            Ok(res) if res.status().is_success() => return (attempt, None),
+            Ok(res) => last_error = Some(format!("webhook responded {}", res.status())),
+            Err(e) => last_error = Some(format!("webhook request failed {}", e)),
+        }
+        if attempt < MAX_DELIVERY_ATTEMPTS {
+            sleep(Duration::from_secs(1 << (attempt - 1))).await;
+        }
+    }
+    (MAX_DELIVERY_ATTEMPTS, last_error)
+}
+
+impl AlertRule {
+    // latest value of the metric for the pool at or before `at`, with the end_time of its interval
+    async fn get_metric_value(db: &DataBase, metric: &str, pool: &str, at: Option<i64>) -> Result<Option<(f64, i64)>, CustomError> {
+        let (collection, field, _) = resolve_metric(metric)
+            .ok_or(CustomError::InvalidInput(format!("Unsupported metric {}", metric)))?;
+        let query = RecordQuery { pool: Some(pool.to_string()), to: at, descending: true, limit: 1, ..RecordQuery::default() };
+        let records = db.store.records(collection.history_collection(), &query).await?;
+        Ok(records.first().and_then(|record| {
+            Some((record.get(field).and_then(bson_to_f64)?, record.get_i64("end_time").ok()?))
+        }))
+    }
+
+    // value compared against the threshold, a percentage for change_pct conditions
+    async fn get_rule_value(&self, db: &DataBase) -> Result<Option<(f64, i64)>, CustomError> {
+        let Some((latest, end_time)) = AlertRule::get_metric_value(db, &self.metric, &self.pool, None).await? else {
+            return Ok(None);
+        };
+        if !self.condition.starts_with("change_pct") {
+            return Ok(Some((latest, end_time)));
+        }
+        match AlertRule::get_metric_value(db, &self.metric, &self.pool, Some(end_time - self.window_secs)).await? {
+            Some((previous, _)) if previous != 0.0 => Ok(Some(((latest - previous) / previous * 100.0, end_time))),
+            _ => Ok(None),
+        }
+    }
+
+    fn is_triggered(&self, value: f64) -> bool {
+        match self.condition.as_str() {
+            "above" | "change_pct_above" => value > self.threshold,
+            "below" | "change_pct_below" => value < self.threshold,
+            _ => false,
+        }
+    }
+
+    async fn evaluate(&self, db: &DataBase) -> Result<bool, CustomError> {
+        let Some((value, end_time)) = self.get_rule_value(db).await? else {
+            return Ok(false);
+        };
+        if !self.is_triggered(value) || self.last_triggered_end_time == Some(end_time) {
+            return Ok(false);
+        }
+        let now = Utc::now().timestamp();
+        if self.last_triggered_at.is_some_and(|last| now - last < self.cooldown_secs) {
+            return Ok(false);
+        }
+
+        let body = json!({
+            "ruleId": self._id.to_hex(),
+            "name": self.name,
+            "pool": self.pool,
+            "metric": self.metric,
+            "condition": self.condition,
+            "threshold": self.threshold,
+            "windowSecs": self.window_secs,
+            "value": value,
+            "endTime": end_time,
+            "triggeredAt": now
+        })
+        .to_string();
+        let (attempts, last_error) = deliver_webhook(&self.webhook_url, &self.secret, body).await;
+
+        // cool-down starts even when delivery failed so a dead webhook is not hammered every cycle
+        db.mongodb()?.alert_rules
+            .update_one(
+                doc! { "_id": self._id },
+                doc! { "$set": { "last_triggered_at": now, "last_triggered_end_time": end_time } },
+            )
+            .await?;
+        db.mongodb()?.alert_events
+            .insert_one(AlertEvent {
+                _id: ObjectId::new(),
+                rule_id: self._id,
+                pool: self.pool.clone(),
+                metric: self.metric.clone(),
+                value,
+                end_time,
+                triggered_at: now,
+                delivered: last_error.is_none(),
+                attempts,
+                last_error,
+            })
+            .await?;
+        Ok(true)
+    }
+
+    // evaluates every enabled rule against the latest stored intervals, returns the number of rules fired
+    #[tracing::instrument(skip(db))]
+    pub async fn evaluate_alert_rules(db: &DataBase) -> Result<u64, CustomError> {
+        let rules: Vec<AlertRule> = db.mongodb()?.alert_rules.find(doc! { "enabled": true }).await?.try_collect().await?;
+        let fired = stream::iter(rules)
+            .map(|rule| async move {
+                match rule.evaluate(db).await {
+                    Ok(fired) => fired,
+                    Err(e) => {
+                        error!(rule = %rule._id, error = ?e, "Failed evaluating alert rule");
+                        false
+                    }
+                }
+            })
+            .buffer_unordered(CONCURRENT_RULES)
+            .filter(|fired| std::future::ready(*fired))
+            .count()
+            .await;
+        Ok(fired as u64)
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeSet;
+
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId};
+
+use crate::{
+    models::{anomaly_model::{severity_for_score, Anomaly}, custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory, swap_history_model::SwapHistory},
+    stores::metrics_store::{for_each_record_page, records_as, HistoryCollection, RecordQuery},
+    utils::stats_utils::{mad, median, robust_z_score},
+};
+
+use super::db::DataBase;
+
+// rolling baseline of one week of hourly intervals before the scored interval
+const BASELINE_WINDOW_SECS: i64 = 7 * 86_400;
+// with fewer points a baseline is too noisy to score against
+const MIN_BASELINE_POINTS: usize = 24;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Up,
+    Down,
+}
+
+// pools with intervals stored after `since`, read a page at a time
+async fn pools_since(db: &DataBase, collection: HistoryCollection, since: i64) -> Result<BTreeSet<String>, CustomError> {
+    let mut pools = BTreeSet::new();
+    let query = RecordQuery { after: Some(since), ..RecordQuery::default() };
+    for_each_record_page(db.store.as_ref(), collection, query, |page| {
+        pools.extend(page.iter().filter_map(|record| Some(record.get_str("pool").ok()?.to_string())))
+    })
+    .await?;
+    Ok(pools)
+}
+
+// the intervals after `since` of the pool with the baseline window before them, oldest first
+fn baseline_query(pool: &str, since: i64) -> RecordQuery {
+    RecordQuery { pool: Some(pool.to_string()), after: Some(since - BASELINE_WINDOW_SECS), limit: i64::MAX, ..RecordQuery::default() }
+}
+
+struct Scored<'a> {
+    pool: &'a str,
+    collection: &'a str,
+    kind: &'a str,
+    metric: &'a str,
+    direction: Direction,
+    start_time: i64,
+    end_time: i64,
+}
+
+impl Anomaly {
+    async fn record_if_anomalous(db: &DataBase, scored: Scored<'_>, value: f64, baseline: &[f64]) -> Result<bool, CustomError> {
+        if baseline.len() < MIN_BASELINE_POINTS {
+            return Ok(false);
+        }
+        let Some(score) = robust_z_score(value, baseline) else {
+            return Ok(false);
+        };
+        // volumes and slips only matter when they jump, depths only when they drop
+        let in_direction = match scored.direction {
+            Direction::Up => score > 0.0,
+            Direction::Down => score < 0.0,
+        };
+        let Some(severity) = severity_for_score(score).filter(|_| in_direction) else {
+            return Ok(false);
+        };
+        // re-running the analyzer over the same intervals must not duplicate anomalies
+        let existing = db
+            .mongodb()?
+            .anomalies
+            .find_one(doc! { "pool": scored.pool, "kind": scored.kind, "metric": scored.metric, "end_time": scored.end_time })
+            .await?;
+        if existing.is_some() {
+            return Ok(false);
+        }
+        let baseline_median = median(baseline).unwrap_or(0.0);
+        let anomaly = Anomaly {
+            _id: ObjectId::new(),
+            pool: scored.pool.to_string(),
+            collection: scored.collection.to_string(),
+            kind: scored.kind.to_string(),
+            metric: scored.metric.to_string(),
+            value,
+            baseline_median,
+            baseline_mad: mad(baseline, baseline_median).unwrap_or(0.0),
+            score,
+            severity: severity.to_string(),
+            start_time: scored.start_time,
+            end_time: scored.end_time,
+            detected_at: Utc::now().timestamp(),
+        };
+        db.mongodb()?.anomalies.insert_one(anomaly).await?;
+        Ok(true)
+    }
+
+    async fn detect_swap_anomalies(db: &DataBase, since: i64) -> Result<u64, CustomError> {
+        let mut recorded = 0;
+        for pool in &pools_since(db, HistoryCollection::Swaps, since).await? {
+            let swaps: Vec<SwapHistory> = records_as(db.store.records(HistoryCollection::Swaps, &baseline_query(pool, since)).await?)?;
+            for (i, swap) in swaps.iter().enumerate().filter(|(_, swap)| swap.end_time > since) {
+                let window = swaps[..i]
+                    .iter()
+                    .filter(|previous| previous.end_time >= swap.start_time - BASELINE_WINDOW_SECS)
+                    .collect::<Vec<&SwapHistory>>();
+                let volumes = window.iter().map(|previous| previous.total_volume_usd).collect::<Vec<f64>>();
+                let slips = window.iter().map(|previous| previous.average_slip).collect::<Vec<f64>>();
+                let scored = |kind, metric| Scored {
+                    pool,
+                    collection: "swap_history",
+                    kind,
+                    metric,
+                    direction: Direction::Up,
+                    start_time: swap.start_time,
+                    end_time: swap.end_time,
+                };
+                if Anomaly::record_if_anomalous(db, scored("volume_spike", "totalVolumeUSD"), swap.total_volume_usd, &volumes).await? {
+                    recorded += 1;
+                }
+                if Anomaly::record_if_anomalous(db, scored("slip_spike", "averageSlip"), swap.average_slip, &slips).await? {
+                    recorded += 1;
+                }
+            }
+        }
+        Ok(recorded)
+    }
+
+    async fn detect_depth_anomalies(db: &DataBase, since: i64) -> Result<u64, CustomError> {
+        let mut recorded = 0;
+        for pool in &pools_since(db, HistoryCollection::Depths, since).await? {
+            let depths: Vec<PoolDepthPriceHistory> = records_as(db.store.records(HistoryCollection::Depths, &baseline_query(pool, since)).await?)?;
+            // depths are levels, score the relative change from the previous interval instead of the level itself
+            let change = |previous: f64, current: f64| if previous > 0.0 { current / previous - 1.0 } else { 0.0 };
+            for i in 1..depths.len() {
+                let depth = &depths[i];
+                if depth.end_time <= since {
+                    continue;
+                }
+                let window = (1..i)
+                    .filter(|&j| depths[j].end_time >= depth.start_time - BASELINE_WINDOW_SECS)
+                    .collect::<Vec<usize>>();
+                for (metric, get) in [
+                    ("assetDepth", (|d: &PoolDepthPriceHistory| d.asset_depth as f64) as fn(&PoolDepthPriceHistory) -> f64),
+                    ("runeDepth", |d: &PoolDepthPriceHistory| d.rune_depth as f64),
+                ] {
+                    let baseline = window
+                        .iter()
+                        .map(|&j| change(get(&depths[j - 1]), get(&depths[j])))
+                        .collect::<Vec<f64>>();
+                    let scored = Scored {
+                        pool,
+                        collection: "depth_history",
+                        kind: "depth_drop",
+                        metric,
+                        direction: Direction::Down,
+                        start_time: depth.start_time,
+                        end_time: depth.end_time,
+                    };
+                    let value = change(get(&depths[i - 1]), get(depth));
+                    if Anomaly::record_if_anomalous(db, scored, value, &baseline).await? {
+                        recorded += 1;
+                    }
+                }
+            }
+        }
+        Ok(recorded)
+    }
+
+    // scores every swap and depth interval ingested after `since` against its pool's rolling baseline
+    #[tracing::instrument(skip(db))]
+    pub async fn detect_anomalies(db: &DataBase, since: i64) -> Result<u64, CustomError> {
+        let swap_anomalies = Anomaly::detect_swap_anomalies(db, since).await?;
+        let depth_anomalies = Anomaly::detect_depth_anomalies(db, since).await?;
+        Ok(swap_anomalies + depth_anomalies)
+    }
+}
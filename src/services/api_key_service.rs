@@ -0,0 +1,63 @@
+use chrono::Utc;
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+use crate::models::{api_key_model::{validate_role, ApiKey}, custom_error_model::CustomError};
+
+use super::db::DataBase;
+
+const KEY_PREFIX: &str = "tm_";
+const KEY_LENGTH: usize = 40;
+const DISPLAY_PREFIX_LENGTH: usize = 11;
+
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+impl ApiKey {
+    // creates a key and returns it in plain text, it can not be recovered afterwards
+    pub async fn mint_api_key(db: &DataBase, name: &str, role: &str) -> Result<String, CustomError> {
+        validate_role(role)?;
+        let random: String = rand::thread_rng().sample_iter(&Alphanumeric).take(KEY_LENGTH).map(char::from).collect();
+        let key = format!("{}{}", KEY_PREFIX, random);
+        let api_key = ApiKey {
+            _id: ObjectId::new(),
+            name: name.to_string(),
+            prefix: key[..DISPLAY_PREFIX_LENGTH].to_string(),
+            key_hash: hash_api_key(&key),
+            role: role.to_string(),
+            created_at: Utc::now().timestamp(),
+            revoked_at: None,
+        };
+        db.mongodb()?.api_keys.insert_one(api_key).await?;
+        Ok(key)
+    }
+
+    // revokes every active key with the given prefix, returns how many were revoked
+    pub async fn revoke_api_key(db: &DataBase, prefix: &str) -> Result<u64, CustomError> {
+        let result = db
+            .mongodb()?
+            .api_keys
+            .update_many(
+                doc! { "prefix": prefix, "revoked_at": null },
+                doc! { "$set": { "revoked_at": Utc::now().timestamp() } },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
+
+    pub async fn list_api_keys(db: &DataBase) -> Result<Vec<ApiKey>, CustomError> {
+        Ok(db.mongodb()?.api_keys.find(doc! {}).sort(doc! { "created_at": 1 }).await?.try_collect().await?)
+    }
+
+    // the active key matching the presented plain key
+    pub async fn find_active_api_key(db: &DataBase, key: &str) -> Result<Option<ApiKey>, CustomError> {
+        Ok(db
+            .mongodb()?
+            .api_keys
+            .find_one(doc! { "key_hash": hash_api_key(key), "revoked_at": null })
+            .await?)
+    }
+}
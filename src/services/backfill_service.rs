@@ -0,0 +1,173 @@
+use futures_util::{stream, StreamExt};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::{
+    config::Config,
+    models::{
+        custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory, earning_history_model::PoolEarningHistory,
+        rune_pool_model::RunePool, swap_history_model::SwapHistory,
+    },
+    utils::constants::INGESTED_COLLECTIONS,
+};
+
+use super::{
+    db::DataBase, depth_history_service, earnings_history_service, metrics_service::METRICS, midgard_page_service::page_end_time,
+    rune_pool_service, swap_history_service,
+};
+
+// backfills always store hour intervals like the cron
+const BACKFILL_INTERVAL: &str = "hour";
+const BACKFILL_INTERVAL_SECS: i64 = 3_600;
+
+#[derive(Debug,Serialize)]
+pub struct BackfillReport{
+    pub collection : String,
+    pub pool : Option<String>,
+    pub pages : u32,
+    pub failed_pages : u32,
+    // end_time reported by the last successful page
+    pub end_time : Option<i64>
+}
+
+// depths and swaps are stored per pool, earnings and rune pool history cover every pool at once
+pub fn is_pool_collection(collection: &str) -> bool {
+    collection == "depths" || collection == "swaps"
+}
+
+pub fn validate_collection(collection: &str) -> Result<(), CustomError> {
+    if INGESTED_COLLECTIONS.contains(&collection) {
+        Ok(())
+    } else {
+        Err(CustomError::InvalidInput(format!("collection must be in {:?}", INGESTED_COLLECTIONS)))
+    }
+}
+
+// a midgard page fetched ahead of being stored
+enum FetchedPage {
+    Depths(depth_history_service::ApiResponse),
+    // the swaps meta is several times the size of the others
+    Swaps(Box<swap_history_service::ApiResponse>),
+    Earnings(earnings_history_service::ApiResponse),
+    RunePool(rune_pool_service::ApiResponse),
+}
+
+impl FetchedPage {
+    fn end_time(&self) -> Result<i64, CustomError> {
+        page_end_time(match self {
+            FetchedPage::Depths(page) => &page.meta.end_time,
+            FetchedPage::Swaps(page) => &page.meta.end_time,
+            FetchedPage::Earnings(page) => &page.meta.end_time,
+            FetchedPage::RunePool(page) => &page.meta.end_time,
+        })
+    }
+
+    // the end_time of the stored page, intervals that fail validation are quarantined
+    async fn store(self, db: &DataBase, pool: Option<&str>) -> Result<i64, CustomError> {
+        let end_time = self.end_time()?;
+        match (self, pool) {
+            (FetchedPage::Depths(page), Some(pool)) => PoolDepthPriceHistory::store_price_history(db, pool, page.intervals).await?,
+            (FetchedPage::Swaps(page), Some(pool)) => SwapHistory::store_swap_history(db, pool, page.intervals).await?,
+            (FetchedPage::Earnings(page), _) => PoolEarningHistory::store_earning_history(db, page.intervals).await?,
+            (FetchedPage::RunePool(page), _) => RunePool::store_rune_pool(db, page.intervals).await?,
+            _ => return Err(CustomError::InvalidInput("Per pool page without a pool".to_string())),
+        };
+        Ok(end_time)
+    }
+}
+
+// the label of the ingestion metrics
+fn metric_collection(collection: &str) -> &'static str {
+    match collection {
+        "depths" => "depth_history",
+        "swaps" => "swap_history",
+        "earnings" => "earnings",
+        _ => "rune_pool_history",
+    }
+}
+
+async fn fetch_page(config: &Config, collection: &str, pool: Option<&str>, from: i64) -> Result<FetchedPage, CustomError> {
+    let base_url = &config.midgard.base_url;
+    let count = &config.midgard.page_size.to_string();
+    let from = &from.to_string();
+    Ok(match (collection, pool) {
+        ("depths", Some(pool)) => FetchedPage::Depths(PoolDepthPriceHistory::fetch_price_history_page(base_url, pool, BACKFILL_INTERVAL, count, from).await?),
+        ("swaps", Some(pool)) => FetchedPage::Swaps(Box::new(SwapHistory::fetch_swap_history_page(base_url, pool, BACKFILL_INTERVAL, count, from).await?)),
+        ("earnings", None) => FetchedPage::Earnings(PoolEarningHistory::fetch_earning_history_page(base_url, BACKFILL_INTERVAL, count, from).await?),
+        ("runepool", None) => FetchedPage::RunePool(RunePool::fetch_rune_pool_page(base_url, BACKFILL_INTERVAL, count, from).await?),
+        _ => return Err(CustomError::InvalidInput(format!("Unsupported collection and pool {} {:?}", collection, pool))),
+    })
+}
+
+// pages through midgard from `from` until a page reaches `to`, a failed page is skipped so one
+// network error does not stop the whole backfill, `gaps` finds what was skipped.
+// the next page is fetched while the previous one is stored, at most `prefetch_pages` wait to be stored
+async fn backfill_one(db: &DataBase, config: &Config, collection: &str, pool: Option<&str>, from: i64, to: i64) -> BackfillReport {
+    let page_secs = config.midgard.page_size as i64 * BACKFILL_INTERVAL_SECS;
+    let mut report = BackfillReport {
+        collection: collection.to_string(),
+        pool: pool.map(str::to_string),
+        pages: 0,
+        failed_pages: 0,
+        end_time: None,
+    };
+    let (pages, mut fetched) = mpsc::channel::<(i64, Result<FetchedPage, CustomError>)>(config.ingestion.prefetch_pages.max(1));
+    let fetcher = async move {
+        let mut start = from;
+        while start < to {
+            let page = fetch_page(config, collection, pool, start).await;
+            let end_time = page.as_ref().map_err(|_| ()).and_then(|page| page.end_time().map_err(|_| ()));
+            if pages.send((start, page)).await.is_err() {
+                break;
+            }
+            match end_time {
+                // midgard answers with an end_time at or before `from` once nothing newer exists
+                Ok(end_time) if end_time <= start => break,
+                Ok(end_time) => start = end_time,
+                Err(()) => start += page_secs,
+            }
+        }
+    };
+    let writer = async {
+        while let Some((start, page)) = fetched.recv().await {
+            report.pages += 1;
+            let result = match page {
+                Ok(page) => page.store(db, pool).await,
+                Err(e) => Err(e),
+            };
+            METRICS.record_fetch(metric_collection(collection), &result);
+            match result {
+                Ok(end_time) => report.end_time = Some(end_time),
+                Err(e) => {
+                    warn!(collection, pool, start, error = ?e, "Skipping failed backfill page");
+                    report.failed_pages += 1;
+                }
+            }
+        }
+    };
+    tokio::join!(fetcher, writer);
+    info!(collection, pool, pages = report.pages, failed_pages = report.failed_pages, "Backfill finished");
+    report
+}
+
+// pool None backfills every configured pool of the per pool collections
+#[tracing::instrument(skip(db, config))]
+pub async fn backfill(db: &DataBase, config: &Config, collection: &str, pool: Option<&str>, from: i64, to: i64) -> Result<Vec<BackfillReport>, CustomError> {
+    validate_collection(collection)?;
+    if from >= to {
+        return Err(CustomError::InvalidInput("from must be before to".to_string()));
+    }
+    let pools: Vec<Option<&str>> = match (is_pool_collection(collection), pool) {
+        (true, Some(pool)) => vec![Some(pool)],
+        (true, None) => config.pools.iter().map(|pool| Some(pool.as_str())).collect(),
+        (false, None) => vec![None],
+        (false, Some(_)) => return Err(CustomError::InvalidInput(format!("{} is not stored per pool", collection))),
+    };
+    // `concurrency` pools at once, the reports keep the order of the pools
+    Ok(stream::iter(pools)
+        .map(|pool| backfill_one(db, config, collection, pool, from, to))
+        .buffered(config.ingestion.concurrency.max(1))
+        .collect()
+        .await)
+}
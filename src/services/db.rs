@@ -1,44 +1,108 @@
-use crate::models::{custom_error_model::CustomError,
+use crate::{config::{without_credentials, DatabaseConfig}, models::{alert_rule_model::{AlertEvent, AlertRule},
+    anomaly_model::Anomaly,
+    api_key_model::ApiKey,
+    usage_model::ApiUsage,
+    custom_error_model::CustomError,
     depth_history_model::PoolDepthPriceHistory,
     earning_history_model::{PoolEarningHistory, PoolEarningSummary},
     rune_pool_model::RunePool,
+    stream_event_model::StreamEvent,
     swap_history_model::SwapHistory,
-};
+}, stores::{metrics_store::MetricsStore, mongo_store::MongoStore, postgres_store::PostgresStore, sqlite_store::SqliteStore}};
+use std::sync::Arc;
+
 use chrono::Utc;
-use dotenv::dotenv;
 use futures_util::StreamExt;
-use mongodb::{bson::doc,Client, Collection,};
-use std::env;
-pub struct DataBase {
+use mongodb::{bson::doc,Client, Collection, Database};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::{debug, error};
+
+// live subscribers that fall further behind than this miss events and have to resume with `since`
+const STREAM_CAPACITY: usize = 1024;
+
+// the collections on mongodb, the history ones back graphql, the others the keys, usage, anomalies and alerts
+pub struct MongoCollections {
+    // handle for commands that are not bound to a collection like ping
+    pub database: Database,
     pub depth_history: Collection<PoolDepthPriceHistory>,
     pub earnings: Collection<PoolEarningHistory>,
     pub earnings_summary: Collection<PoolEarningSummary>,
     pub swap_history: Collection<SwapHistory>,
     pub rune_pool_history: Collection<RunePool>,
+    pub anomalies: Collection<Anomaly>,
+    pub alert_rules: Collection<AlertRule>,
+    pub alert_events: Collection<AlertEvent>,
+    pub api_keys: Collection<ApiKey>,
+    pub api_usage: Collection<ApiUsage>,
 }
 
-impl DataBase {
-    // database initialization
-    pub async fn init() -> Self {
-        dotenv().ok();
+impl MongoCollections {
+    pub fn new(db: &Database) -> Self {
+        MongoCollections {
+            database: db.clone(),
+            depth_history: db.collection("depth_history"),
+            earnings: db.collection("earnings"),
+            earnings_summary: db.collection("earnings_summary"),
+            swap_history: db.collection("swap_history"),
+            rune_pool_history: db.collection("rune_pool_history"),
+            anomalies: db.collection("anomalies"),
+            alert_rules: db.collection("alert_rules"),
+            alert_events: db.collection("alert_events"),
+            api_keys: db.collection("api_keys"),
+            api_usage: db.collection("api_usage"),
+        }
+    }
+}
 
-        let uri = env::var("DB").unwrap();
-        let client = Client::with_uri_str(&uri).await.unwrap();
-        let db = client.database("token-metrics");
-        let depth_history_collection = db.collection("depth_history");
-        let earnings_collection = db.collection("earnings");
-        let earning_summary_collection = db.collection("earnings_summary");
-        let swap_history_collection = db.collection("swap_history");
-        let rune_pool_collection = db.collection("rune_pool_history");
+pub struct DataBase {
+    // None without database.uri, a sqlite history then runs without any mongodb
+    pub mongodb: Option<MongoCollections>,
+    // ingested history behind the history endpoints, mongodb unless database.history_uri is set or replaced with with_store
+    pub store: Arc<dyn MetricsStore>,
+    // newly stored intervals for /stream subscribers
+    pub stream: broadcast::Sender<StreamEvent>,
+}
 
-        // registered collections in the db
-        DataBase {
-            depth_history: depth_history_collection,
-            earnings: earnings_collection,
-            earnings_summary: earning_summary_collection,
-            swap_history: swap_history_collection,
-            rune_pool_history: rune_pool_collection,
-        }
+impl DataBase {
+    // database initialization, the errors name the backend and its url without credentials
+    pub async fn init(config: &DatabaseConfig) -> Result<Self, CustomError> {
+        let failed = |backend: &str, url: &str, e: &dyn std::fmt::Display| {
+            CustomError::DatabaseError(format!("Failed connecting to {} at {}: {}", backend, without_credentials(url), e))
+        };
+        let db = match config.uri.is_empty() {
+            true => None,
+            false => Some(Client::with_uri_str(&config.uri).await.map_err(|e| failed("MongoDB", &config.uri, &e))?.database(&config.name)),
+        };
+        let store: Arc<dyn MetricsStore> = match (&config.history_uri, &db) {
+            (Some(history_uri), _) if history_uri.starts_with("sqlite://") => {
+                Arc::new(SqliteStore::open(history_uri).map_err(|e| failed("SQLite", history_uri, &e))?)
+            }
+            (Some(history_uri), _) => Arc::new(PostgresStore::connect(history_uri).await.map_err(|e| failed("PostgreSQL", history_uri, &e))?),
+            (None, Some(db)) => Arc::new(MongoStore::new(db)),
+            (None, None) => return Err(CustomError::InvalidInput("database.uri is required unless database.history_uri is set".to_string())),
+        };
+
+        Ok(DataBase {
+            mongodb: db.as_ref().map(MongoCollections::new),
+            store,
+            stream: broadcast::channel(STREAM_CAPACITY).0,
+        })
+    }
+    // the mongodb collections or an error naming the missing setting
+    pub fn mongodb(&self) -> Result<&MongoCollections, CustomError> {
+        self.mongodb
+            .as_ref()
+            .ok_or_else(|| CustomError::DatabaseError("MongoDB is not configured, set database.uri to use this feature".to_string()))
+    }
+    // serve and ingest the history from another store, the other collections stay on mongodb
+    pub fn with_store(mut self, store: Arc<dyn MetricsStore>) -> Self {
+        self.store = store;
+        self
+    }
+    // helper function to notify /stream subscribers of a stored record, no subscribers is not an error
+    pub fn publish<T: Serialize>(&self, topic: &str, pool: Option<&str>, end_time: i64, record: &T) {
+        let _ = self.stream.send(StreamEvent::from_record(topic, pool, end_time, record));
     }
     // helper functions to get the latest timestamp of record in the collection
     pub async fn get_max_end_time<T>(&self, collection: &Collection<T>) -> Result<i64, CustomError>
@@ -61,12 +125,12 @@ impl DataBase {
                 Ok(doc) => {
                     let fetched_end_time =
                         doc.get_i64("end_time").unwrap_or(Utc::now().timestamp());
-                    println!("{:?}", fetched_end_time);
+                    debug!(fetched_end_time, "max end_time");
 
                     Ok(fetched_end_time)
                 }
                 Err(e) => {
-                    eprintln!("Failed to fetch max end_time: {}", e);
+                    error!(error = %e, "Failed to fetch max end_time");
                     Err(CustomError::DatabaseError(e.to_string()))
                 }
             }
@@ -1,15 +1,19 @@
 use serde::{Deserialize, Serialize};
 
-use crate::models::{custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory};
-use super::db::DataBase;
+use crate::models::{custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory, quarantine_model::QuarantinedInterval};
+use super::{
+    db::DataBase, metrics_service::METRICS, midgard_page_service::{fetch_page, page_end_time},
+    validation_service::{quarantine, validate_page, MidgardInterval},
+};
+use tracing::debug;
 
 // due to volume issues we are sticking to BTC BTC pool type in depths fetch
-fn generate_api_url(pool:&str,interval:&str,from:&str,count:&str) -> String{
-    format!("https://midgard.ninerealms.com/v2/history/depths/{}?interval={}&from={}&count={}",pool,interval,from,count)
+fn generate_api_url(base_url:&str,pool:&str,interval:&str,from:&str,count:&str) -> String{
+    format!("{}/v2/history/depths/{}?interval={}&from={}&count={}",base_url,pool,interval,from,count)
 }
 
 
-#[derive(Debug,Deserialize,Serialize)]
+#[derive(Debug,Default,Deserialize,Serialize)]
 #[serde(rename_all="camelCase")]
 pub struct Meta {
     pub end_asset_depth: String,
@@ -55,58 +59,65 @@ pub struct ApiResponse{
     pub meta : Meta
 }
 
+impl MidgardInterval for Interval {
+    type Record = PoolDepthPriceHistory;
+    fn start_time(&self) -> &str {
+        &self.start_time
+    }
+    fn end_time(&self) -> &str {
+        &self.end_time
+    }
+    fn depths(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("assetDepth", &self.asset_depth),
+            ("liquidityUnits", &self.liquidity_units),
+            ("runeDepth", &self.rune_depth),
+            ("synthSupply", &self.synth_supply),
+            ("synthUnits", &self.synth_units),
+            ("units", &self.units),
+        ]
+    }
+    fn into_record(self, pool: Option<&str>) -> Result<PoolDepthPriceHistory, CustomError> {
+        let mut record = PoolDepthPriceHistory::try_from(self)?;
+        // midgard depth intervals do not carry their pool
+        record.pool = pool.unwrap_or_default().to_string();
+        Ok(record)
+    }
+}
+
 impl PoolDepthPriceHistory{
-    pub async fn store_price_history(db: &DataBase, data: ApiResponse) -> Result<(),CustomError>{
-        for interval in data.intervals {
-            match PoolDepthPriceHistory::try_from(interval) {
-                Ok(pool_history_interval) => {
-                    if let Err(e) = db.depth_history.insert_one(pool_history_interval).await {
-                        eprint!("Error inserting record: {:?}", e);
-                    }
-                },
-                Err(e) => {
-                    return Err(CustomError::DatabaseError(format!("Error writing pool history to db: {:?}", e)));
-                }
-            }
+    // returns the intervals that failed validation, they are quarantined instead of stored
+    #[tracing::instrument(skip(db, intervals))]
+    pub async fn store_price_history(db: &DataBase, pool: &str, intervals: Vec<Interval>) -> Result<Vec<QuarantinedInterval>,CustomError>{
+        let page = validate_page("depths", Some(pool), intervals);
+        quarantine(db, &page.quarantined).await?;
+        // one insert_many for the page
+        if let Err(e) = db.store.insert_depths(&page.records).await {
+            return Err(CustomError::DatabaseError(format!("Error inserting depth records {}", e)));
+        }
+        for pool_history_interval in &page.records {
+            db.publish("depths", Some(&pool_history_interval.pool), pool_history_interval.end_time, pool_history_interval);
+            METRICS.record_insert("depth_history");
         }
-        Ok(())
+        Ok(page.quarantined)
+    }
+    // fetches one page from midgard and stores it, failures and the reached end_time are exported on /metrics
+    #[tracing::instrument(skip(db), err(Debug))]
+    pub async fn fetch_price_history(db:&DataBase,base_url:&str,pool:&str,interval:&str,count:&str,from:&str) -> Result<i64,CustomError>{
+        let result = PoolDepthPriceHistory::try_fetch_price_history(db, base_url, pool, interval, count, from).await;
+        METRICS.record_fetch("depth_history", &result);
+        result
+    }
+    // one page from midgard, stored by the caller
+    pub async fn fetch_price_history_page(base_url:&str,pool:&str,interval:&str,count:&str,from:&str) -> Result<ApiResponse,CustomError>{
+        let url = generate_api_url(base_url,pool,interval,from,count);
+        debug!(url, "fetching depth history");
+        fetch_page(&url, "depth_history").await
     }
-    pub async fn fetch_price_history(db:&DataBase,pool:&str,interval:&str,count:&str,from:&str) -> Result<i64,CustomError>{
-        let url = generate_api_url(&pool,&interval,&from,&count);
-        println!("{}",url);
-        let api_response = match reqwest::get(&url).await {
-            Ok(res) => res,
-            Err(e) => return Err(CustomError::DatabaseError(format!("Failed to fetch data: {}", e)))
-        };
-    
-        let raw_body = match api_response.text().await {
-            Ok(res) => res,
-            Err(e) => return Err(CustomError::StandardError(format!("Failed to read response text: {}", e))),
-        };
-    
-        println!("Raw response: {}", raw_body);
-    
-        let response = match reqwest::get(&url).await {
-            Ok(res) => {
-                match res.json::<ApiResponse>().await {
-                    Ok(res) => res,
-                    Err(e) => return Err(CustomError::StandardError(format!("Parsing error - {}", e.to_string()))),
-                }
-            },
-            Err(e) => return Err(CustomError::StandardError(format!("{}", e.to_string()))),
-        };        
-    
-        // Extract end_time and handle any potential errors
-        let end_time = match response.meta.end_time.parse::<i64>() {
-            Ok(time) => time,
-            Err(e) => return Err(CustomError::StandardError(format!("Failed to parse end time: {}", e)))
-        };
-        // println!("{:?}",response);
-        match self::PoolDepthPriceHistory::store_price_history(db,response).await{
-            Ok(_res) => (),
-            Err(e) => return Err(e)
-        };
-        // println!("{}","in");
+    async fn try_fetch_price_history(db:&DataBase,base_url:&str,pool:&str,interval:&str,count:&str,from:&str) -> Result<i64,CustomError>{
+        let response = PoolDepthPriceHistory::fetch_price_history_page(base_url, pool, interval, count, from).await?;
+        let end_time = page_end_time(&response.meta.end_time)?;
+        PoolDepthPriceHistory::store_price_history(db, pool, response.intervals).await?;
         Ok(end_time)
     }
 }
@@ -1,16 +1,16 @@
 use serde::{Deserialize, Serialize};
 use mongodb::bson::oid::ObjectId;
-use crate::{models::{custom_error_model::CustomError, earning_history_model::{PoolEarningHistory, PoolEarningSummary}}, parse_field};
+use crate::{models::{custom_error_model::CustomError, earning_history_model::{EarningsInterval, PoolEarningHistory, PoolEarningSummary}, quarantine_model::QuarantinedInterval}, parse_field};
 
-use super::db::DataBase;
+use super::{
+    db::DataBase, metrics_service::METRICS, midgard_page_service::{fetch_page, page_end_time},
+    validation_service::{quarantine, validate_page, MidgardInterval},
+};
+use tracing::debug;
 
 // earnings history is designed to fetch data of all pool types (around 8L+ records)
-fn generate_api_url(interval:&str,from:&str,count:&str) -> String{
-    format!("https://midgard.ninerealms.com/v2/history/earnings?interval={}&from={}&count={}",interval,from,count)
-}
-
-fn generate_error_text(field_name:&str) -> String{
-    format!("Incorrect {} format",field_name)
+fn generate_api_url(base_url:&str,interval:&str,from:&str,count:&str) -> String{
+    format!("{}/v2/history/earnings?interval={}&from={}&count={}",base_url,interval,from,count)
 }
 
 #[derive(Debug,Serialize,Deserialize)]
@@ -25,20 +25,21 @@ pub struct Pool{
     pub earnings: String,
 }
 
-#[derive(Debug,Serialize,Deserialize)]
+// midgard's meta is an interval over the whole range, the fields keep its order
+#[derive(Debug,Default,Serialize,Deserialize)]
 #[serde(rename_all="camelCase")]
 pub struct Meta{
+    pub start_time: String,
+    pub end_time: String,
     pub avg_node_count: String,
     pub block_rewards: String,
     pub bonding_earnings: String,
     pub earnings: String,
-    pub end_time: String,
     pub liquidity_earnings: String,
     pub liquidity_fees: String,
-    pub pools: Vec<Pool>,
     #[serde(rename="runePriceUSD")]
     pub rune_price_usd: String,
-    pub start_time: String,
+    pub pools: Vec<Pool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -64,99 +65,94 @@ pub struct ApiResponse{
     pub intervals : Vec<Interval>
 }
 
+impl MidgardInterval for Interval {
+    type Record = EarningsInterval;
+    fn start_time(&self) -> &str {
+        &self.start_time
+    }
+    fn end_time(&self) -> &str {
+        &self.end_time
+    }
+    fn depths(&self) -> Vec<(&'static str, &str)> {
+        let mut depths = vec![("earnings", self.earnings.as_str()), ("liquidityFees", self.liquidity_fees.as_str())];
+        depths.extend(self.pools.iter().map(|pool| ("pools.earnings", pool.earnings.as_str())));
+        depths
+    }
+    fn into_record(self, _pool: Option<&str>) -> Result<Self::Record, CustomError> {
+        PoolEarningHistory::from_interval(self)
+    }
+}
+
 impl PoolEarningHistory{
-    pub async fn store_earning_history(db: &DataBase, data: ApiResponse) -> Result<(), CustomError> {
-        for interval in data.intervals {
-            // iterate over each pool data in the interval of API Response
-            let pool_earning_summary = PoolEarningSummary {
+    // the summary of a midgard interval and the earnings of its pools
+    fn from_interval(interval: Interval) -> Result<EarningsInterval, CustomError> {
+        let pool_earning_summary = PoolEarningSummary {
+            _id: ObjectId::new(),
+            avg_node_count: parse_field!(interval, avg_node_count, f64),
+            block_rewards: parse_field!(interval, block_rewards, i64),
+            bonding_earnings: parse_field!(interval, bonding_earnings, i64),
+            earnings: parse_field!(interval, earnings, i64),
+            end_time: parse_field!(interval, end_time, i64),
+            liquidity_earnings: parse_field!(interval, liquidity_earnings, i64),
+            liquidity_fees: parse_field!(interval, liquidity_fees, i64),
+            start_time: parse_field!(interval, start_time, i64),
+            rune_price_usd: parse_field!(interval, rune_price_usd, f64),
+        };
+        let mut pools = Vec::new();
+        for pool in &interval.pools {
+            pools.push(PoolEarningHistory {
                 _id: ObjectId::new(),
-                avg_node_count: parse_field!(interval, avg_node_count, f64),
-                block_rewards: parse_field!(interval, block_rewards, f64),
-                bonding_earnings: parse_field!(interval, bonding_earnings, f64),
-                earnings: parse_field!(interval, earnings, u64),
-                end_time: parse_field!(interval, end_time, i64),
-                liquidity_earnings: parse_field!(interval, liquidity_earnings, f64),
-                liquidity_fees: parse_field!(interval, liquidity_fees, u64),
-                start_time: parse_field!(interval, start_time, i64),
-                rune_price_usd: parse_field!(interval, rune_price_usd, f64),
-            };
-            // collect the earnings summary of interval result
-            let earnings_summary_id = db.earnings_summary.insert_one(pool_earning_summary).await.unwrap().inserted_id;
-            let mut check = true;
-            for pool in interval.pools {
-                if check{
-                    println!("{:?}",pool);
-                }
-                check = false;
-                let pool_earnings = PoolEarningHistory {
-                    _id: ObjectId::new(),
-                    pool: pool.pool.clone(),
-                    asset_liquidity_fees: parse_field!(pool, asset_liquidity_fees, f64),
-                    earning: parse_field!(pool, earnings, u64),
-                    rewards: parse_field!(pool, rewards, f64),
-                    rune_liquidity_fees: parse_field!(pool, rune_liquidity_fees, f64),
-                    saver_earning: parse_field!(pool, saver_earning, f64),
-                    total_liquidity_fees_rune: parse_field!(pool, total_liquidity_fees_rune, f64),
-                    start_time: parse_field!(interval, start_time, i64),
-                    end_time: parse_field!(interval, end_time, i64),
-                    earnings_summary: earnings_summary_id.as_object_id().expect(&generate_error_text("earning summary id")),
-                };
-    
-                match db.earnings.insert_one(pool_earnings).await {
-                    Ok(_rec) => {
-                        println!("Successfully inserted earnings history to db");
-                    }
-                    Err(e) => return Err(CustomError::DatabaseError(format!("Failed inserting earnings history {}",e))),
-                }
+                pool: pool.pool.clone(),
+                asset_liquidity_fees: parse_field!(pool, asset_liquidity_fees, i64),
+                earning: parse_field!(pool, earnings, i64),
+                rewards: parse_field!(pool, rewards, i64),
+                rune_liquidity_fees: parse_field!(pool, rune_liquidity_fees, i64),
+                saver_earning: parse_field!(pool, saver_earning, i64),
+                total_liquidity_fees_rune: parse_field!(pool, total_liquidity_fees_rune, i64),
+                start_time: pool_earning_summary.start_time,
+                end_time: pool_earning_summary.end_time,
+                earnings_summary: pool_earning_summary._id,
+            });
+        }
+        Ok((pool_earning_summary, pools))
+    }
+
+    // returns the intervals that failed validation, they are quarantined instead of stored
+    #[tracing::instrument(skip(db, intervals))]
+    pub async fn store_earning_history(db: &DataBase, intervals: Vec<Interval>) -> Result<Vec<QuarantinedInterval>, CustomError> {
+        let page = validate_page("earnings", None, intervals);
+        quarantine(db, &page.quarantined).await?;
+        // the summaries and pools of the page are written together so a failure leaves no partial interval
+        if let Err(e) = db.store.insert_earnings_intervals(&page.records).await {
+            return Err(CustomError::DatabaseError(format!("Failed inserting earnings history {}", e)));
+        }
+        for (_summary, pools) in &page.records {
+            if let Some(pool) = pools.first() {
+                debug!(?pool, "first pool of the interval");
+            }
+            for pool_earnings in pools {
+                db.publish("earnings", Some(&pool_earnings.pool), pool_earnings.end_time, pool_earnings);
+                METRICS.record_insert("earnings");
             }
         }
-        Ok(())
-    }    
-    pub async fn fetch_earning_history(
-        db: &DataBase,
-        interval: &str,
-        count: &str,
-        from: &str,
-    ) -> Result<i64, CustomError> {
-        // Generate the API URL
-        let url = generate_api_url(interval, from, count);
-        print!("url - {}", url);
-        
-        let api_response = match reqwest::get(&url).await {
-            Ok(res) => res,
-            Err(e) => return Err(CustomError::StandardError(format!("Failed to fetch data: {}", e)))
-        };
-    
-        let raw_body = match api_response.text().await {
-            Ok(res) => res,
-            Err(e) => return Err(CustomError::InvalidInput(format!("Failed to read response text: {}", e))),
-        };
-    
-        println!("Raw response: {}", raw_body);
-    
-        let response = match reqwest::get(&url).await {
-            Ok(res) => {
-                match res.json::<ApiResponse>().await {
-                    Ok(res) => res,
-                    Err(e) => return Err(CustomError::InvalidInput(format!("Failed to parse JSON response: {}", e))),
-                }
-            },
-            Err(e) => return Err(CustomError::StandardError(format!("{}", e.to_string()))),
-        };        
-    
-        // Extract end_time and handle any potential errors
-        let end_time = match response.meta.end_time.parse::<i64>() {
-            Ok(time) => time,
-            Err(e) => return Err(CustomError::StandardError(format!("Failed to parse end time: {}", e)))
-        };
-    
-        // Store earning history and handle any potential errors
-        match self::PoolEarningHistory::store_earning_history(db, response).await{
-            Ok(_res) => (),
-            Err(e) => return Err(e)
-        };
-    
-        Ok(end_time) // Return the end_time if everything is successful
+        Ok(page.quarantined)
+    }
+    #[tracing::instrument(skip(db), err(Debug))]
+    pub async fn fetch_earning_history(db: &DataBase, base_url: &str, interval: &str, count: &str, from: &str) -> Result<i64, CustomError>{
+        let result = PoolEarningHistory::try_fetch_earning_history(db, base_url, interval, count, from).await;
+        METRICS.record_fetch("earnings", &result);
+        result
+    }
+    // one page from midgard, stored by the caller
+    pub async fn fetch_earning_history_page(base_url: &str, interval: &str, count: &str, from: &str) -> Result<ApiResponse, CustomError> {
+        let url = generate_api_url(base_url, interval, from, count);
+        debug!(url, "fetching earnings history");
+        fetch_page(&url, "earnings").await
+    }
+    async fn try_fetch_earning_history(db: &DataBase, base_url: &str, interval: &str, count: &str, from: &str) -> Result<i64, CustomError> {
+        let response = PoolEarningHistory::fetch_earning_history_page(base_url, interval, count, from).await?;
+        let end_time = page_end_time(&response.meta.end_time)?;
+        PoolEarningHistory::store_earning_history(db, response.intervals).await?;
+        Ok(end_time)
     }
-    
 }
\ No newline at end of file
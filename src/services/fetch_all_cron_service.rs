@@ -1,47 +1,68 @@
 use actix_web::web::Data;
 use chrono::Utc;
 use tokio::time::{interval, Duration};
-use dotenv::dotenv;
 use std::time::Instant;
-use crate::models::{depth_history_model::PoolDepthPriceHistory, earning_history_model::PoolEarningHistory, rune_pool_model::RunePool, swap_history_model::SwapHistory};
+use crate::{config::Config, models::{alert_rule_model::AlertRule, anomaly_model::Anomaly, depth_history_model::PoolDepthPriceHistory, earning_history_model::PoolEarningHistory, rune_pool_model::RunePool, swap_history_model::SwapHistory}, stores::metrics_store::HistoryCollection};
 
-use super::db::DataBase;
+use super::{db::DataBase, metrics_service::METRICS};
+use tracing::{error, info};
 
-const ONE_HOUR_SECS: u64 = 3_600;
-
-pub async fn run_cron_job(db: Data<DataBase>, pool: &str) {
-    dotenv().ok();
-
-    let mut interval = interval(Duration::from_secs(ONE_HOUR_SECS));
+pub async fn run_cron_job(db: Data<DataBase>, config: Data<Config>) {
+    let mut interval = interval(Duration::from_secs(config.ingestion.interval_secs));
 
     loop {
         interval.tick().await; // Wait for the next tick
         let start_time = Instant::now();
+        // intervals stored after this point are new in this cycle and get scored for anomalies
+        let last_swap_end_time = db.store.latest_end_time(HistoryCollection::Swaps).await.ok().flatten().unwrap_or(Utc::now().timestamp());
+        let last_depth_end_time = db.store.latest_end_time(HistoryCollection::Depths).await.ok().flatten().unwrap_or(Utc::now().timestamp());
 
         // Just try to perform tasks and ignore the logging part for errors
-        if perform_all_tasks(&db, &pool).await.is_ok() {
-            println!("All fetches completed.");
+        if perform_all_tasks(&db, &config).await.is_ok() {
+            info!("All fetches completed.");
+            METRICS.ingest_cycles.with_label_values(&["ok"]).inc();
+        } else {
+            METRICS.ingest_cycles.with_label_values(&["failed"]).inc();
         }
 
-        println!(
-            "Data fetch cycle completed, duration: {:?}",
-            start_time.elapsed()
+        // anomalies and alert rules are kept in mongodb, their history comes from the store
+        if db.mongodb.is_some() {
+            match Anomaly::detect_anomalies(&db, last_swap_end_time.min(last_depth_end_time)).await {
+                Ok(recorded) => info!(recorded, "Anomaly detection finished"),
+                Err(e) => error!(error = ?e, "Anomaly detection failed"),
+            }
+
+            match AlertRule::evaluate_alert_rules(&db).await {
+                Ok(fired) => info!(fired, "Alert evaluation finished"),
+                Err(e) => error!(error = ?e, "Alert evaluation failed"),
+            }
+        }
+
+        info!(
+            elapsed_ms = start_time.elapsed().as_millis() as u64,
+            "Data fetch cycle completed"
         );
+        METRICS.ingest_last_cycle_duration.set(start_time.elapsed().as_secs() as i64);
     }
 }
 
-async fn perform_all_tasks(db: &DataBase, pool: &str) -> Result<(), ()> {
-    let one_hour_ago = Utc::now().timestamp() - ONE_HOUR_SECS as i64;
+async fn perform_all_tasks(db: &DataBase, config: &Config) -> Result<(), ()> {
+    let one_cycle_ago = Utc::now().timestamp() - config.ingestion.interval_secs as i64;
     let interval_str = "hour";
-    let start_timer = &one_hour_ago.to_string();
+    let start_timer = &one_cycle_ago.to_string();
+    let base_url = &config.midgard.base_url;
+    let count = &config.midgard.page_size.to_string();
 
     // Collect task results but discard error messages
-    let tasks = vec![
-        SwapHistory::fetch_swap_history(db, pool, interval_str, "400", start_timer).await,
-        RunePool::fetch_rune_pool(db, "hour", "400", start_timer).await,
-        PoolEarningHistory::fetch_earning_history(db, interval_str, "400", start_timer).await,
-        PoolDepthPriceHistory::fetch_price_history(db, "BTC.BTC", interval_str, "400", start_timer).await,
-    ];
+    let mut tasks = Vec::new();
+    for pool in &config.pools {
+        tasks.push(SwapHistory::fetch_swap_history(db, base_url, pool, interval_str, count, start_timer).await);
+    }
+    tasks.push(RunePool::fetch_rune_pool(db, base_url, "hour", count, start_timer).await);
+    tasks.push(PoolEarningHistory::fetch_earning_history(db, base_url, interval_str, count, start_timer).await);
+    for pool in &config.pools {
+        tasks.push(PoolDepthPriceHistory::fetch_price_history(db, base_url, pool, interval_str, count, start_timer).await);
+    }
 
     // If any task returns an error, immediately return an Err result
     for task in tasks {
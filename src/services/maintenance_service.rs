@@ -0,0 +1,232 @@
+use std::{collections::BTreeMap, io::Write};
+
+use mongodb::bson::{doc, Bson, Document};
+use serde::Serialize;
+
+use crate::{
+    models::{custom_error_model::CustomError, earning_history_model::EarningsMismatch},
+    stores::{metrics_store::{for_each_record_page, HistoryCollection, RecordQuery}, mongo_store::create_indexes},
+};
+
+use super::{backfill_service::validate_collection, db::DataBase};
+
+const HOUR_SECS: i64 = 3_600;
+
+#[derive(Debug,Serialize)]
+pub struct Gap{
+    pub pool : Option<String>,
+    // end_time of the last stored interval before the gap
+    pub after_end_time : i64,
+    // start_time of the first stored interval after the gap
+    pub next_start_time : i64,
+    pub missing_intervals : i64
+}
+
+#[derive(Debug,Serialize)]
+pub struct VerifyReport{
+    pub collection : String,
+    pub records : u64,
+    // (pool, end_time, copies) of intervals stored more than once
+    pub duplicates : Vec<(Option<String>, i64, i64)>,
+    // intervals whose end_time is not one hour after their start_time
+    pub malformed : u64,
+    pub gaps : usize,
+    // earnings summaries whose pools do not add up to them, informational only
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inconsistent_summaries : Vec<EarningsMismatch>
+}
+
+impl VerifyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.duplicates.is_empty() && self.malformed == 0 && self.gaps == 0
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+fn record_query(collection: &str, pool: Option<&str>, from: Option<i64>, to: Option<i64>) -> Result<(HistoryCollection, RecordQuery), CustomError> {
+    validate_collection(collection)?;
+    let collection = HistoryCollection::ALL
+        .into_iter()
+        .find(|history_collection| history_collection.as_str() == collection)
+        .ok_or(CustomError::InvalidInput(format!("Unknown collection {}", collection)))?;
+    let query = RecordQuery {
+        pool: pool.map(str::to_string),
+        from,
+        to,
+        limit: i64::MAX,
+        ..RecordQuery::default()
+    };
+    Ok((collection, query))
+}
+
+// the stored records of an ingested collection oldest first, from whichever store holds the history
+async fn stored_records(db: &DataBase, collection: &str, pool: Option<&str>, from: Option<i64>, to: Option<i64>) -> Result<Vec<Document>, CustomError> {
+    let (collection, query) = record_query(collection, pool, from, to)?;
+    db.store.records(collection, &query).await
+}
+
+fn record_pool(record: &Document) -> Option<String> {
+    record.get_str("pool").ok().map(str::to_string)
+}
+
+// gaps between the records of each pool, fed oldest first
+#[derive(Default)]
+struct GapFinder {
+    previous_end_times: BTreeMap<Option<String>, i64>,
+    gaps: Vec<Gap>,
+}
+
+impl GapFinder {
+    fn add(&mut self, record: &Document) {
+        let (Ok(start_time), Ok(end_time)) = (record.get_i64("start_time"), record.get_i64("end_time")) else {
+            return;
+        };
+        let pool = record_pool(record);
+        match self.previous_end_times.get_mut(&pool) {
+            Some(previous_end_time) => {
+                if start_time > *previous_end_time {
+                    self.gaps.push(Gap {
+                        pool,
+                        after_end_time: *previous_end_time,
+                        next_start_time: start_time,
+                        missing_intervals: (start_time - *previous_end_time) / HOUR_SECS,
+                    });
+                }
+                // duplicates and overlaps must not move the cursor backwards
+                *previous_end_time = end_time.max(*previous_end_time);
+            }
+            None => {
+                self.previous_end_times.insert(pool, end_time);
+            }
+        }
+    }
+
+    fn into_gaps(mut self) -> Vec<Gap> {
+        self.gaps.sort_by(|a, b| (&a.pool, a.after_end_time).cmp(&(&b.pool, b.after_end_time)));
+        self.gaps
+    }
+}
+
+// hour intervals missing between stored ones, per pool
+pub async fn find_gaps(db: &DataBase, collection: &str, pool: Option<&str>, from: Option<i64>, to: Option<i64>) -> Result<Vec<Gap>, CustomError> {
+    let (collection, query) = record_query(collection, pool, from, to)?;
+    let mut gaps = GapFinder::default();
+    for_each_record_page(db.store.as_ref(), collection, query, |page| page.iter().for_each(|record| gaps.add(record))).await?;
+    Ok(gaps.into_gaps())
+}
+
+// duplicate, malformed and missing intervals of a collection, read a page at a time
+pub async fn verify_collection(db: &DataBase, collection: &str) -> Result<VerifyReport, CustomError> {
+    let (history_collection, query) = record_query(collection, None, None, None)?;
+    let mut records = 0;
+    let mut duplicates = Vec::new();
+    let mut malformed = 0;
+    let mut gaps = GapFinder::default();
+    for_each_record_page(db.store.as_ref(), history_collection, query, |page| {
+        records += page.len() as u64;
+        // pages hold whole end_times, so every copy of an interval is in the same page
+        let mut copies: BTreeMap<(Option<String>, i64), i64> = BTreeMap::new();
+        for record in &page {
+            let (start_time, end_time) = (record.get_i64("start_time").ok(), record.get_i64("end_time").ok());
+            if let Some(end_time) = end_time {
+                *copies.entry((record_pool(record), end_time)).or_default() += 1;
+            }
+            if start_time.zip(end_time).is_none_or(|(start_time, end_time)| end_time - start_time != HOUR_SECS) {
+                malformed += 1;
+            }
+            gaps.add(record);
+        }
+        duplicates.extend(
+            copies
+                .into_iter()
+                .filter(|(_, copies)| *copies > 1)
+                .map(|((pool, end_time), copies)| (pool, end_time, copies)),
+        );
+    })
+    .await?;
+    duplicates.sort();
+    let inconsistent_summaries = match collection {
+        "earnings" => db.store.earnings_mismatches().await?,
+        _ => Vec::new(),
+    };
+    Ok(VerifyReport {
+        collection: collection.to_string(),
+        records,
+        duplicates,
+        malformed,
+        gaps: gaps.into_gaps().len(),
+        inconsistent_summaries,
+    })
+}
+
+// indexes backing the history queries, the api key lookups and the usage upserts, creating an existing one is a no-op
+pub async fn reindex(db: &DataBase) -> Result<Vec<String>, CustomError> {
+    let mut created = db.store.reindex().await?;
+    // the other collections only exist with mongodb
+    if let Some(mongodb) = &db.mongodb {
+        created.extend(create_indexes(&mongodb.anomalies, vec![(doc! { "pool": 1, "end_time": -1 }, false)]).await?);
+        created.extend(create_indexes(&mongodb.alert_events, vec![(doc! { "rule_id": 1, "triggered_at": -1 }, false)]).await?);
+        created.extend(create_indexes(&mongodb.api_keys, vec![(doc! { "key_hash": 1 }, true), (doc! { "prefix": 1 }, false)]).await?);
+        created.extend(create_indexes(&mongodb.api_usage, vec![(doc! { "client": 1, "day": 1, "route_group": 1 }, true)]).await?);
+    }
+    Ok(created)
+}
+
+fn csv_value(value: Option<&Bson>) -> String {
+    let value = match value {
+        None | Some(Bson::Null) => String::new(),
+        Some(Bson::String(value)) => value.clone(),
+        Some(Bson::Double(value)) => value.to_string(),
+        Some(Bson::Int32(value)) => value.to_string(),
+        Some(Bson::Int64(value)) => value.to_string(),
+        Some(Bson::Boolean(value)) => value.to_string(),
+        Some(Bson::ObjectId(value)) => value.to_hex(),
+        Some(value) => value.clone().into_relaxed_extjson().to_string(),
+    };
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+// writes the records oldest first as json lines or csv with a header row, returns how many were written
+pub async fn export_collection(
+    db: &DataBase,
+    collection: &str,
+    pool: Option<&str>,
+    from: Option<i64>,
+    to: Option<i64>,
+    format: ExportFormat,
+    out: &mut dyn Write,
+) -> Result<u64, CustomError> {
+    let write_error = |e: std::io::Error| CustomError::StandardError(format!("Failed writing export {}", e));
+    let mut records = stored_records(db, collection, pool, from, to).await?;
+    records.sort_by_key(|record| (record.get_i64("end_time").ok(), record_pool(record)));
+    let mut header: Option<Vec<String>> = None;
+    let mut written = 0;
+    for mut record in records {
+        record.remove("_id");
+        match format {
+            ExportFormat::Json => {
+                writeln!(out, "{}", Bson::Document(record).into_relaxed_extjson()).map_err(write_error)?;
+            }
+            ExportFormat::Csv => {
+                let columns = header.get_or_insert_with(|| record.keys().cloned().collect());
+                if written == 0 {
+                    writeln!(out, "{}", columns.join(",")).map_err(write_error)?;
+                }
+                let row: Vec<String> = columns.iter().map(|column| csv_value(record.get(column))).collect();
+                writeln!(out, "{}", row.join(",")).map_err(write_error)?;
+            }
+        }
+        written += 1;
+    }
+    out.flush().map_err(write_error)?;
+    Ok(written)
+}
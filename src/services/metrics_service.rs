@@ -0,0 +1,133 @@
+use std::{future::IntoFuture, sync::LazyLock, time::Instant};
+
+use prometheus::{
+    histogram_opts, opts, Encoder, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Registry, TextEncoder,
+};
+
+use tracing::{debug, info_span, Instrument};
+
+use crate::models::custom_error_model::CustomError;
+
+// midgard answers 400 intervals in seconds, slow pages can take much longer than an api request
+const MIDGARD_BUCKETS: [f64; 9] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+pub struct Metrics{
+    registry : Registry,
+    // labels: method, route pattern, status
+    pub http_requests : IntCounterVec,
+    // labels: method, route pattern
+    pub http_request_duration : HistogramVec,
+    // labels: controller
+    pub db_aggregation_duration : HistogramVec,
+    // labels: collection
+    pub ingest_last_end_time : IntGaugeVec,
+    pub ingest_records_inserted : IntCounterVec,
+    pub ingest_fetch_failures : IntCounterVec,
+    pub ingest_records_quarantined : IntCounterVec,
+    pub midgard_request_duration : HistogramVec,
+    pub ingest_last_cycle_duration : IntGauge,
+    // labels: status
+    pub ingest_cycles : IntCounterVec
+}
+
+impl Metrics {
+    fn new() -> Result<Self, prometheus::Error> {
+        let metrics = Metrics {
+            registry: Registry::new(),
+            http_requests: IntCounterVec::new(
+                opts!("http_requests_total", "HTTP requests served"),
+                &["method", "route", "status"],
+            )?,
+            http_request_duration: HistogramVec::new(
+                histogram_opts!("http_request_duration_seconds", "HTTP request latency"),
+                &["method", "route"],
+            )?,
+            db_aggregation_duration: HistogramVec::new(
+                histogram_opts!("db_aggregation_duration_seconds", "MongoDB aggregation latency until the first batch"),
+                &["controller"],
+            )?,
+            ingest_last_end_time: IntGaugeVec::new(
+                opts!("ingest_last_end_time_seconds", "end_time of the last successful Midgard fetch"),
+                &["collection"],
+            )?,
+            ingest_records_inserted: IntCounterVec::new(
+                opts!("ingest_records_inserted_total", "Intervals written by the ingestion services"),
+                &["collection"],
+            )?,
+            ingest_fetch_failures: IntCounterVec::new(
+                opts!("ingest_fetch_failures_total", "Midgard fetches that failed to fetch, parse or store"),
+                &["collection"],
+            )?,
+            ingest_records_quarantined: IntCounterVec::new(
+                opts!("ingest_records_quarantined_total", "Midgard intervals that failed validation and were quarantined"),
+                &["collection"],
+            )?,
+            midgard_request_duration: HistogramVec::new(
+                histogram_opts!("midgard_request_duration_seconds", "Midgard history request latency", MIDGARD_BUCKETS.to_vec()),
+                &["collection"],
+            )?,
+            ingest_last_cycle_duration: IntGauge::new("ingest_last_cycle_duration_seconds", "Duration of the last cron ingestion cycle")?,
+            ingest_cycles: IntCounterVec::new(
+                opts!("ingest_cycles_total", "Cron ingestion cycles"),
+                &["status"],
+            )?,
+        };
+        metrics.registry.register(Box::new(metrics.http_requests.clone()))?;
+        metrics.registry.register(Box::new(metrics.http_request_duration.clone()))?;
+        metrics.registry.register(Box::new(metrics.db_aggregation_duration.clone()))?;
+        metrics.registry.register(Box::new(metrics.ingest_last_end_time.clone()))?;
+        metrics.registry.register(Box::new(metrics.ingest_records_inserted.clone()))?;
+        metrics.registry.register(Box::new(metrics.ingest_fetch_failures.clone()))?;
+        metrics.registry.register(Box::new(metrics.ingest_records_quarantined.clone()))?;
+        metrics.registry.register(Box::new(metrics.midgard_request_duration.clone()))?;
+        metrics.registry.register(Box::new(metrics.ingest_last_cycle_duration.clone()))?;
+        metrics.registry.register(Box::new(metrics.ingest_cycles.clone()))?;
+        Ok(metrics)
+    }
+
+    // prometheus text exposition of every registered metric
+    pub fn render(&self) -> Result<String, CustomError> {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(|e| CustomError::StandardError(format!("Failed encoding metrics {}", e)))?;
+        String::from_utf8(buffer).map_err(|e| CustomError::StandardError(format!("Failed encoding metrics {}", e)))
+    }
+
+    pub fn record_insert(&self, collection: &str) {
+        self.ingest_records_inserted.with_label_values(&[collection]).inc();
+    }
+
+    pub fn record_quarantine(&self, collection: &str) {
+        self.ingest_records_quarantined.with_label_values(&[collection]).inc();
+    }
+
+    // outcome of a fetch_* call, Ok holds the end_time reached
+    pub fn record_fetch(&self, collection: &str, result: &Result<i64, CustomError>) {
+        match result {
+            Ok(end_time) => self.ingest_last_end_time.with_label_values(&[collection]).set(*end_time),
+            Err(_) => self.ingest_fetch_failures.with_label_values(&[collection]).inc(),
+        }
+    }
+
+    pub fn record_midgard_request(&self, collection: &str, started: Instant) {
+        self.midgard_request_duration
+            .with_label_values(&[collection])
+            .observe(started.elapsed().as_secs_f64());
+    }
+}
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(|| Metrics::new().expect("metric names are valid and unique"));
+
+// times and traces the aggregate call of a controller, the cursor is returned once the first batch is ready
+pub async fn timed_aggregation<T>(controller: &str, aggregation: impl IntoFuture<Output = T>) -> T {
+    let started = Instant::now();
+    let result = aggregation.into_future().instrument(info_span!("aggregation", controller)).await;
+    let elapsed = started.elapsed();
+    METRICS
+        .db_aggregation_duration
+        .with_label_values(&[controller])
+        .observe(elapsed.as_secs_f64());
+    debug!(controller, elapsed_ms = elapsed.as_millis() as u64, "aggregation cursor ready");
+    result
+}
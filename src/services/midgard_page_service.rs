@@ -0,0 +1,32 @@
+use std::{sync::LazyLock, time::Instant};
+
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::models::custom_error_model::CustomError;
+
+use super::metrics_service::METRICS;
+
+// one client for every history request so connections to midgard are kept alive between pages
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+// a midgard history page with a single request, the body is read once and parsed,
+// `collection` labels the request duration on /metrics
+pub async fn fetch_page<T: DeserializeOwned>(url: &str, collection: &str) -> Result<T, CustomError> {
+    let started = Instant::now();
+    let response = CLIENT
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| CustomError::StandardError(format!("Failed to fetch data: {}", e)))?;
+    let body = response.bytes().await.map_err(|e| CustomError::StandardError(format!("Failed to read response: {}", e)))?;
+    METRICS.record_midgard_request(collection, started);
+    debug!(bytes = body.len(), "midgard response received");
+    serde_json::from_slice(&body).map_err(|e| CustomError::StandardError(format!("Failed to parse JSON response: {}", e)))
+}
+
+// the meta.endTime of a page, where the next page starts
+pub fn page_end_time(end_time: &str) -> Result<i64, CustomError> {
+    end_time.parse::<i64>().map_err(|e| CustomError::StandardError(format!("Failed to parse end time: {}", e)))
+}
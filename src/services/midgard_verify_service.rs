@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use mongodb::bson::{Bson, Document};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::{
+    config::Config,
+    models::{api_request_param_model::QueryParams, custom_error_model::CustomError},
+    stores::metrics_store::HistoryCollection,
+    utils::db_helper_utils::get_seconds_per_interval,
+};
+
+use super::db::DataBase;
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct MidgardVerifyPlan{
+    pub collections : Vec<HistoryCollection>,
+    // pools of /depths and /swaps
+    pub pools : Vec<String>,
+    pub intervals : Vec<String>,
+    // windows per collection, pool and interval, spread evenly over from..to
+    pub samples : u32,
+    // intervals per window
+    pub count : u32,
+    pub from : i64,
+    pub to : Option<i64>,
+    // relative, numbers within tolerance * max(|midgard|, |ours|, 1) are equal
+    pub tolerance : f64
+}
+
+impl MidgardVerifyPlan {
+    // every collection and configured pool over the ingested range
+    pub fn new(config: &Config) -> Self {
+        MidgardVerifyPlan {
+            collections: HistoryCollection::ALL.to_vec(),
+            pools: config.pools.clone(),
+            intervals: vec!["hour".to_string()],
+            samples: 5,
+            count: 24,
+            from: config.ingestion.start_time,
+            to: None,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancyKind {
+    // both sides have the field with different values
+    Value,
+    // midgard has a field our interval or meta lacks
+    MissingField,
+    // midgard has an interval (or earnings pool) we do not return
+    MissingInterval,
+    // we return an interval midgard does not have
+    ExtraInterval,
+    // either side failed to answer for the window
+    RequestFailed,
+}
+
+#[derive(Debug,Serialize)]
+pub struct Discrepancy{
+    pub collection : String,
+    pub pool : Option<String>,
+    pub interval : String,
+    pub from : i64,
+    pub to : i64,
+    // "meta", the interval startTime, or startTime/pool for earnings pools
+    pub key : String,
+    pub field : Option<String>,
+    pub kind : DiscrepancyKind,
+    pub midgard : Option<Value>,
+    pub ours : Option<Value>
+}
+
+#[derive(Debug,Default,Serialize)]
+pub struct MidgardVerifyReport{
+    pub windows : u32,
+    pub compared_fields : u64,
+    pub discrepancies : Vec<Discrepancy>
+}
+
+impl MidgardVerifyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+// (key, field) -> value of a response, flattened the same way for both sides
+type Fields = BTreeMap<String, BTreeMap<String, Value>>;
+
+fn scalar_fields(object: &serde_json::Map<String, Value>, skip: &[&str]) -> BTreeMap<String, Value> {
+    object
+        .iter()
+        .filter(|(field, value)| !value.is_array() && !value.is_object() && !skip.contains(&field.as_str()))
+        .map(|(field, value)| (field.clone(), value.clone()))
+        .collect()
+}
+
+fn time_key(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(time) => Some(time.clone()),
+        Value::Number(time) => Some(time.as_i64()?.to_string()),
+        _ => None,
+    }
+}
+
+// midgard: meta, intervals by startTime, earnings pools by startTime/pool
+fn midgard_fields(response: &Value) -> Fields {
+    let mut fields = Fields::new();
+    if let Some(meta) = response["meta"].as_object() {
+        fields.insert("meta".to_string(), scalar_fields(meta, &[]));
+        for pool in meta.get("pools").and_then(Value::as_array).into_iter().flatten() {
+            if let (Some(name), Some(pool)) = (pool["pool"].as_str(), pool.as_object()) {
+                fields.insert(format!("meta/{}", name), scalar_fields(pool, &["pool"]));
+            }
+        }
+    }
+    for interval in response["intervals"].as_array().into_iter().flatten() {
+        let (Some(start_time), Some(object)) = (time_key(interval.get("startTime")), interval.as_object()) else {
+            continue;
+        };
+        fields.insert(start_time.clone(), scalar_fields(object, &[]));
+        for pool in interval.get("pools").and_then(Value::as_array).into_iter().flatten() {
+            if let (Some(name), Some(pool)) = (pool["pool"].as_str(), pool.as_object()) {
+                fields.insert(format!("{}/{}", start_time, name), scalar_fields(pool, &["pool"]));
+            }
+        }
+    }
+    fields
+}
+
+// ours: earnings keep one summary and a flat pools list keyed by interval_start instead of intervals
+fn our_fields(collection: HistoryCollection, response: &Document, seconds_per_interval: i64) -> Fields {
+    let response = Bson::Document(response.clone()).into_relaxed_extjson();
+    let mut fields = Fields::new();
+    if let Some(meta) = response["meta"].as_object() {
+        fields.insert("meta".to_string(), scalar_fields(meta, &[]));
+    }
+    if collection != HistoryCollection::Earnings {
+        for interval in response["intervals"].as_array().into_iter().flatten() {
+            if let (Some(start_time), Some(object)) = (time_key(interval.get("startTime")), interval.as_object()) {
+                fields.insert(start_time, scalar_fields(object, &[]));
+            }
+        }
+        return fields;
+    }
+    if let Some(summary) = response["intervals"]["earnings_summary"].as_object() {
+        if let Some(start_time) = time_key(summary.get("startTime")) {
+            fields.insert(start_time, scalar_fields(summary, &[]));
+        }
+    }
+    for pool in response["intervals"]["pools"].as_array().into_iter().flatten() {
+        let (Some(interval_start), Some(name), Some(object)) = (pool["interval_start"].as_i64(), pool["pool"].as_str(), pool.as_object()) else {
+            continue;
+        };
+        let start_time = interval_start - interval_start % seconds_per_interval;
+        fields.insert(format!("{}/{}", start_time, name), scalar_fields(object, &["pool", "interval_start"]));
+    }
+    fields
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(number) => number.parse().ok(),
+        _ => None,
+    }
+}
+
+fn values_match(midgard: &Value, ours: &Value, tolerance: f64) -> bool {
+    match (number(midgard), number(ours)) {
+        (Some(midgard), Some(ours)) => (midgard - ours).abs() <= tolerance * midgard.abs().max(ours.abs()).max(1.0),
+        _ => midgard == ours,
+    }
+}
+
+struct Window<'a>{
+    collection : HistoryCollection,
+    pool : Option<&'a str>,
+    interval : &'a str,
+    from : i64,
+    to : i64
+}
+
+impl Window<'_> {
+    fn discrepancy(&self, key: &str, field: Option<&str>, kind: DiscrepancyKind, midgard: Option<Value>, ours: Option<Value>) -> Discrepancy {
+        Discrepancy {
+            collection: self.collection.as_str().to_string(),
+            pool: self.pool.map(str::to_string),
+            interval: self.interval.to_string(),
+            from: self.from,
+            to: self.to,
+            key: key.to_string(),
+            field: field.map(str::to_string),
+            kind,
+            midgard,
+            ours,
+        }
+    }
+
+    fn midgard_url(&self, base_url: &str) -> String {
+        let range = format!("interval={}&from={}&to={}", self.interval, self.from, self.to);
+        match (self.collection, self.pool) {
+            (HistoryCollection::Depths, Some(pool)) => format!("{}/v2/history/depths/{}?{}", base_url, pool, range),
+            (HistoryCollection::Swaps, Some(pool)) => format!("{}/v2/history/swaps?pool={}&{}", base_url, pool, range),
+            (HistoryCollection::Earnings, _) => format!("{}/v2/history/earnings?{}", base_url, range),
+            _ => format!("{}/v2/history/runepool?{}", base_url, range),
+        }
+    }
+
+    async fn our_response(&self, db: &DataBase, count: u32) -> Result<Document, CustomError> {
+        let count = match self.collection {
+            // earnings are limited per pool row, the window bounds them already
+            HistoryCollection::Earnings => u32::MAX,
+            _ => count,
+        };
+        let params = QueryParams {
+            pool: self.pool.map(str::to_string),
+            interval: Some(self.interval.to_string()),
+            count: Some(count),
+            to: Some(self.to as u64),
+            from: Some(self.from as u64),
+            page: None,
+            // midgard lists intervals oldest first
+            sort_by: Some("end_time".to_string()),
+            sort_order: Some(1),
+            limit: None,
+        };
+        match self.collection {
+            HistoryCollection::Depths => db.store.get_depth_price_history_api(params).await,
+            HistoryCollection::Swaps => db.store.get_swaps_history_api(params).await,
+            HistoryCollection::Earnings => db.store.get_pool_earnings_history_api(params).await,
+            HistoryCollection::RunePool => db.store.get_rune_pool_history_api(params).await,
+        }
+    }
+
+    fn compare(&self, midgard: &Fields, ours: &Fields, tolerance: f64, report: &mut MidgardVerifyReport) {
+        for (key, midgard_fields) in midgard {
+            let Some(our_fields) = ours.get(key) else {
+                report.discrepancies.push(self.discrepancy(key, None, DiscrepancyKind::MissingInterval, None, None));
+                continue;
+            };
+            for (field, midgard_value) in midgard_fields {
+                report.compared_fields += 1;
+                match our_fields.get(field) {
+                    None => report.discrepancies.push(self.discrepancy(key, Some(field), DiscrepancyKind::MissingField, Some(midgard_value.clone()), None)),
+                    Some(our_value) if !values_match(midgard_value, our_value, tolerance) => report.discrepancies.push(self.discrepancy(
+                        key,
+                        Some(field),
+                        DiscrepancyKind::Value,
+                        Some(midgard_value.clone()),
+                        Some(our_value.clone()),
+                    )),
+                    Some(_) => {}
+                }
+            }
+        }
+        for key in ours.keys().filter(|key| !midgard.contains_key(*key)) {
+            report.discrepancies.push(self.discrepancy(key, None, DiscrepancyKind::ExtraInterval, None, None));
+        }
+    }
+}
+
+// window ends spread evenly over from..to, aligned to the interval
+fn window_ends(from: i64, to: i64, span: i64, seconds_per_interval: i64, samples: u32) -> Vec<i64> {
+    let first = from + span;
+    if first >= to || samples <= 1 {
+        return vec![to - to % seconds_per_interval];
+    }
+    let mut ends: Vec<i64> = (0..samples as i64)
+        .map(|sample| first + (to - first) * sample / (samples as i64 - 1))
+        .map(|end| end - end % seconds_per_interval)
+        .collect();
+    ends.dedup();
+    ends
+}
+
+// compares sampled /depths, /swaps, /earnings and /runepool windows field by field with midgard
+#[tracing::instrument(skip(db, config))]
+pub async fn verify_against_midgard(db: &DataBase, config: &Config, plan: &MidgardVerifyPlan) -> Result<MidgardVerifyReport, CustomError> {
+    let client = reqwest::Client::new();
+    let mut report = MidgardVerifyReport::default();
+    for &collection in &plan.collections {
+        let pools: Vec<Option<&str>> = match collection {
+            HistoryCollection::Depths | HistoryCollection::Swaps => plan.pools.iter().map(|pool| Some(pool.as_str())).collect(),
+            _ => vec![None],
+        };
+        let to = match plan.to {
+            Some(to) => to,
+            None => db.store.latest_end_time(collection).await?.unwrap_or(Utc::now().timestamp()),
+        };
+        for pool in pools {
+            for interval in &plan.intervals {
+                let seconds_per_interval = get_seconds_per_interval(interval) as i64;
+                let span = plan.count as i64 * seconds_per_interval;
+                for end in window_ends(plan.from, to, span, seconds_per_interval, plan.samples) {
+                    let window = Window { collection, pool, interval, from: end - span, to: end };
+                    report.windows += 1;
+
+                    let midgard = match fetch_midgard(&client, &window.midgard_url(&config.midgard.base_url)).await {
+                        Ok(midgard) => midgard,
+                        Err(e) => {
+                            warn!(collection = collection.as_str(), pool, error = ?e, "Midgard request failed");
+                            report.discrepancies.push(window.discrepancy("", None, DiscrepancyKind::RequestFailed, Some(Value::String(e.to_string())), None));
+                            continue;
+                        }
+                    };
+                    let ours = match window.our_response(db, plan.count).await {
+                        Ok(ours) => ours,
+                        Err(e) => {
+                            report.discrepancies.push(window.discrepancy("", None, DiscrepancyKind::RequestFailed, None, Some(Value::String(e.to_string()))));
+                            continue;
+                        }
+                    };
+                    window.compare(&midgard_fields(&midgard), &our_fields(collection, &ours, seconds_per_interval), plan.tolerance, &mut report);
+                }
+            }
+        }
+    }
+    info!(windows = report.windows, discrepancies = report.discrepancies.len(), "Midgard verification finished");
+    Ok(report)
+}
+
+async fn fetch_midgard(client: &reqwest::Client, url: &str) -> Result<Value, CustomError> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| CustomError::InvalidInput(format!("Failed to fetch {}: {}", url, e)))?;
+    response.json().await.map_err(|e| CustomError::InvalidInput(format!("Failed to parse {}: {}", url, e)))
+}
@@ -1,12 +1,16 @@
 use serde::{Deserialize, Serialize};
-use crate::models::{custom_error_model::CustomError, rune_pool_model::RunePool};
-use super::db::DataBase;
+use crate::models::{custom_error_model::CustomError, quarantine_model::QuarantinedInterval, rune_pool_model::RunePool};
+use super::{
+    db::DataBase, metrics_service::METRICS, midgard_page_service::{fetch_page, page_end_time},
+    validation_service::{quarantine, validate_page, MidgardInterval},
+};
+use tracing::debug;
 
-fn generate_api_url(interval:&str,from:&str,count:&str) -> String{
-    format!("https://midgard.ninerealms.com/v2/history/runepool?interval={}&from={}&count={}",interval,from,count)
+fn generate_api_url(base_url:&str,interval:&str,from:&str,count:&str) -> String{
+    format!("{}/v2/history/runepool?interval={}&from={}&count={}",base_url,interval,from,count)
 }
 
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Default,Serialize,Deserialize)]
 #[serde(rename_all="camelCase")]
 pub struct Meta{
     pub end_count: String,
@@ -32,57 +36,51 @@ pub struct ApiResponse{
     pub intervals : Vec<Interval>
 }
 
+impl MidgardInterval for Interval {
+    type Record = RunePool;
+    fn start_time(&self) -> &str {
+        &self.start_time
+    }
+    fn end_time(&self) -> &str {
+        &self.end_time
+    }
+    fn into_record(self, _pool: Option<&str>) -> Result<RunePool, CustomError> {
+        RunePool::try_from(self)
+    }
+}
+
 impl RunePool{
-    pub async fn store_rune_pool(db:&DataBase,data:ApiResponse) -> Result<(),CustomError>{
-        for interval in data.intervals{
-            match RunePool::try_from(interval) {
-                Ok(rune_pool_object) => {
-                    match db.rune_pool_history.insert_one(rune_pool_object).await {
-                        Ok(_record) => println!("Rune pool record writted to db!"),
-                        Err(e) => eprintln!("Err adding rune pool to db {}",e)
-                    }
-                },
-                Err(e) => {
-                    return Err(CustomError::DatabaseError(format!("Error parsing interval to rune pool object! {}",e)));
-                }
-            }
+    // returns the intervals that failed validation, they are quarantined instead of stored
+    #[tracing::instrument(skip(db, intervals))]
+    pub async fn store_rune_pool(db:&DataBase,intervals:Vec<Interval>) -> Result<Vec<QuarantinedInterval>,CustomError>{
+        let page = validate_page("runepool", None, intervals);
+        quarantine(db, &page.quarantined).await?;
+        // one insert_many for the page
+        if let Err(e) = db.store.insert_rune_pool(&page.records).await {
+            return Err(CustomError::DatabaseError(format!("Err adding rune pool to db {}", e)));
+        }
+        for rune_pool_object in &page.records{
+            db.publish("runepool", None, rune_pool_object.end_time, rune_pool_object);
+            METRICS.record_insert("rune_pool_history");
         }
-        Ok(())
+        Ok(page.quarantined)
+    }
+    #[tracing::instrument(skip(db), err(Debug))]
+    pub async fn fetch_rune_pool(db:&DataBase,base_url:&str,interval:&str,count:&str,from:&str) -> Result<i64, CustomError>{
+        let result = RunePool::try_fetch_rune_pool(db, base_url, interval, count, from).await;
+        METRICS.record_fetch("rune_pool_history", &result);
+        result
+    }
+    // one page from midgard, stored by the caller
+    pub async fn fetch_rune_pool_page(base_url:&str,interval:&str,count:&str,from:&str) -> Result<ApiResponse, CustomError>{
+        let url = generate_api_url(base_url, interval, from, count);
+        debug!(url, "fetching rune pool history");
+        fetch_page(&url, "rune_pool_history").await
     }
-    pub async fn fetch_rune_pool(db:&DataBase,interval:&str,count:&str,from:&str) -> Result<i64, CustomError>{
-        let url = generate_api_url(interval, from, count);
-        println!("url - {}",&url);
-        let api_response = match reqwest::get(&url).await {
-            Ok(res) => res,
-            Err(e) => return Err(CustomError::StandardError(format!("Failed to fetch data: {}", e)))
-        };
-    
-        let raw_body = match api_response.text().await {
-            Ok(res) => res,
-            Err(e) => return Err(CustomError::InvalidInput(format!("Failed to read response text: {}", e))),
-        };
-    
-        println!("Raw response: {}", raw_body);
-    
-        let response = match reqwest::get(&url).await {
-            Ok(res) => {
-                match res.json::<ApiResponse>().await {
-                    Ok(res) => res,
-                    Err(e) => return Err(CustomError::InvalidInput(format!("Failed to parse JSON response: {}", e))),
-                }
-            },
-            Err(e) => return Err(CustomError::StandardError(format!("{}", e.to_string()))),
-        };        
-    
-        // Extract end_time and handle any potential errors
-        let end_time = match response.meta.end_time.parse::<i64>() {
-            Ok(time) => time,
-            Err(e) => return Err(CustomError::StandardError(format!("Failed to parse end time: {}", e)))
-        };
-        match self::RunePool::store_rune_pool(db, response).await{
-            Ok(_res) => (),
-            Err(e) => return Err(e)
-        };
+    async fn try_fetch_rune_pool(db:&DataBase,base_url:&str,interval:&str,count:&str,from:&str) -> Result<i64, CustomError>{
+        let response = RunePool::fetch_rune_pool_page(base_url, interval, count, from).await?;
+        let end_time = page_end_time(&response.meta.end_time)?;
+        RunePool::store_rune_pool(db, response.intervals).await?;
         Ok(end_time)
     }
 }
\ No newline at end of file
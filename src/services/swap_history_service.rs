@@ -1,12 +1,16 @@
 use serde::{Deserialize, Serialize};
-use crate::models::{custom_error_model::CustomError, swap_history_model::SwapHistory};
-use super::db::DataBase;
+use crate::models::{custom_error_model::CustomError, quarantine_model::QuarantinedInterval, swap_history_model::SwapHistory};
+use super::{
+    db::DataBase, metrics_service::METRICS, midgard_page_service::{fetch_page, page_end_time},
+    validation_service::{quarantine, validate_page, MidgardInterval},
+};
+use tracing::debug;
 
-fn generate_api_url(pool:&str,interval:&str,from:&str,count:&str) -> String{
-    format!("https://midgard.ninerealms.com/v2/history/swaps?pool={}&interval={}&from={}&count={}",pool,interval,from,count)
+fn generate_api_url(base_url:&str,pool:&str,interval:&str,from:&str,count:&str) -> String{
+    format!("{}/v2/history/swaps?pool={}&interval={}&from={}&count={}",base_url,pool,interval,from,count)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all="camelCase")]
 pub struct Meta {
     pub start_time: String,
@@ -114,51 +118,51 @@ pub struct ApiResponse{
     pub meta: Meta
 }
 
+impl MidgardInterval for Interval {
+    type Record = SwapHistory;
+    fn start_time(&self) -> &str {
+        &self.start_time
+    }
+    fn end_time(&self) -> &str {
+        &self.end_time
+    }
+    fn into_record(self, pool: Option<&str>) -> Result<SwapHistory, CustomError> {
+        SwapHistory::to_swap_history(self, pool.unwrap_or_default())
+    }
+}
+
 impl SwapHistory{
-    pub async fn store_swap_history(db:&DataBase,pool:&str,data:ApiResponse) -> Result<(),CustomError>{
-        for interval in data.intervals{
-            let pool_swap_history = SwapHistory::to_swap_history(interval, pool).unwrap();
-            match db.swap_history.insert_one(pool_swap_history).await {
-                Ok(_record) => println!("Inserted pool swap doc {}",pool),
-                Err(e) => return Err(CustomError::DatabaseError(format!("Error inserting swap doc to db {:?}",e)))
-            }
+    // returns the intervals that failed validation, they are quarantined instead of stored
+    #[tracing::instrument(skip(db, intervals))]
+    pub async fn store_swap_history(db:&DataBase,pool:&str,intervals:Vec<Interval>) -> Result<Vec<QuarantinedInterval>,CustomError>{
+        let page = validate_page("swaps", Some(pool), intervals);
+        quarantine(db, &page.quarantined).await?;
+        // one insert_many for the page
+        if let Err(e) = db.store.insert_swaps(&page.records).await {
+            return Err(CustomError::DatabaseError(format!("Error inserting swap docs to db {:?}",e)));
+        }
+        for pool_swap_history in &page.records{
+            db.publish("swaps", Some(pool), pool_swap_history.end_time, pool_swap_history);
+            METRICS.record_insert("swap_history");
         }
-        Ok(())
+        Ok(page.quarantined)
+    }
+    #[tracing::instrument(skip(db), err(Debug))]
+    pub async fn fetch_swap_history(db:&DataBase,base_url:&str,pool:&str,interval:&str,count:&str,from:&str) -> Result<i64,CustomError>{
+        let result = SwapHistory::try_fetch_swap_history(db, base_url, pool, interval, count, from).await;
+        METRICS.record_fetch("swap_history", &result);
+        result
+    }
+    // one page from midgard, stored by the caller
+    pub async fn fetch_swap_history_page(base_url:&str,pool:&str,interval:&str,count:&str,from:&str) -> Result<ApiResponse,CustomError>{
+        let url = generate_api_url(base_url,pool,interval,from,count);
+        debug!(url, "fetching swap history");
+        fetch_page(&url, "swap_history").await
     }
-    pub async fn fetch_swap_history(db:&DataBase,pool:&str,interval:&str,count:&str,from:&str) -> Result<i64,CustomError>{
-        let url = generate_api_url(&pool,&interval, &from, &count);
-        println!("url - {}",url);
-        let api_response = match reqwest::get(&url).await {
-            Ok(res) => res,
-            Err(e) => return Err(CustomError::InvalidInput(format!("Failed to fetch data: {}", e)))
-        };
-    
-        let raw_body = match api_response.text().await {
-            Ok(res) => res,
-            Err(e) => return Err(CustomError::InvalidInput(format!("Failed to read response text: {}", e))),
-        };
-    
-        println!("Raw response: {}", raw_body);
-    
-        let response = match reqwest::get(&url).await {
-            Ok(res) => {
-                match res.json::<ApiResponse>().await {
-                    Ok(res) => res,
-                    Err(e) => return Err(CustomError::DatabaseError(format!("Failed to parse JSON response: {}", e))),
-                }
-            },
-            Err(e) => return Err(CustomError::InvalidInput(format!("Failed getting data at swap history {}", e.to_string()))),
-        };        
-    
-        // Extract end_time and handle any potential errors
-        let end_time = match response.meta.end_time.parse::<i64>() {
-            Ok(time) => time,
-            Err(e) => return Err(CustomError::InvalidInput(format!("Failed to parse end time: {}", e)))
-        };
-        match self::SwapHistory::store_swap_history(db, pool, response).await{
-            Ok(_res) => (),
-            Err(e) => return Err(e)
-        };
+    async fn try_fetch_swap_history(db:&DataBase,base_url:&str,pool:&str,interval:&str,count:&str,from:&str) -> Result<i64,CustomError>{
+        let response = SwapHistory::fetch_swap_history_page(base_url, pool, interval, count, from).await?;
+        let end_time = page_end_time(&response.meta.end_time)?;
+        SwapHistory::store_swap_history(db, pool, response.intervals).await?;
         Ok(end_time)
     }
 }
\ No newline at end of file
@@ -0,0 +1,152 @@
+use chrono::Utc;
+use mongodb::bson::oid::ObjectId;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::models::{
+    custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory, earning_history_model::PoolEarningHistory,
+    quarantine_model::{QuarantineEntry, QuarantinedInterval, ReplayReport}, rune_pool_model::RunePool, swap_history_model::SwapHistory,
+};
+
+use super::{db::DataBase, depth_history_service, earnings_history_service, metrics_service::METRICS, rune_pool_service, swap_history_service};
+
+// every interval of an ingested midgard page is checked before it is stored, one that fails goes to the
+// quarantine with its reasons and the rest of the page is stored
+
+// the ingestion always requests hour intervals
+const HOUR: i64 = 3_600;
+
+// an interval of a midgard history response and the record it is stored as
+pub trait MidgardInterval: Serialize + DeserializeOwned {
+    type Record;
+    fn start_time(&self) -> &str;
+    fn end_time(&self) -> &str;
+    // (field, value) of the amounts that can not be negative
+    fn depths(&self) -> Vec<(&'static str, &str)> {
+        Vec::new()
+    }
+    fn into_record(self, pool: Option<&str>) -> Result<Self::Record, CustomError>;
+}
+
+pub struct ValidatedPage<T>{
+    pub records : Vec<T>,
+    pub quarantined : Vec<QuarantinedInterval>
+}
+
+// every string of a midgard interval is a number, except the pool of the earnings pools
+fn unparsable_numbers(value: &Value, path: &str, reasons: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            for (name, value) in object.iter().filter(|(name, _)| name.as_str() != "pool") {
+                let path = if path.is_empty() { name.clone() } else { format!("{}.{}", path, name) };
+                unparsable_numbers(value, &path, reasons);
+            }
+        }
+        Value::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                unparsable_numbers(value, &format!("{}[{}]", path, index), reasons);
+            }
+        }
+        Value::String(text) if !text.parse::<f64>().is_ok_and(f64::is_finite) => reasons.push(format!("{} {:?} is not a number", path, text)),
+        _ => {}
+    }
+}
+
+// start < end, hour aligned and not overlapping the previous interval of the page
+fn time_reasons(start_time: Option<i64>, end_time: Option<i64>, previous_end_time: Option<i64>, reasons: &mut Vec<String>) {
+    let (Some(start_time), Some(end_time)) = (start_time, end_time) else {
+        return;
+    };
+    if start_time >= end_time {
+        reasons.push(format!("startTime {} is not before endTime {}", start_time, end_time));
+    }
+    for (name, time) in [("startTime", start_time), ("endTime", end_time)] {
+        if time % HOUR != 0 {
+            reasons.push(format!("{} {} is not hour aligned", name, time));
+        }
+    }
+    if let Some(previous_end_time) = previous_end_time.filter(|previous_end_time| start_time < *previous_end_time) {
+        reasons.push(format!("startTime {} is before the endTime {} of the previous interval", start_time, previous_end_time));
+    }
+}
+
+pub fn validate_page<I: MidgardInterval>(collection: &str, pool: Option<&str>, intervals: Vec<I>) -> ValidatedPage<I::Record> {
+    let mut page = ValidatedPage { records: Vec::new(), quarantined: Vec::new() };
+    let mut previous_end_time = None;
+    for interval in intervals {
+        let received = serde_json::to_value(&interval).unwrap_or(Value::Null);
+        let mut reasons = Vec::new();
+        unparsable_numbers(&received, "", &mut reasons);
+        let (start_time, end_time) = (interval.start_time().parse::<i64>().ok(), interval.end_time().parse::<i64>().ok());
+        time_reasons(start_time, end_time, previous_end_time, &mut reasons);
+        for (name, value) in interval.depths() {
+            if value.parse::<f64>().is_ok_and(|value| value < 0.0) {
+                reasons.push(format!("{} {} is negative", name, value));
+            }
+        }
+        previous_end_time = end_time.max(previous_end_time);
+        match interval.into_record(pool) {
+            Ok(record) if reasons.is_empty() => page.records.push(record),
+            Ok(_) => {}
+            // numbers that parse as f64 but not as the stored type, like a fraction for an amount
+            Err(e) if reasons.is_empty() => reasons.push(e.to_string()),
+            Err(_) => {}
+        }
+        if !reasons.is_empty() {
+            page.quarantined.push(QuarantinedInterval {
+                _id: ObjectId::new(),
+                collection: collection.to_string(),
+                pool: pool.map(str::to_string),
+                interval: received,
+                reasons,
+                quarantined_at: Utc::now().timestamp(),
+            });
+        }
+    }
+    page
+}
+
+// writes the rejected intervals of a page before its records are stored
+pub async fn quarantine(db: &DataBase, records: &[QuarantinedInterval]) -> Result<(), CustomError> {
+    for record in records {
+        warn!(collection = record.collection, pool = record.pool, reasons = ?record.reasons, "Quarantining midgard interval");
+        METRICS.record_quarantine(&record.collection);
+    }
+    db.store.insert_quarantined(records).await?;
+    Ok(())
+}
+
+fn received<I: DeserializeOwned>(record: &QuarantinedInterval) -> Result<Vec<I>, CustomError> {
+    let interval = serde_json::from_value(record.interval.clone())
+        .map_err(|e| CustomError::InvalidInput(format!("Quarantined interval is not a {} interval {}", record.collection, e)))?;
+    Ok(vec![interval])
+}
+
+fn pool(record: &QuarantinedInterval) -> Result<&str, CustomError> {
+    record.pool.as_deref().ok_or_else(|| CustomError::InvalidInput(format!("Quarantined {} interval has no pool", record.collection)))
+}
+
+// stores a quarantined interval through the ingestion again, `interval` replaces the one received from midgard,
+// the entry is removed either way and an interval that still fails is quarantined anew, None for an unknown id
+pub async fn replay_quarantined(db: &DataBase, id: &str, interval: Option<Value>) -> Result<Option<ReplayReport>, CustomError> {
+    let Some(mut record) = db.store.quarantined_interval(id).await? else {
+        return Ok(None);
+    };
+    if let Some(interval) = interval {
+        record.interval = interval;
+    }
+    let quarantined = match record.collection.as_str() {
+        "depths" => PoolDepthPriceHistory::store_price_history(db, pool(&record)?, received::<depth_history_service::Interval>(&record)?).await?,
+        "swaps" => SwapHistory::store_swap_history(db, pool(&record)?, received::<swap_history_service::Interval>(&record)?).await?,
+        "earnings" => PoolEarningHistory::store_earning_history(db, received::<earnings_history_service::Interval>(&record)?).await?,
+        "runepool" => RunePool::store_rune_pool(db, received::<rune_pool_service::Interval>(&record)?).await?,
+        collection => return Err(CustomError::DatabaseError(format!("Quarantined interval of unknown collection {}", collection))),
+    };
+    db.store.remove_quarantined(id).await?;
+    Ok(Some(ReplayReport {
+        id: id.to_string(),
+        stored: quarantined.is_empty(),
+        quarantined: quarantined.into_iter().next().map(QuarantineEntry::from),
+    }))
+}
@@ -0,0 +1,6 @@
+pub mod metrics_store;
+pub mod mongo_store;
+pub mod memory_store;
+pub mod postgres_store;
+pub mod sqlite_store;
+pub mod sql_history;
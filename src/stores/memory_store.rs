@@ -0,0 +1,323 @@
+use std::{cmp::Ordering, collections::HashMap, sync::RwLock};
+
+use async_trait::async_trait;
+use mongodb::bson::{self, doc, oid::ObjectId, Bson, Document};
+use serde::Serialize;
+
+use crate::models::{
+    custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory,
+    earning_history_model::{EarningsInterval, EarningsMismatch}, quarantine_model::QuarantinedInterval, rune_pool_model::RunePool,
+    swap_history_model::SwapHistory,
+};
+
+use super::metrics_store::{
+    HistoryCollection, HistoryQuery, MetricsStore, RecordQuery, DEPTH_FIELDS, EARNING_FIELDS, EARNING_SUMMARY_FIELDS, RUNE_POOL_FIELDS, SWAP_FIELDS,
+};
+
+// keeps records as the documents mongodb would store and answers the history queries like the
+// aggregation pipelines, for tests and runs without a database
+#[derive(Default)]
+pub struct MemoryStore{
+    depth_history : RwLock<Vec<Document>>,
+    earnings : RwLock<Vec<Document>>,
+    earnings_summary : RwLock<Vec<Document>>,
+    swap_history : RwLock<Vec<Document>>,
+    rune_pool_history : RwLock<Vec<Document>>,
+    quarantine : RwLock<Vec<QuarantinedInterval>>
+}
+
+fn documents<T: Serialize>(records: &[T]) -> Result<Vec<Document>, CustomError> {
+    records
+        .iter()
+        .map(bson::to_document)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| CustomError::DatabaseError(format!("Failed serializing record {}", e)))
+}
+
+fn push_all<T: Serialize>(collection: &RwLock<Vec<Document>>, records: &[T]) -> Result<u64, CustomError> {
+    let documents = documents(records)?;
+    let inserted = documents.len() as u64;
+    collection.write().unwrap().extend(documents);
+    Ok(inserted)
+}
+
+fn matches(record: &Document, query: &HistoryQuery) -> bool {
+    if let Some(pool) = &query.pool {
+        if record.get_str("pool").ok() != Some(pool.as_str()) {
+            return false;
+        }
+    }
+    let Ok(start_time) = record.get_i64("start_time") else {
+        return false;
+    };
+    if start_time < query.from {
+        return false;
+    }
+    match (query.to, record.get_i64("end_time")) {
+        (Some(to), Ok(end_time)) => end_time <= to,
+        (Some(_), Err(_)) => false,
+        (None, _) => true,
+    }
+}
+
+fn matches_records(record: &Document, query: &RecordQuery) -> bool {
+    if let Some(pool) = &query.pool {
+        if record.get_str("pool").ok() != Some(pool.as_str()) {
+            return false;
+        }
+    }
+    let (Ok(start_time), Ok(end_time)) = (record.get_i64("start_time"), record.get_i64("end_time")) else {
+        return false;
+    };
+    query.after.is_none_or(|after| end_time > after)
+        && query.from.is_none_or(|from| start_time >= from)
+        && query.to.is_none_or(|to| end_time <= to)
+}
+
+fn number(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Int32(value) => Some(*value as f64),
+        Bson::Int64(value) => Some(*value as f64),
+        Bson::Double(value) => Some(*value),
+        _ => None,
+    }
+}
+
+// amounts written before they were stored as integers are doubles
+fn integer(value: &Bson) -> Option<i64> {
+    match value {
+        Bson::Int32(value) => Some(*value as i64),
+        Bson::Int64(value) => Some(*value),
+        Bson::Double(value) => Some(value.round() as i64),
+        _ => None,
+    }
+}
+
+// bson ordering for the types the history documents hold, missing fields sort first like null
+fn compare_bson(a: Option<&Bson>, b: Option<&Bson>) -> Ordering {
+    fn rank(value: Option<&Bson>) -> u8 {
+        match value {
+            None | Some(Bson::Null) => 0,
+            Some(value) if number(value).is_some() => 1,
+            Some(Bson::String(_)) => 2,
+            Some(_) => 3,
+        }
+    }
+    match (a, b) {
+        (Some(Bson::String(a)), Some(Bson::String(b))) => a.cmp(b),
+        (Some(a), Some(b)) => match (number(a), number(b)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => rank(Some(a)).cmp(&rank(Some(b))),
+        },
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn copy_fields(target: &mut Document, record: &Document, fields: &[(&str, &str)]) {
+    for (name, field) in fields {
+        target.insert(*name, record.get(*field).cloned().unwrap_or(Bson::Null));
+    }
+}
+
+impl MemoryStore {
+    fn collection(&self, collection: HistoryCollection) -> &RwLock<Vec<Document>> {
+        match collection {
+            HistoryCollection::Depths => &self.depth_history,
+            HistoryCollection::Swaps => &self.swap_history,
+            HistoryCollection::Earnings => &self.earnings,
+            HistoryCollection::RunePool => &self.rune_pool_history,
+        }
+    }
+
+    // same bucketing as the $group stage: a record belongs to the interval its end_time closes
+    fn bucket_last_records(&self, collection: HistoryCollection, query: &HistoryQuery) -> Vec<(i64, Document)> {
+        let seconds_per_interval = query.seconds_per_interval as i64;
+        let mut positions: HashMap<(i64, Option<String>), usize> = HashMap::new();
+        let mut buckets: Vec<(i64, Document)> = Vec::new();
+        for record in self.collection(collection).read().unwrap().iter().filter(|record| matches(record, query)) {
+            let Ok(end_time) = record.get_i64("end_time") else {
+                continue;
+            };
+            let interval_start = (end_time + 1) - (end_time - 1) % seconds_per_interval;
+            // earnings are bucketed per pool as well
+            let pool = match collection {
+                HistoryCollection::Earnings => record.get_str("pool").ok().map(str::to_string),
+                _ => None,
+            };
+            match positions.get(&(interval_start, pool.clone())) {
+                // $last keeps the latest inserted record of the bucket
+                Some(&position) => buckets[position].1 = record.clone(),
+                None => {
+                    positions.insert((interval_start, pool), buckets.len());
+                    buckets.push((interval_start, record.clone()));
+                }
+            }
+        }
+        buckets
+    }
+
+    fn earnings_interval(&self, interval_start: i64, record: &Document) -> Document {
+        let mut interval = doc! { "interval_start": interval_start };
+        if let Some(pool) = record.get("pool") {
+            interval.insert("pool", pool.clone());
+        }
+        for (name, field) in EARNING_FIELDS {
+            if let Some(value) = record.get(*field) {
+                interval.insert(*name, value.clone());
+            }
+        }
+        // the $lookup, a missing summary leaves an empty document after the $project
+        let mut summary = Document::new();
+        if let Some(id) = record.get("earnings_summary") {
+            let summaries = self.earnings_summary.read().unwrap();
+            if let Some(stored) = summaries.iter().find(|stored| stored.get("_id") == Some(id)) {
+                for (name, field) in EARNING_SUMMARY_FIELDS {
+                    if let Some(value) = stored.get(*field) {
+                        summary.insert(*name, value.clone());
+                    }
+                }
+            }
+        }
+        interval.insert("earnings_summary", summary);
+        interval
+    }
+}
+
+#[async_trait]
+impl MetricsStore for MemoryStore {
+    async fn insert_depths(&self, records: &[PoolDepthPriceHistory]) -> Result<u64, CustomError> {
+        push_all(&self.depth_history, records)
+    }
+
+    async fn insert_swaps(&self, records: &[SwapHistory]) -> Result<u64, CustomError> {
+        push_all(&self.swap_history, records)
+    }
+
+    async fn insert_earnings_intervals(&self, intervals: &[EarningsInterval]) -> Result<u64, CustomError> {
+        // everything is serialized before either collection changes
+        let summaries = documents(&intervals.iter().map(|(summary, _)| summary).collect::<Vec<_>>())?;
+        let pools = documents(&intervals.iter().flat_map(|(_, pools)| pools).collect::<Vec<_>>())?;
+        let inserted = pools.len() as u64;
+        let times: Vec<(i64, i64)> = intervals.iter().map(|(summary, _)| (summary.start_time, summary.end_time)).collect();
+        let mut stored_summaries = self.earnings_summary.write().unwrap();
+        let mut stored_pools = self.earnings.write().unwrap();
+        let (replaced, kept): (Vec<Document>, Vec<Document>) = stored_summaries.drain(..).partition(|summary| {
+            let (Ok(start_time), Ok(end_time)) = (summary.get_i64("start_time"), summary.get_i64("end_time")) else {
+                return false;
+            };
+            times.contains(&(start_time, end_time))
+        });
+        let replaced: Vec<ObjectId> = replaced.iter().filter_map(|summary| summary.get_object_id("_id").ok()).collect();
+        stored_pools.retain(|pool| pool.get_object_id("earnings_summary").is_ok_and(|summary| !replaced.contains(&summary)));
+        stored_pools.extend(pools);
+        *stored_summaries = kept;
+        stored_summaries.extend(summaries);
+        Ok(inserted)
+    }
+
+    async fn earnings_mismatches(&self) -> Result<Vec<EarningsMismatch>, CustomError> {
+        let earnings = self.earnings.read().unwrap();
+        let mut mismatches = Vec::new();
+        for summary in self.earnings_summary.read().unwrap().iter() {
+            let id = summary.get_object_id("_id").map_err(|e| CustomError::DatabaseError(format!("Invalid earnings summary {}", e)))?;
+            let pools: Vec<&Document> = earnings.iter().filter(|pool| pool.get_object_id("earnings_summary").ok() == Some(id)).collect();
+            let pool_earnings = pools.iter().filter_map(|pool| pool.get("earning").and_then(integer)).sum();
+            let liquidity_earnings = summary.get("liquidity_earnings").and_then(integer).unwrap_or_default();
+            if pool_earnings != liquidity_earnings {
+                mismatches.push(EarningsMismatch {
+                    summary: id.to_hex(),
+                    start_time: summary.get_i64("start_time").unwrap_or_default(),
+                    end_time: summary.get_i64("end_time").unwrap_or_default(),
+                    liquidity_earnings,
+                    pools: pools.len() as i64,
+                    pool_earnings,
+                });
+            }
+        }
+        mismatches.sort_by_key(|mismatch| (mismatch.start_time, mismatch.summary.clone()));
+        Ok(mismatches)
+    }
+
+    async fn insert_rune_pool(&self, records: &[RunePool]) -> Result<u64, CustomError> {
+        push_all(&self.rune_pool_history, records)
+    }
+
+    async fn insert_quarantined(&self, records: &[QuarantinedInterval]) -> Result<u64, CustomError> {
+        self.quarantine.write().unwrap().extend_from_slice(records);
+        Ok(records.len() as u64)
+    }
+
+    async fn quarantined(&self, collection: Option<&str>, limit: i64) -> Result<Vec<QuarantinedInterval>, CustomError> {
+        Ok(self
+            .quarantine
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|record| collection.is_none_or(|collection| record.collection == collection))
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn quarantined_interval(&self, id: &str) -> Result<Option<QuarantinedInterval>, CustomError> {
+        Ok(self.quarantine.read().unwrap().iter().find(|record| record._id.to_hex() == id).cloned())
+    }
+
+    async fn remove_quarantined(&self, id: &str) -> Result<bool, CustomError> {
+        let mut quarantine = self.quarantine.write().unwrap();
+        let before = quarantine.len();
+        quarantine.retain(|record| record._id.to_hex() != id);
+        Ok(quarantine.len() < before)
+    }
+
+    async fn latest_end_time(&self, collection: HistoryCollection) -> Result<Option<i64>, CustomError> {
+        Ok(self.collection(collection).read().unwrap().iter().filter_map(|record| record.get_i64("end_time").ok()).max())
+    }
+
+    async fn records(&self, collection: HistoryCollection, query: &RecordQuery) -> Result<Vec<Document>, CustomError> {
+        let mut records: Vec<Document> = self.collection(collection).read().unwrap().iter().filter(|record| matches_records(record, query)).cloned().collect();
+        // stable, so records with the same end_time and pool stay in insertion order
+        records.sort_by(|a, b| {
+            let key = |record: &Document| (record.get_i64("end_time").unwrap_or_default(), record.get_str("pool").ok().map(str::to_string));
+            key(a).cmp(&key(b))
+        });
+        if query.descending {
+            records.reverse();
+        }
+        records.truncate(query.limit.max(0) as usize);
+        Ok(records)
+    }
+
+    async fn history_intervals(&self, collection: HistoryCollection, query: &HistoryQuery) -> Result<Vec<Document>, CustomError> {
+        let seconds_per_interval = query.seconds_per_interval as i64;
+        let mut intervals: Vec<Document> = self
+            .bucket_last_records(collection, query)
+            .into_iter()
+            .map(|(interval_start, record)| {
+                let fields = match collection {
+                    HistoryCollection::Earnings => return self.earnings_interval(interval_start, &record),
+                    HistoryCollection::Depths => DEPTH_FIELDS,
+                    HistoryCollection::Swaps => SWAP_FIELDS,
+                    HistoryCollection::RunePool => RUNE_POOL_FIELDS,
+                };
+                let start_time = interval_start - interval_start % seconds_per_interval;
+                let mut interval = doc! { "startTime": start_time, "endTime": start_time + seconds_per_interval };
+                copy_fields(&mut interval, &record, fields);
+                interval
+            })
+            .collect();
+
+        intervals.sort_by(|a, b| {
+            query
+                .sort
+                .iter()
+                .map(|(field, order)| {
+                    let ordering = compare_bson(a.get(field), b.get(field));
+                    if *order < 0 { ordering.reverse() } else { ordering }
+                })
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+        Ok(intervals.into_iter().skip(query.skip.max(0) as usize).take(query.limit.max(0) as usize).collect())
+    }
+}
@@ -0,0 +1,373 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use mongodb::bson::{self, doc, Document};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    controllers::{
+        depth_history_api_controller::depth_history_response, earnings_history_api_controller::earnings_history_response,
+        rune_pool_history_api_controller::rune_pool_history_response, swaps_history_api_controller::swaps_history_response,
+    },
+    models::{
+        api_request_param_model::QueryParams, custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory,
+        earning_history_model::{EarningsInterval, EarningsMismatch}, quarantine_model::QuarantinedInterval, rune_pool_model::RunePool,
+        swap_history_model::SwapHistory,
+    },
+    utils::db_helper_utils::get_seconds_per_interval,
+};
+
+const DEFAULT_COUNT: u32 = 400;
+// earnings responses carry every pool of an interval, so fewer buckets are returned by default
+const DEFAULT_EARNINGS_COUNT: u32 = 27;
+// records read at once by for_each_record_page
+const RECORD_PAGE: i64 = 10_000;
+
+// (response field, stored field) of the $last accumulators in the mongodb pipelines
+pub(crate) const DEPTH_FIELDS: &[(&str, &str)] = &[
+    ("assetDepth", "asset_depth"),
+    ("assetPrice", "asset_price"),
+    ("assetPriceUSD", "asset_price_usd"),
+    ("liquidityUnits", "liquidity_units"),
+    ("luvi", "luvi"),
+    ("membersCount", "members_count"),
+    ("runeDepth", "rune_depth"),
+    ("synthSupply", "synth_supply"),
+    ("synthUnits", "synth_units"),
+    ("units", "units"),
+    ("pool", "pool"),
+];
+
+pub(crate) const SWAP_FIELDS: &[(&str, &str)] = &[
+    ("averageSlip", "average_slip"),
+    ("fromTradeAverageSlip", "from_trade_average_slip"),
+    ("fromTradeCount", "from_trade_count"),
+    ("fromTradeFees", "from_trade_fees"),
+    ("fromTradeVolume", "from_trade_volume"),
+    ("fromTradeVolumeUSD", "from_trade_volume_usd"),
+    ("runePriceUSD", "rune_price_usd"),
+    ("synthMintAverageSlip", "synth_mint_average_slip"),
+    ("synthMintCount", "synth_mint_count"),
+    ("synthMintFees", "synth_mint_fees"),
+    ("synthMintVolume", "synth_mint_volume"),
+    ("synthMintVolumeUSD", "synth_mint_volume_usd"),
+    ("synthRedeemAverageSlip", "synth_redeem_average_slip"),
+    ("synthRedeemCount", "synth_redeem_count"),
+    ("synthRedeemFees", "synth_redeem_fees"),
+    ("synthRedeemVolume", "synth_redeem_volume"),
+    ("synthRedeemVolumeUSD", "synth_redeem_volume_usd"),
+    ("toAssetAverageSlip", "to_asset_average_slip"),
+    ("toAssetCount", "to_asset_count"),
+    ("toAssetFees", "to_asset_fees"),
+    ("toAssetVolume", "to_asset_volume"),
+    ("toAssetVolumeUSD", "to_asset_volume_usd"),
+    ("toRuneAverageSlip", "to_rune_average_slip"),
+    ("toRuneCount", "to_rune_count"),
+    ("toRuneFees", "to_rune_fees"),
+    ("toRuneVolume", "to_rune_volume"),
+    ("toRuneVolumeUSD", "to_rune_volume_usd"),
+    ("toTradeAverageSlip", "to_trade_average_slip"),
+    ("toTradeCount", "to_trade_count"),
+    ("toTradeFees", "to_trade_fees"),
+    ("toTradeVolume", "to_trade_volume"),
+    ("toTradeVolumeUSD", "to_trade_volume_usd"),
+    ("totalCount", "total_count"),
+    ("totalFees", "total_fees"),
+    ("totalVolume", "total_volume"),
+    ("totalVolumeUSD", "total_volume_usd"),
+];
+
+pub(crate) const RUNE_POOL_FIELDS: &[(&str, &str)] = &[("count", "count"), ("units", "units")];
+
+// only the accumulators the earnings $project keeps
+pub(crate) const EARNING_FIELDS: &[(&str, &str)] = &[
+    ("assetLiquidityFees", "asset_liquidity_fees"),
+    ("earning", "earning"),
+    ("rewards", "rewards"),
+    ("runeLiquidityFees", "rune_liquidity_fees"),
+    ("saverEarning", "saver_earning"),
+    ("totalLiquidityFeesRune", "total_liquidity_fees_rune"),
+];
+
+pub(crate) const EARNING_SUMMARY_FIELDS: &[(&str, &str)] = &[
+    ("avgNodeCount", "avg_node_count"),
+    ("blockRewards", "block_rewards"),
+    ("bondingEarnings", "bonding_earnings"),
+    ("earnings", "earnings"),
+    ("endTime", "end_time"),
+    ("liquidityEarnings", "liquidity_earnings"),
+    ("liquidityFees", "liquidity_fees"),
+    ("startTime", "start_time"),
+    ("runePriceUSD", "rune_price_usd"),
+];
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum HistoryCollection {
+    Depths,
+    Swaps,
+    Earnings,
+    RunePool,
+}
+
+impl HistoryCollection {
+    pub const ALL: [HistoryCollection; 4] = [
+        HistoryCollection::Depths,
+        HistoryCollection::Swaps,
+        HistoryCollection::Earnings,
+        HistoryCollection::RunePool,
+    ];
+
+    // same names as INGESTED_COLLECTIONS and the /stream topics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryCollection::Depths => "depths",
+            HistoryCollection::Swaps => "swaps",
+            HistoryCollection::Earnings => "earnings",
+            HistoryCollection::RunePool => "runepool",
+        }
+    }
+}
+
+// backend independent form of the history query params
+#[derive(Debug,Clone,PartialEq)]
+pub struct HistoryQuery{
+    pub pool : Option<String>,
+    pub seconds_per_interval : i32,
+    // records with start_time >= from
+    pub from : i64,
+    // records with end_time <= to
+    pub to : Option<i64>,
+    // (response field, 1 or -1), applied to the bucketed intervals
+    pub sort : Vec<(String, i32)>,
+    pub skip : i64,
+    pub limit : i64
+}
+
+impl HistoryQuery {
+    // `latest_end_time` anchors the range when neither from nor to is given
+    pub fn new(collection: HistoryCollection, params: QueryParams, latest_end_time: Option<i64>) -> Result<Self, CustomError> {
+        let QueryParams { pool, interval, count, to, from, page, sort_by, sort_order, limit } = params;
+        if collection == HistoryCollection::RunePool && pool.is_some() {
+            return Err(CustomError::InvalidInput("Invalid parameter pool!".to_string()));
+        }
+        let seconds_per_interval = get_seconds_per_interval(interval.as_deref().unwrap_or("hour"));
+
+        // as per midgard api if from is not specified the from has to be fixed back relative to either current timestamp or "to" timestamp (if given) or w.r.t the latest record in the collection
+        let from = match from {
+            Some(from) => from as i64,
+            None => {
+                let calc_start = match to {
+                    Some(to) => to as i64,
+                    None => latest_end_time.unwrap_or(Utc::now().timestamp()),
+                };
+                calc_start - count.unwrap_or(DEFAULT_COUNT) as i64 * seconds_per_interval as i64
+            }
+        };
+
+        // stored records use snake_case times, the bucketed intervals do not
+        let time_field = match collection {
+            HistoryCollection::Earnings => ("interval_start", "interval_start"),
+            _ => ("startTime", "endTime"),
+        };
+        let mut sort = match sort_by {
+            Some(sort_by) => {
+                let field = match sort_by.as_str() {
+                    "start_time" | "startTime" => time_field.0.to_string(),
+                    "end_time" | "endTime" => time_field.1.to_string(),
+                    _ => sort_by,
+                };
+                vec![(field, if sort_order.unwrap_or(1) >= 1 { 1 } else { -1 })]
+            }
+            None => vec![(time_field.1.to_string(), -1)],
+        };
+        // several pools share an earnings bucket
+        if collection == HistoryCollection::Earnings {
+            sort.push(("pool".to_string(), 1));
+        }
+
+        let default_count = match collection {
+            HistoryCollection::Earnings => DEFAULT_EARNINGS_COUNT,
+            _ => DEFAULT_COUNT,
+        };
+        let page_size = limit.map(|limit| limit as i64).unwrap_or(count.unwrap_or(DEFAULT_COUNT) as i64);
+        Ok(HistoryQuery {
+            pool,
+            seconds_per_interval,
+            from,
+            to: to.map(|to| to as i64),
+            sort,
+            skip: (page.unwrap_or(1) as i64 - 1) * page_size,
+            limit: count.unwrap_or(default_count) as i64,
+        })
+    }
+
+    pub fn filter(&self) -> Document {
+        let mut filter = doc! {};
+        if let Some(pool) = &self.pool {
+            filter.insert("pool", pool);
+        }
+        filter.insert("start_time", doc! { "$gte": self.from });
+        if let Some(to) = self.to {
+            filter.insert("end_time", doc! { "$lte": to });
+        }
+        filter
+    }
+
+    pub fn sort_filter(&self) -> Document {
+        self.sort.iter().map(|(field, order)| (field.clone(), (*order).into())).collect()
+    }
+}
+
+// a range of stored records of one collection, for the routes that compute over the records themselves
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct RecordQuery{
+    pub pool : Option<String>,
+    // records with end_time > after
+    pub after : Option<i64>,
+    // records with start_time >= from
+    pub from : Option<i64>,
+    // records with end_time <= to
+    pub to : Option<i64>,
+    // latest end_time first, otherwise oldest first, records with the same end_time by pool and then in the order they
+    // were stored, every backend reverses all three keys when descending
+    pub descending : bool,
+    pub limit : i64
+}
+
+impl RecordQuery {
+    pub fn filter(&self) -> Document {
+        let mut filter = doc! {};
+        if let Some(pool) = &self.pool {
+            filter.insert("pool", pool);
+        }
+        if let Some(from) = self.from {
+            filter.insert("start_time", doc! { "$gte": from });
+        }
+        let mut end_time = doc! {};
+        if let Some(after) = self.after {
+            end_time.insert("$gt", after);
+        }
+        if let Some(to) = self.to {
+            end_time.insert("$lte", to);
+        }
+        if !end_time.is_empty() {
+            filter.insert("end_time", end_time);
+        }
+        filter
+    }
+}
+
+// documents of MetricsStore::records as their model
+pub fn records_as<T: DeserializeOwned>(documents: Vec<Document>) -> Result<Vec<T>, CustomError> {
+    documents
+        .into_iter()
+        .map(bson::from_document)
+        .collect::<Result<Vec<T>, _>>()
+        .map_err(|e| CustomError::DatabaseError(format!("Invalid stored record {}", e)))
+}
+
+// records of MetricsStore::records a page at a time, oldest first, a page ends with a whole end_time so the next
+// one starts after it
+pub async fn for_each_record_page(
+    store: &dyn MetricsStore,
+    collection: HistoryCollection,
+    query: RecordQuery,
+    mut visit: impl FnMut(Vec<Document>),
+) -> Result<(), CustomError> {
+    let end_time = |record: &Document| record.get_i64("end_time").unwrap_or(i64::MIN);
+    let mut query = RecordQuery { descending: false, limit: RECORD_PAGE, ..query };
+    loop {
+        let mut page = store.records(collection, &query).await?;
+        let full = page.len() as i64 == query.limit;
+        if full {
+            let last = page.last().map_or(i64::MIN, end_time);
+            let whole = page.partition_point(|record| end_time(record) < last);
+            // one end_time fills the page, it is fetched again with room for all of its records
+            if whole == 0 {
+                query.limit = query.limit.saturating_mul(2);
+                continue;
+            }
+            page.truncate(whole);
+        }
+        let resume = page.last().map(end_time);
+        visit(page);
+        match resume {
+            Some(after) if full => query.after = Some(after),
+            _ => return Ok(()),
+        }
+    }
+}
+
+// storage of the ingested history, the history endpoints only talk to this trait
+#[async_trait]
+pub trait MetricsStore: Send + Sync {
+    async fn insert_depths(&self, records: &[PoolDepthPriceHistory]) -> Result<u64, CustomError>;
+    async fn insert_swaps(&self, records: &[SwapHistory]) -> Result<u64, CustomError>;
+    // the summaries of earnings intervals with their pools in one batch, none of them is kept when a write fails,
+    // a summary with the same start_time and end_time is replaced with its pools, returns the pools inserted
+    async fn insert_earnings_intervals(&self, intervals: &[EarningsInterval]) -> Result<u64, CustomError>;
+    // summaries whose stored pools do not add up to their liquidity earnings, oldest first
+    async fn earnings_mismatches(&self) -> Result<Vec<EarningsMismatch>, CustomError>;
+    async fn insert_rune_pool(&self, records: &[RunePool]) -> Result<u64, CustomError>;
+
+    // intervals the ingestion rejected, see validation_service
+    async fn insert_quarantined(&self, records: &[QuarantinedInterval]) -> Result<u64, CustomError>;
+    // oldest first, of every collection when None
+    async fn quarantined(&self, collection: Option<&str>, limit: i64) -> Result<Vec<QuarantinedInterval>, CustomError>;
+    // None for an unknown or malformed id
+    async fn quarantined_interval(&self, id: &str) -> Result<Option<QuarantinedInterval>, CustomError>;
+    // false when nothing had the id
+    async fn remove_quarantined(&self, id: &str) -> Result<bool, CustomError>;
+
+    // None when the collection is empty
+    async fn latest_end_time(&self, collection: HistoryCollection) -> Result<Option<i64>, CustomError>;
+
+    // stored records as mongodb stores them, with their ObjectId _id
+    async fn records(&self, collection: HistoryCollection, query: &RecordQuery) -> Result<Vec<Document>, CustomError>;
+
+    // indexes backing the history queries, the sql stores create theirs in the migrations
+    async fn reindex(&self) -> Result<Vec<String>, CustomError> {
+        Ok(Vec::new())
+    }
+
+    // true for the mongodb store, graphql still aggregates the mongodb collections itself
+    fn is_mongodb(&self) -> bool {
+        false
+    }
+
+    // the last record of every interval bucket as response documents, sorted and paged
+    async fn history_intervals(&self, collection: HistoryCollection, query: &HistoryQuery) -> Result<Vec<Document>, CustomError>;
+
+    async fn history_query(&self, collection: HistoryCollection, params: QueryParams) -> Result<HistoryQuery, CustomError> {
+        // rune pool ranges are anchored to now like midgard
+        let latest_end_time = if params.from.is_none() && params.to.is_none() && collection != HistoryCollection::RunePool {
+            self.latest_end_time(collection).await.ok().flatten()
+        } else {
+            None
+        };
+        HistoryQuery::new(collection, params, latest_end_time)
+    }
+
+    // /depths
+    async fn get_depth_price_history_api(&self, params: QueryParams) -> Result<Document, CustomError> {
+        let query = self.history_query(HistoryCollection::Depths, params).await?;
+        depth_history_response(self.history_intervals(HistoryCollection::Depths, &query).await?)
+    }
+
+    // /swaps
+    async fn get_swaps_history_api(&self, params: QueryParams) -> Result<Document, CustomError> {
+        let query = self.history_query(HistoryCollection::Swaps, params).await?;
+        swaps_history_response(self.history_intervals(HistoryCollection::Swaps, &query).await?)
+    }
+
+    // /earnings
+    async fn get_pool_earnings_history_api(&self, params: QueryParams) -> Result<Document, CustomError> {
+        let query = self.history_query(HistoryCollection::Earnings, params).await?;
+        earnings_history_response(self.history_intervals(HistoryCollection::Earnings, &query).await?)
+    }
+
+    // /runepool
+    async fn get_rune_pool_history_api(&self, params: QueryParams) -> Result<Document, CustomError> {
+        let query = self.history_query(HistoryCollection::RunePool, params).await?;
+        rune_pool_history_response(self.history_intervals(HistoryCollection::RunePool, &query).await?)
+    }
+}
@@ -0,0 +1,238 @@
+use std::borrow::Borrow;
+
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId, Document}, options::IndexOptions, Collection, Database, IndexModel};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::warn;
+
+use crate::models::{
+    custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory,
+    earning_history_model::{EarningsInterval, EarningsMismatch, PoolEarningHistory, PoolEarningSummary}, quarantine_model::QuarantinedInterval, rune_pool_model::RunePool,
+    swap_history_model::SwapHistory,
+};
+
+use super::metrics_store::{HistoryCollection, HistoryQuery, MetricsStore, RecordQuery};
+
+// the history collections of the mongodb database, the default store
+#[derive(Clone)]
+pub struct MongoStore{
+    pub depth_history : Collection<PoolDepthPriceHistory>,
+    pub earnings : Collection<PoolEarningHistory>,
+    pub earnings_summary : Collection<PoolEarningSummary>,
+    pub swap_history : Collection<SwapHistory>,
+    pub rune_pool_history : Collection<RunePool>,
+    pub quarantine : Collection<QuarantinedInterval>
+}
+
+impl MongoStore {
+    pub fn new(db: &Database) -> Self {
+        MongoStore {
+            depth_history: db.collection("depth_history"),
+            earnings: db.collection("earnings"),
+            earnings_summary: db.collection("earnings_summary"),
+            swap_history: db.collection("swap_history"),
+            rune_pool_history: db.collection("rune_pool_history"),
+            quarantine: db.collection("quarantine"),
+        }
+    }
+}
+
+// one insert_many, of records or of references to them
+async fn insert_all<T: Serialize + Send + Sync, R: Borrow<T> + Send + Sync>(collection: &Collection<T>, records: &[R]) -> Result<u64, CustomError> {
+    // insert_many rejects an empty batch
+    if records.is_empty() {
+        return Ok(0);
+    }
+    let result = collection.insert_many(records.iter().map(R::borrow)).await?;
+    Ok(result.inserted_ids.len() as u64)
+}
+
+async fn max_end_time<T: DeserializeOwned + Send + Sync>(collection: &Collection<T>) -> Result<Option<i64>, CustomError> {
+    let latest = collection
+        .clone_with_type::<Document>()
+        .find_one(doc! {})
+        .sort(doc! { "end_time": -1 })
+        .projection(doc! { "_id": 0, "end_time": 1 })
+        .await?;
+    Ok(latest.and_then(|record| record.get_i64("end_time").ok()))
+}
+
+async fn find_records<T: Send + Sync>(collection: &Collection<T>, query: &RecordQuery) -> Result<Vec<Document>, CustomError> {
+    let order = if query.descending { -1 } else { 1 };
+    Ok(collection
+        .clone_with_type::<Document>()
+        .find(query.filter())
+        .sort(doc! { "end_time": order, "pool": order, "_id": order })
+        .limit(query.limit.max(0))
+        .await?
+        .try_collect()
+        .await?)
+}
+
+// creating an existing index is a no-op, returns collection.index names
+pub async fn create_indexes<T: Send + Sync>(collection: &Collection<T>, indexes: Vec<(Document, bool)>) -> Result<Vec<String>, CustomError> {
+    let mut created = Vec::new();
+    for (keys, unique) in indexes {
+        let index = IndexModel::builder()
+            .keys(keys)
+            .options(IndexOptions::builder().unique(unique).build())
+            .build();
+        let result = collection.create_index(index).await?;
+        created.push(format!("{}.{}", collection.name(), result.index_name));
+    }
+    Ok(created)
+}
+
+#[async_trait]
+impl MetricsStore for MongoStore {
+    async fn insert_depths(&self, records: &[PoolDepthPriceHistory]) -> Result<u64, CustomError> {
+        insert_all(&self.depth_history, records).await
+    }
+
+    async fn insert_swaps(&self, records: &[SwapHistory]) -> Result<u64, CustomError> {
+        insert_all(&self.swap_history, records).await
+    }
+
+    async fn insert_earnings_intervals(&self, intervals: &[EarningsInterval]) -> Result<u64, CustomError> {
+        let summaries: Vec<&PoolEarningSummary> = intervals.iter().map(|(summary, _)| summary).collect();
+        let pools: Vec<&PoolEarningHistory> = intervals.iter().flat_map(|(_, pools)| pools).collect();
+        if summaries.is_empty() {
+            return Ok(0);
+        }
+        // the summaries stored before with the same times, removed once the new ones are written
+        let times: Vec<Document> = summaries.iter().map(|summary| doc! { "start_time": summary.start_time, "end_time": summary.end_time }).collect();
+        let replaced: Vec<Document> = self.earnings_summary.clone_with_type::<Document>().find(doc! { "$or": times }).await?.try_collect().await?;
+        let replaced_ids: Vec<ObjectId> = replaced.iter().filter_map(|summary| summary.get_object_id("_id").ok()).collect();
+        let mut replaced_removed = false;
+        let written = async {
+            insert_all(&self.earnings_summary, &summaries).await?;
+            let inserted = insert_all(&self.earnings, &pools).await?;
+            if !replaced_ids.is_empty() {
+                self.earnings_summary.delete_many(doc! { "_id": { "$in": &replaced_ids } }).await?;
+                replaced_removed = true;
+                self.earnings.delete_many(doc! { "earnings_summary": { "$in": &replaced_ids } }).await?;
+            }
+            Ok::<u64, CustomError>(inserted)
+        };
+        let error = match written.await {
+            Ok(inserted) => return Ok(inserted),
+            Err(e) => e,
+        };
+        // transactions need a replica set, so what was written of the batch is removed again and the replaced
+        // summaries are put back, their pools are only deleted after them
+        let ids: Vec<ObjectId> = summaries.iter().map(|summary| summary._id).collect();
+        let rollback = async {
+            self.earnings.delete_many(doc! { "earnings_summary": { "$in": &ids } }).await?;
+            self.earnings_summary.delete_many(doc! { "_id": { "$in": &ids } }).await?;
+            if replaced_removed {
+                self.earnings_summary.clone_with_type::<Document>().insert_many(&replaced).await?;
+            }
+            Ok::<(), CustomError>(())
+        };
+        if let Err(e) = rollback.await {
+            warn!(summaries = ids.len(), error = ?e, "Failed rolling back earnings intervals");
+        }
+        Err(error)
+    }
+
+    async fn earnings_mismatches(&self) -> Result<Vec<EarningsMismatch>, CustomError> {
+        let pipeline = vec![
+            doc! { "$lookup": { "from": self.earnings.name(), "localField": "_id", "foreignField": "earnings_summary", "as": "pools" } },
+            doc! { "$project": {
+                "_id": 0,
+                "summary": { "$toString": "$_id" },
+                "start_time": 1,
+                "end_time": 1,
+                "liquidity_earnings": { "$toLong": "$liquidity_earnings" },
+                "pools": { "$toLong": { "$size": "$pools" } },
+                "pool_earnings": { "$toLong": { "$sum": "$pools.earning" } },
+            } },
+            doc! { "$match": { "$expr": { "$ne": ["$pool_earnings", "$liquidity_earnings"] } } },
+            doc! { "$sort": { "start_time": 1, "summary": 1 } },
+        ];
+        let cursor = self.earnings_summary.aggregate(pipeline).with_type::<EarningsMismatch>().await?;
+        Ok(cursor.try_collect().await?)
+    }
+
+    async fn insert_rune_pool(&self, records: &[RunePool]) -> Result<u64, CustomError> {
+        insert_all(&self.rune_pool_history, records).await
+    }
+
+    async fn insert_quarantined(&self, records: &[QuarantinedInterval]) -> Result<u64, CustomError> {
+        insert_all(&self.quarantine, records).await
+    }
+
+    async fn quarantined(&self, collection: Option<&str>, limit: i64) -> Result<Vec<QuarantinedInterval>, CustomError> {
+        let filter = match collection {
+            Some(collection) => doc! { "collection": collection },
+            None => doc! {},
+        };
+        let cursor = self.quarantine.find(filter).sort(doc! { "quarantined_at": 1, "_id": 1 }).limit(limit.max(0)).await?;
+        Ok(cursor.try_collect().await?)
+    }
+
+    async fn quarantined_interval(&self, id: &str) -> Result<Option<QuarantinedInterval>, CustomError> {
+        let Ok(id) = ObjectId::parse_str(id) else {
+            return Ok(None);
+        };
+        Ok(self.quarantine.find_one(doc! { "_id": id }).await?)
+    }
+
+    async fn remove_quarantined(&self, id: &str) -> Result<bool, CustomError> {
+        let Ok(id) = ObjectId::parse_str(id) else {
+            return Ok(false);
+        };
+        Ok(self.quarantine.delete_one(doc! { "_id": id }).await?.deleted_count > 0)
+    }
+
+    async fn latest_end_time(&self, collection: HistoryCollection) -> Result<Option<i64>, CustomError> {
+        match collection {
+            HistoryCollection::Depths => max_end_time(&self.depth_history).await,
+            HistoryCollection::Swaps => max_end_time(&self.swap_history).await,
+            HistoryCollection::Earnings => max_end_time(&self.earnings).await,
+            HistoryCollection::RunePool => max_end_time(&self.rune_pool_history).await,
+        }
+    }
+
+    async fn records(&self, collection: HistoryCollection, query: &RecordQuery) -> Result<Vec<Document>, CustomError> {
+        // a limit of 0 means no limit to mongodb
+        if query.limit <= 0 {
+            return Ok(Vec::new());
+        }
+        match collection {
+            HistoryCollection::Depths => find_records(&self.depth_history, query).await,
+            HistoryCollection::Swaps => find_records(&self.swap_history, query).await,
+            HistoryCollection::Earnings => find_records(&self.earnings, query).await,
+            HistoryCollection::RunePool => find_records(&self.rune_pool_history, query).await,
+        }
+    }
+
+    async fn reindex(&self) -> Result<Vec<String>, CustomError> {
+        let pool_end_time = || vec![(doc! { "pool": 1, "end_time": 1 }, false), (doc! { "end_time": 1 }, false)];
+        let mut created = Vec::new();
+        created.extend(create_indexes(&self.depth_history, pool_end_time()).await?);
+        created.extend(create_indexes(&self.swap_history, pool_end_time()).await?);
+        let mut earnings = pool_end_time();
+        // the pools of a summary, looked up by the earnings queries and when an interval is replaced
+        earnings.push((doc! { "earnings_summary": 1 }, false));
+        created.extend(create_indexes(&self.earnings, earnings).await?);
+        created.extend(create_indexes(&self.rune_pool_history, vec![(doc! { "end_time": 1 }, false)]).await?);
+        created.extend(create_indexes(&self.earnings_summary, vec![(doc! { "end_time": 1 }, false), (doc! { "start_time": 1, "end_time": 1 }, false)]).await?);
+        Ok(created)
+    }
+
+    fn is_mongodb(&self) -> bool {
+        true
+    }
+
+    // the pipelines live in the controllers
+    async fn history_intervals(&self, collection: HistoryCollection, query: &HistoryQuery) -> Result<Vec<Document>, CustomError> {
+        match collection {
+            HistoryCollection::Depths => self.depth_history_intervals(query).await,
+            HistoryCollection::Swaps => self.swaps_history_intervals(query).await,
+            HistoryCollection::Earnings => self.earnings_history_intervals(query).await,
+            HistoryCollection::RunePool => self.rune_pool_history_intervals(query).await,
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Config as PoolConfig, Pool, Runtime};
+use mongodb::bson::{Bson, Document};
+use serde::Serialize;
+use tokio_postgres::{types::{ToSql, Type}, NoTls, Row};
+use tracing::info;
+
+use crate::models::{
+    custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory,
+    earning_history_model::{EarningsInterval, EarningsMismatch}, quarantine_model::QuarantinedInterval, rune_pool_model::RunePool,
+    swap_history_model::SwapHistory,
+};
+
+use super::{
+    metrics_store::{HistoryCollection, HistoryQuery, MetricsStore, RecordQuery},
+    sql_history::{
+        earnings_mismatch, history_sql, insert_sql, interval_document, quarantine_columns, quarantine_record, quarantined_sql, record_columns, records_sql, replaced_earnings,
+        stored_document, table, Columns, EARNINGS_MISMATCHES_SQL, QUARANTINE_COLUMNS,
+    },
+};
+
+// applied in order, each once, the version is recorded in schema_migrations
+const MIGRATIONS: &[(&str, &str)] = &[
+    ("0001_history_tables", include_str!("../../migrations/postgres/0001_history_tables.sql")),
+    ("0002_integer_amounts", include_str!("../../migrations/postgres/0002_integer_amounts.sql")),
+    ("0003_quarantine", include_str!("../../migrations/postgres/0003_quarantine.sql")),
+    ("0004_earnings_replace", include_str!("../../migrations/postgres/0004_earnings_replace.sql")),
+];
+
+fn pg_error(e: impl std::fmt::Display) -> CustomError {
+    CustomError::DatabaseError(format!("Postgres error {}", e))
+}
+
+fn sql_value(value: &Bson) -> Result<Box<dyn ToSql + Sync + Send>, CustomError> {
+    Ok(match value {
+        Bson::String(value) => Box::new(value.clone()),
+        Bson::Int32(value) => Box::new(*value as i64),
+        Bson::Int64(value) => Box::new(*value),
+        Bson::Double(value) => Box::new(*value),
+        // only nullable text columns take a null
+        Bson::Null => Box::new(None::<String>),
+        value => return Err(CustomError::DatabaseError(format!("Unsupported value for postgres {}", value))),
+    })
+}
+
+fn bson_value(row: &Row, index: usize) -> Result<Bson, CustomError> {
+    let column = &row.columns()[index];
+    let value = match *column.type_() {
+        Type::INT8 => row.try_get::<_, Option<i64>>(index).map(|value| value.map(Bson::Int64)),
+        Type::FLOAT8 => row.try_get::<_, Option<f64>>(index).map(|value| value.map(Bson::Double)),
+        Type::TEXT | Type::VARCHAR => row.try_get::<_, Option<String>>(index).map(|value| value.map(Bson::String)),
+        ref other => return Err(CustomError::DatabaseError(format!("Unsupported column type {} of {}", other, column.name()))),
+    };
+    Ok(value.map_err(pg_error)?.unwrap_or(Bson::Null))
+}
+
+// history tables in postgres, on timescaledb the large ones are hypertables
+pub struct PostgresStore{
+    pool : Pool
+}
+
+impl PostgresStore {
+    // connects with a postgres:// url and applies the pending migrations
+    pub async fn connect(url: &str) -> Result<Self, CustomError> {
+        let mut config = PoolConfig::new();
+        config.url = Some(url.to_string());
+        let pool = config.create_pool(Some(Runtime::Tokio1), NoTls).map_err(pg_error)?;
+        let store = PostgresStore { pool };
+        store.migrate().await?;
+        Ok(store)
+    }
+
+    async fn migrate(&self) -> Result<(), CustomError> {
+        let mut client = self.pool.get().await.map_err(pg_error)?;
+        client
+            .batch_execute("CREATE TABLE IF NOT EXISTS schema_migrations (version TEXT PRIMARY KEY, applied_at TIMESTAMPTZ NOT NULL DEFAULT now())")
+            .await
+            .map_err(pg_error)?;
+        for (version, sql) in MIGRATIONS {
+            let transaction = client.transaction().await.map_err(pg_error)?;
+            // serializes concurrent startups on the same database
+            transaction.batch_execute("LOCK TABLE schema_migrations IN EXCLUSIVE MODE").await.map_err(pg_error)?;
+            let applied = transaction
+                .query_opt("SELECT version FROM schema_migrations WHERE version = $1", &[version])
+                .await
+                .map_err(pg_error)?
+                .is_some();
+            if !applied {
+                transaction.batch_execute(sql).await.map_err(|e| pg_error(format!("in migration {}: {}", version, e)))?;
+                transaction.execute("INSERT INTO schema_migrations (version) VALUES ($1)", &[version]).await.map_err(pg_error)?;
+                info!(version, "Applied postgres migration");
+            }
+            transaction.commit().await.map_err(pg_error)?;
+        }
+        Ok(())
+    }
+
+    // the columns are the serialized field names, _id goes to id
+    async fn insert_all<T: Serialize + Sync>(&self, table: &str, records: &[T]) -> Result<u64, CustomError> {
+        self.insert_rows(table, records.iter().map(record_columns).collect::<Result<Vec<_>, _>>()?).await
+    }
+
+    async fn insert_rows(&self, table: &str, rows: Vec<Vec<(String, Bson)>>) -> Result<u64, CustomError> {
+        self.insert_tables(Vec::new(), vec![(table, rows)]).await
+    }
+
+    // the deletes of the replaced rows and then the rows of several tables in one transaction, the count is of the last table
+    async fn insert_tables(&self, deletes: Vec<(String, Columns)>, tables: Vec<(&str, Vec<Columns>)>) -> Result<u64, CustomError> {
+        let mut client = self.pool.get().await.map_err(pg_error)?;
+        let transaction = client.transaction().await.map_err(pg_error)?;
+        for (sql, params) in &deletes {
+            let values = params.iter().map(|(_, value)| sql_value(value)).collect::<Result<Vec<_>, _>>()?;
+            let params: Vec<&(dyn ToSql + Sync)> = values.iter().map(|value| value.as_ref() as &(dyn ToSql + Sync)).collect();
+            transaction.execute(sql.as_str(), &params).await.map_err(pg_error)?;
+        }
+        let mut inserted = 0;
+        for (table, rows) in &tables {
+            let Some(first) = rows.first() else {
+                inserted = 0;
+                continue;
+            };
+            inserted = rows.len() as u64;
+            let statement = transaction.prepare(&insert_sql(table, first, '$')).await.map_err(pg_error)?;
+            for row in rows {
+                let values = row.iter().map(|(_, value)| sql_value(value)).collect::<Result<Vec<_>, _>>()?;
+                let params: Vec<&(dyn ToSql + Sync)> = values.iter().map(|value| value.as_ref() as &(dyn ToSql + Sync)).collect();
+                transaction.execute(&statement, &params).await.map_err(pg_error)?;
+            }
+        }
+        transaction.commit().await.map_err(pg_error)?;
+        Ok(inserted)
+    }
+
+    async fn query_columns(&self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Vec<(String, Bson)>>, CustomError> {
+        let client = self.pool.get().await.map_err(pg_error)?;
+        let rows = client.query(sql, params).await.map_err(pg_error)?;
+        rows.iter()
+            .map(|row| {
+                row.columns()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, column)| Ok((column.name().to_string(), bson_value(row, index)?)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl MetricsStore for PostgresStore {
+    async fn insert_depths(&self, records: &[PoolDepthPriceHistory]) -> Result<u64, CustomError> {
+        self.insert_all("depth_history", records).await
+    }
+
+    async fn insert_swaps(&self, records: &[SwapHistory]) -> Result<u64, CustomError> {
+        self.insert_all("swap_history", records).await
+    }
+
+    async fn insert_earnings_intervals(&self, intervals: &[EarningsInterval]) -> Result<u64, CustomError> {
+        let summaries = intervals.iter().map(|(summary, _)| record_columns(summary)).collect::<Result<Vec<_>, _>>()?;
+        let pools = intervals.iter().flat_map(|(_, pools)| pools).map(record_columns).collect::<Result<Vec<_>, _>>()?;
+        self.insert_tables(replaced_earnings(intervals, '$'), vec![("earnings_summary", summaries), ("earnings", pools)]).await
+    }
+
+    async fn earnings_mismatches(&self) -> Result<Vec<EarningsMismatch>, CustomError> {
+        self.query_columns(EARNINGS_MISMATCHES_SQL, &[]).await?.into_iter().map(earnings_mismatch).collect()
+    }
+
+    async fn insert_rune_pool(&self, records: &[RunePool]) -> Result<u64, CustomError> {
+        self.insert_all("rune_pool_history", records).await
+    }
+
+    async fn insert_quarantined(&self, records: &[QuarantinedInterval]) -> Result<u64, CustomError> {
+        self.insert_rows("quarantine", records.iter().map(quarantine_columns).collect::<Result<Vec<_>, _>>()?).await
+    }
+
+    async fn quarantined(&self, collection: Option<&str>, limit: i64) -> Result<Vec<QuarantinedInterval>, CustomError> {
+        let limit = limit.max(0);
+        let rows = self.query_columns(&quarantined_sql('$'), &[&collection, &limit]).await?;
+        rows.into_iter().map(quarantine_record).collect()
+    }
+
+    async fn quarantined_interval(&self, id: &str) -> Result<Option<QuarantinedInterval>, CustomError> {
+        let sql = format!("SELECT {} FROM quarantine WHERE id = $1", QUARANTINE_COLUMNS);
+        self.query_columns(&sql, &[&id]).await?.into_iter().next().map(quarantine_record).transpose()
+    }
+
+    async fn remove_quarantined(&self, id: &str) -> Result<bool, CustomError> {
+        let client = self.pool.get().await.map_err(pg_error)?;
+        let removed = client.execute("DELETE FROM quarantine WHERE id = $1", &[&id]).await.map_err(pg_error)?;
+        Ok(removed > 0)
+    }
+
+    async fn latest_end_time(&self, collection: HistoryCollection) -> Result<Option<i64>, CustomError> {
+        let client = self.pool.get().await.map_err(pg_error)?;
+        let row = client
+            .query_one(&format!("SELECT MAX(end_time) FROM {}", table(collection)), &[])
+            .await
+            .map_err(pg_error)?;
+        row.try_get(0).map_err(pg_error)
+    }
+
+    async fn records(&self, collection: HistoryCollection, query: &RecordQuery) -> Result<Vec<Document>, CustomError> {
+        let limit = query.limit.max(0);
+        let rows = self
+            .query_columns(&records_sql(collection, query, '$'), &[&query.pool, &query.after, &query.from, &query.to, &limit])
+            .await?;
+        rows.into_iter().map(stored_document).collect()
+    }
+
+    async fn history_intervals(&self, collection: HistoryCollection, query: &HistoryQuery) -> Result<Vec<Document>, CustomError> {
+        let seconds_per_interval = query.seconds_per_interval as i64;
+        let (limit, skip) = (query.limit.max(0), query.skip.max(0));
+        let rows = self
+            .query_columns(
+                &history_sql(collection, query, '$'),
+                &[&seconds_per_interval, &query.from, &query.to, &query.pool, &limit, &skip],
+            )
+            .await?;
+        Ok(rows.into_iter().map(|columns| interval_document(collection, columns)).collect())
+    }
+}
@@ -0,0 +1,257 @@
+use mongodb::bson::{self, oid::ObjectId, Bson, Document};
+use serde::Serialize;
+
+use crate::models::{custom_error_model::CustomError, earning_history_model::{EarningsInterval, EarningsMismatch}, quarantine_model::QuarantinedInterval};
+
+use super::metrics_store::{HistoryCollection, HistoryQuery, RecordQuery, DEPTH_FIELDS, EARNING_FIELDS, EARNING_SUMMARY_FIELDS, RUNE_POOL_FIELDS, SWAP_FIELDS};
+
+// queries shared by the sql stores, the tables are created by their migrations in migrations/
+
+// a row as (column name, value)
+pub(crate) type Columns = Vec<(String, Bson)>;
+
+// prefix of the joined earnings_summary columns in the earnings query
+const SUMMARY_PREFIX: &str = "summary.";
+
+// the bucket key of the mongodb $group stage, a time_bucket of end_time - 1 shifted by two seconds
+const INTERVAL_START: &str = "(end_time + 1) - ((end_time - 1) % {p}1)";
+
+pub(crate) fn table(collection: HistoryCollection) -> &'static str {
+    match collection {
+        HistoryCollection::Depths => "depth_history",
+        HistoryCollection::Swaps => "swap_history",
+        HistoryCollection::Earnings => "earnings",
+        HistoryCollection::RunePool => "rune_pool_history",
+    }
+}
+
+// column names and values of a record, the ObjectId _id is stored as hex in id
+pub(crate) fn record_columns<T: Serialize>(record: &T) -> Result<Vec<(String, Bson)>, CustomError> {
+    let document = bson::to_document(record).map_err(|e| CustomError::DatabaseError(format!("Failed serializing record {}", e)))?;
+    Ok(document
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Bson::ObjectId(id) => Bson::String(id.to_hex()),
+                value => value,
+            };
+            (if key == "_id" { "id".to_string() } else { key }, value)
+        })
+        .collect())
+}
+
+pub(crate) fn insert_sql(table: &str, columns: &[(String, Bson)], placeholder: char) -> String {
+    let names: Vec<&str> = columns.iter().map(|(name, _)| name.as_str()).collect();
+    let values: Vec<String> = (1..=columns.len()).map(|index| format!("{}{}", placeholder, index)).collect();
+    format!("INSERT INTO {} ({}) VALUES ({})", table, names.join(", "), values.join(", "))
+}
+
+// ORDER BY for the requested sort, only response fields of the collection are accepted so user input
+// never reaches the sql, unknown fields are skipped like missing fields in a mongodb $sort
+fn order_by(collection: HistoryCollection, query: &HistoryQuery) -> String {
+    let fields = match collection {
+        HistoryCollection::Depths => DEPTH_FIELDS,
+        HistoryCollection::Swaps => SWAP_FIELDS,
+        HistoryCollection::Earnings => EARNING_FIELDS,
+        HistoryCollection::RunePool => RUNE_POOL_FIELDS,
+    };
+    let known = |field: &str| {
+        fields.iter().any(|(name, _)| *name == field)
+            || match collection {
+                HistoryCollection::Earnings => field == "interval_start" || field == "pool",
+                _ => field == "startTime" || field == "endTime",
+            }
+    };
+    let mut terms: Vec<String> = query
+        .sort
+        .iter()
+        .filter(|(field, _)| known(field))
+        // mongodb sorts null first
+        .map(|(field, order)| format!("\"{}\" {}", field, if *order < 0 { "DESC NULLS LAST" } else { "ASC NULLS FIRST" }))
+        .collect();
+    terms.push("interval_start".to_string());
+    terms.join(", ")
+}
+
+// the last record of every bucket, params are 1 seconds per interval, 2 from, 3 to, 4 pool, 5 limit, 6 skip
+// written as `{placeholder}n`, $ for postgres and ? for sqlite
+pub(crate) fn history_sql(collection: HistoryCollection, query: &HistoryQuery, placeholder: char) -> String {
+    let filter = match collection {
+        // rune pool records have no pool, the param is always null for them
+        HistoryCollection::RunePool => "start_time >= {p}2 AND (CAST({p}3 AS BIGINT) IS NULL OR end_time <= {p}3) AND CAST({p}4 AS TEXT) IS NULL",
+        _ => "start_time >= {p}2 AND (CAST({p}3 AS BIGINT) IS NULL OR end_time <= {p}3) AND (CAST({p}4 AS TEXT) IS NULL OR pool = {p}4)",
+    };
+    // $last keeps the latest inserted record of a bucket, earnings are bucketed per pool as well
+    let partition = match collection {
+        HistoryCollection::Earnings => "interval_start, pool",
+        _ => "interval_start",
+    };
+    let records = format!(
+        "SELECT *, ROW_NUMBER() OVER (PARTITION BY {} ORDER BY seq DESC) AS position \
+         FROM (SELECT *, {} AS interval_start FROM {} WHERE {}) filtered",
+        partition,
+        INTERVAL_START,
+        table(collection),
+        filter
+    );
+    let buckets = match collection {
+        HistoryCollection::Earnings => {
+            let mut columns = vec!["records.interval_start AS interval_start".to_string(), "records.pool AS pool".to_string()];
+            columns.extend(EARNING_FIELDS.iter().map(|(name, field)| format!("records.{} AS \"{}\"", field, name)));
+            columns.extend(EARNING_SUMMARY_FIELDS.iter().map(|(name, field)| format!("summary.{} AS \"{}{}\"", field, SUMMARY_PREFIX, name)));
+            format!(
+                "SELECT {} FROM ({}) records \
+                 LEFT JOIN earnings_summary summary ON summary.id = records.earnings_summary \
+                 WHERE records.position = 1",
+                columns.join(", "),
+                records
+            )
+        }
+        _ => {
+            let fields = match collection {
+                HistoryCollection::Depths => DEPTH_FIELDS,
+                HistoryCollection::Swaps => SWAP_FIELDS,
+                _ => RUNE_POOL_FIELDS,
+            };
+            let mut columns = vec![
+                "interval_start".to_string(),
+                "interval_start - interval_start % {p}1 AS \"startTime\"".to_string(),
+                "interval_start - interval_start % {p}1 + {p}1 AS \"endTime\"".to_string(),
+            ];
+            columns.extend(fields.iter().map(|(name, field)| format!("{} AS \"{}\"", field, name)));
+            format!("SELECT {} FROM ({}) records WHERE position = 1", columns.join(", "), records)
+        }
+    };
+    format!("SELECT * FROM ({}) buckets ORDER BY {} LIMIT {{p}}5 OFFSET {{p}}6", buckets, order_by(collection, query))
+        .replace("{p}", &placeholder.to_string())
+}
+
+// a history_sql row as the response document of the mongodb pipeline
+pub(crate) fn interval_document(collection: HistoryCollection, columns: impl IntoIterator<Item = (String, Bson)>) -> Document {
+    let mut interval = Document::new();
+    let mut summary = Document::new();
+    for (name, value) in columns {
+        if let Some(name) = name.strip_prefix(SUMMARY_PREFIX) {
+            // every summary column is null when the lookup found nothing
+            if value != Bson::Null {
+                summary.insert(name, value);
+            }
+        } else if name != "interval_start" || collection == HistoryCollection::Earnings {
+            interval.insert(name, value);
+        }
+    }
+    if collection == HistoryCollection::Earnings {
+        interval.insert("earnings_summary", summary);
+    }
+    interval
+}
+
+// stored records of a collection, params are 1 pool, 2 after, 3 from, 4 to and 5 limit
+pub(crate) fn records_sql(collection: HistoryCollection, query: &RecordQuery, placeholder: char) -> String {
+    let order = if query.descending { "DESC" } else { "ASC" };
+    // pools compare bytewise like in mongodb and the memory store, sqlite text already does and postgres follows its locale
+    let collate = if placeholder == '$' { " COLLATE \"C\"" } else { "" };
+    let (pool, pool_order) = match collection {
+        HistoryCollection::RunePool => ("CAST({p}1 AS TEXT) IS NULL", String::new()),
+        _ => ("(CAST({p}1 AS TEXT) IS NULL OR pool = {p}1)", format!("pool{} {}, ", collate, order)),
+    };
+    format!(
+        "SELECT * FROM {} WHERE {} AND (CAST({{p}}2 AS BIGINT) IS NULL OR end_time > {{p}}2) \
+         AND (CAST({{p}}3 AS BIGINT) IS NULL OR start_time >= {{p}}3) AND (CAST({{p}}4 AS BIGINT) IS NULL OR end_time <= {{p}}4) \
+         ORDER BY end_time {}, {}seq {} LIMIT {{p}}5",
+        table(collection),
+        pool,
+        order,
+        pool_order,
+        order
+    )
+    .replace("{p}", &placeholder.to_string())
+}
+
+// a records_sql row as the document mongodb stores, the hex ids are ObjectIds again
+pub(crate) fn stored_document(columns: impl IntoIterator<Item = (String, Bson)>) -> Result<Document, CustomError> {
+    let mut document = Document::new();
+    for (name, value) in columns {
+        match (name.as_str(), value) {
+            ("seq", _) => (),
+            (name @ ("id" | "earnings_summary"), Bson::String(hex)) => {
+                let id = ObjectId::parse_str(&hex).map_err(|e| CustomError::DatabaseError(format!("Invalid stored id {} {}", hex, e)))?;
+                document.insert(if name == "id" { "_id" } else { name }, id);
+            }
+            (_, value) => {
+                document.insert(name, value);
+            }
+        }
+    }
+    Ok(document)
+}
+
+pub(crate) const QUARANTINE_COLUMNS: &str = "id, collection, pool, interval_json, reasons_json, quarantined_at";
+
+// oldest first, params are 1 collection or null and 2 limit
+pub(crate) fn quarantined_sql(placeholder: char) -> String {
+    format!(
+        "SELECT {} FROM quarantine WHERE (CAST({{p}}1 AS TEXT) IS NULL OR collection = {{p}}1) ORDER BY seq LIMIT {{p}}2",
+        QUARANTINE_COLUMNS
+    )
+    .replace("{p}", &placeholder.to_string())
+}
+
+fn quarantine_error(e: impl std::fmt::Display) -> CustomError {
+    CustomError::DatabaseError(format!("Invalid quarantine row {}", e))
+}
+
+// the columns of QUARANTINE_COLUMNS, the interval and the reasons are stored as json text
+pub(crate) fn quarantine_columns(record: &QuarantinedInterval) -> Result<Vec<(String, Bson)>, CustomError> {
+    Ok(vec![
+        ("id".to_string(), Bson::String(record._id.to_hex())),
+        ("collection".to_string(), Bson::String(record.collection.clone())),
+        ("pool".to_string(), record.pool.clone().map(Bson::String).unwrap_or(Bson::Null)),
+        ("interval_json".to_string(), Bson::String(serde_json::to_string(&record.interval).map_err(quarantine_error)?)),
+        ("reasons_json".to_string(), Bson::String(serde_json::to_string(&record.reasons).map_err(quarantine_error)?)),
+        ("quarantined_at".to_string(), Bson::Int64(record.quarantined_at)),
+    ])
+}
+
+// a row of QUARANTINE_COLUMNS
+pub(crate) fn quarantine_record(columns: impl IntoIterator<Item = (String, Bson)>) -> Result<QuarantinedInterval, CustomError> {
+    let row: Document = columns.into_iter().collect();
+    let text = |name: &str| row.get_str(name).map_err(quarantine_error);
+    Ok(QuarantinedInterval {
+        _id: ObjectId::parse_str(text("id")?).map_err(quarantine_error)?,
+        collection: text("collection")?.to_string(),
+        pool: row.get_str("pool").ok().map(str::to_string),
+        interval: serde_json::from_str(text("interval_json")?).map_err(quarantine_error)?,
+        reasons: serde_json::from_str(text("reasons_json")?).map_err(quarantine_error)?,
+        quarantined_at: row.get_i64("quarantined_at").map_err(quarantine_error)?,
+    })
+}
+
+// deletes of the stored summaries with the start_time and end_time of the intervals and of their pools,
+// run before the intervals are inserted again
+pub(crate) fn replaced_earnings(intervals: &[EarningsInterval], placeholder: char) -> Vec<(String, Columns)> {
+    let pools = format!(
+        "DELETE FROM earnings WHERE earnings_summary IN (SELECT id FROM earnings_summary WHERE start_time = {p}1 AND end_time = {p}2)",
+        p = placeholder
+    );
+    let summaries = format!("DELETE FROM earnings_summary WHERE start_time = {p}1 AND end_time = {p}2", p = placeholder);
+    intervals
+        .iter()
+        .flat_map(|(summary, _)| {
+            let times = vec![("start_time".to_string(), Bson::Int64(summary.start_time)), ("end_time".to_string(), Bson::Int64(summary.end_time))];
+            [(pools.clone(), times.clone()), (summaries.clone(), times)]
+        })
+        .collect()
+}
+
+// summaries whose stored pools do not add up to their liquidity earnings, the columns are the fields of EarningsMismatch
+pub(crate) const EARNINGS_MISMATCHES_SQL: &str = "SELECT summary.id AS summary, summary.start_time, summary.end_time, summary.liquidity_earnings, \
+     COUNT(pools.seq) AS pools, CAST(COALESCE(SUM(pools.earning), 0) AS BIGINT) AS pool_earnings \
+     FROM earnings_summary summary LEFT JOIN earnings pools ON pools.earnings_summary = summary.id \
+     GROUP BY summary.id, summary.start_time, summary.end_time, summary.liquidity_earnings \
+     HAVING COALESCE(SUM(pools.earning), 0) <> summary.liquidity_earnings \
+     ORDER BY summary.start_time, summary.id";
+
+pub(crate) fn earnings_mismatch(columns: impl IntoIterator<Item = (String, Bson)>) -> Result<EarningsMismatch, CustomError> {
+    bson::from_document(columns.into_iter().collect()).map_err(|e| CustomError::DatabaseError(format!("Invalid earnings mismatch row {}", e)))
+}
@@ -0,0 +1,246 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use mongodb::bson::{Bson, Document};
+use rusqlite::{params_from_iter, types::{Value, ValueRef}, Connection};
+use serde::Serialize;
+use tracing::info;
+
+use crate::models::{
+    custom_error_model::CustomError, depth_history_model::PoolDepthPriceHistory,
+    earning_history_model::{EarningsInterval, EarningsMismatch}, quarantine_model::QuarantinedInterval, rune_pool_model::RunePool,
+    swap_history_model::SwapHistory,
+};
+
+use super::{
+    metrics_store::{HistoryCollection, HistoryQuery, MetricsStore, RecordQuery},
+    sql_history::{
+        earnings_mismatch, history_sql, insert_sql, interval_document, quarantine_columns, quarantine_record, quarantined_sql, record_columns, records_sql, replaced_earnings,
+        stored_document, table, Columns, EARNINGS_MISMATCHES_SQL, QUARANTINE_COLUMNS,
+    },
+};
+
+// applied in order, each once, the version is recorded in schema_migrations
+const MIGRATIONS: &[(&str, &str)] = &[
+    ("0001_history_tables", include_str!("../../migrations/sqlite/0001_history_tables.sql")),
+    ("0002_integer_amounts", include_str!("../../migrations/sqlite/0002_integer_amounts.sql")),
+    ("0003_quarantine", include_str!("../../migrations/sqlite/0003_quarantine.sql")),
+    ("0004_earnings_replace", include_str!("../../migrations/sqlite/0004_earnings_replace.sql")),
+];
+
+fn sqlite_error(e: impl std::fmt::Display) -> CustomError {
+    CustomError::DatabaseError(format!("SQLite error {}", e))
+}
+
+fn sql_value(value: Bson) -> Result<Value, CustomError> {
+    Ok(match value {
+        Bson::String(value) => Value::Text(value),
+        Bson::Int32(value) => Value::Integer(value as i64),
+        Bson::Int64(value) => Value::Integer(value),
+        Bson::Double(value) => Value::Real(value),
+        Bson::Null => Value::Null,
+        value => return Err(CustomError::DatabaseError(format!("Unsupported value for sqlite {}", value))),
+    })
+}
+
+fn bson_value(value: ValueRef) -> Result<Bson, CustomError> {
+    Ok(match value {
+        ValueRef::Null => Bson::Null,
+        ValueRef::Integer(value) => Bson::Int64(value),
+        ValueRef::Real(value) => Bson::Double(value),
+        ValueRef::Text(value) => Bson::String(String::from_utf8_lossy(value).into_owned()),
+        ValueRef::Blob(_) => return Err(CustomError::DatabaseError("Unsupported blob column".to_string())),
+    })
+}
+
+// history tables in a single sqlite file, for demos and single node deployments without a database server
+#[derive(Clone)]
+pub struct SqliteStore{
+    connection : Arc<Mutex<Connection>>
+}
+
+impl SqliteStore {
+    // sqlite://path or sqlite://:memory:, creates the file and applies the pending migrations
+    pub fn open(url: &str) -> Result<Self, CustomError> {
+        let path = url
+            .strip_prefix("sqlite://")
+            .ok_or_else(|| CustomError::InvalidInput(format!("{} is not a sqlite:// url", url)))?;
+        let mut connection = Connection::open(path).map_err(sqlite_error)?;
+        // readers keep going while the ingestion writes
+        connection.pragma_update(None, "journal_mode", "WAL").map_err(sqlite_error)?;
+        migrate(&mut connection)?;
+        Ok(SqliteStore { connection: Arc::new(Mutex::new(connection)) })
+    }
+
+    // rusqlite blocks, so every statement runs on the blocking pool
+    async fn with_connection<T, F>(&self, f: F) -> Result<T, CustomError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, CustomError> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || f(&mut connection.lock().unwrap()))
+            .await
+            .map_err(sqlite_error)?
+    }
+
+    async fn insert_all<T: Serialize>(&self, table: &'static str, records: &[T]) -> Result<u64, CustomError> {
+        self.insert_rows(table, records.iter().map(record_columns).collect::<Result<Vec<_>, _>>()?).await
+    }
+
+    async fn insert_rows(&self, table: &'static str, rows: Vec<Vec<(String, Bson)>>) -> Result<u64, CustomError> {
+        self.insert_tables(Vec::new(), vec![(table, rows)]).await
+    }
+
+    // the deletes of the replaced rows and then the rows of several tables in one transaction, the count is of the last table
+    async fn insert_tables(&self, deletes: Vec<(String, Columns)>, tables: Vec<(&'static str, Vec<Columns>)>) -> Result<u64, CustomError> {
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction().map_err(sqlite_error)?;
+            for (sql, params) in deletes {
+                let values = params.into_iter().map(|(_, value)| sql_value(value)).collect::<Result<Vec<_>, _>>()?;
+                transaction.execute(&sql, params_from_iter(values)).map_err(sqlite_error)?;
+            }
+            let mut inserted = 0;
+            for (table, rows) in tables {
+                let Some(first) = rows.first() else {
+                    inserted = 0;
+                    continue;
+                };
+                inserted = rows.len() as u64;
+                let mut statement = transaction.prepare(&insert_sql(table, first, '?')).map_err(sqlite_error)?;
+                for row in rows {
+                    let values = row.into_iter().map(|(_, value)| sql_value(value)).collect::<Result<Vec<_>, _>>()?;
+                    statement.execute(params_from_iter(values)).map_err(sqlite_error)?;
+                }
+            }
+            transaction.commit().map_err(sqlite_error)?;
+            Ok(inserted)
+        })
+        .await
+    }
+}
+
+// every row as (column name, value)
+fn query_columns(connection: &Connection, sql: &str, params: Vec<Value>) -> Result<Vec<Vec<(String, Bson)>>, CustomError> {
+    let mut statement = connection.prepare(sql).map_err(sqlite_error)?;
+    let names: Vec<String> = statement.column_names().into_iter().map(str::to_string).collect();
+    let mut rows = statement.query(params_from_iter(params)).map_err(sqlite_error)?;
+    let mut columns = Vec::new();
+    while let Some(row) = rows.next().map_err(sqlite_error)? {
+        columns.push(
+            names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| Ok((name.clone(), bson_value(row.get_ref(index).map_err(sqlite_error)?)?)))
+                .collect::<Result<Vec<_>, CustomError>>()?,
+        );
+    }
+    Ok(columns)
+}
+
+fn migrate(connection: &mut Connection) -> Result<(), CustomError> {
+    connection
+        .execute_batch("CREATE TABLE IF NOT EXISTS schema_migrations (version TEXT PRIMARY KEY, applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP)")
+        .map_err(sqlite_error)?;
+    for (version, sql) in MIGRATIONS {
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        let applied: i64 = transaction
+            .query_row("SELECT COUNT(*) FROM schema_migrations WHERE version = ?1", [version], |row| row.get(0))
+            .map_err(sqlite_error)?;
+        if applied == 0 {
+            transaction.execute_batch(sql).map_err(|e| sqlite_error(format!("in migration {}: {}", version, e)))?;
+            transaction.execute("INSERT INTO schema_migrations (version) VALUES (?1)", [version]).map_err(sqlite_error)?;
+            info!(version, "Applied sqlite migration");
+        }
+        transaction.commit().map_err(sqlite_error)?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl MetricsStore for SqliteStore {
+    async fn insert_depths(&self, records: &[PoolDepthPriceHistory]) -> Result<u64, CustomError> {
+        self.insert_all("depth_history", records).await
+    }
+
+    async fn insert_swaps(&self, records: &[SwapHistory]) -> Result<u64, CustomError> {
+        self.insert_all("swap_history", records).await
+    }
+
+    async fn insert_earnings_intervals(&self, intervals: &[EarningsInterval]) -> Result<u64, CustomError> {
+        let summaries = intervals.iter().map(|(summary, _)| record_columns(summary)).collect::<Result<Vec<_>, _>>()?;
+        let pools = intervals.iter().flat_map(|(_, pools)| pools).map(record_columns).collect::<Result<Vec<_>, _>>()?;
+        self.insert_tables(replaced_earnings(intervals, '?'), vec![("earnings_summary", summaries), ("earnings", pools)]).await
+    }
+
+    async fn earnings_mismatches(&self) -> Result<Vec<EarningsMismatch>, CustomError> {
+        self.with_connection(|connection| query_columns(connection, EARNINGS_MISMATCHES_SQL, Vec::new())?.into_iter().map(earnings_mismatch).collect())
+            .await
+    }
+
+    async fn insert_rune_pool(&self, records: &[RunePool]) -> Result<u64, CustomError> {
+        self.insert_all("rune_pool_history", records).await
+    }
+
+    async fn insert_quarantined(&self, records: &[QuarantinedInterval]) -> Result<u64, CustomError> {
+        self.insert_rows("quarantine", records.iter().map(quarantine_columns).collect::<Result<Vec<_>, _>>()?).await
+    }
+
+    async fn quarantined(&self, collection: Option<&str>, limit: i64) -> Result<Vec<QuarantinedInterval>, CustomError> {
+        let params = vec![collection.map(|collection| Value::Text(collection.to_string())).unwrap_or(Value::Null), Value::Integer(limit.max(0))];
+        self.with_connection(move |connection| query_columns(connection, &quarantined_sql('?'), params)?.into_iter().map(quarantine_record).collect())
+            .await
+    }
+
+    async fn quarantined_interval(&self, id: &str) -> Result<Option<QuarantinedInterval>, CustomError> {
+        let sql = format!("SELECT {} FROM quarantine WHERE id = ?1", QUARANTINE_COLUMNS);
+        let params = vec![Value::Text(id.to_string())];
+        self.with_connection(move |connection| query_columns(connection, &sql, params)?.into_iter().next().map(quarantine_record).transpose())
+            .await
+    }
+
+    async fn remove_quarantined(&self, id: &str) -> Result<bool, CustomError> {
+        let id = id.to_string();
+        self.with_connection(move |connection| {
+            let removed = connection.execute("DELETE FROM quarantine WHERE id = ?1", [id]).map_err(sqlite_error)?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+
+    async fn latest_end_time(&self, collection: HistoryCollection) -> Result<Option<i64>, CustomError> {
+        let sql = format!("SELECT MAX(end_time) FROM {}", table(collection));
+        self.with_connection(move |connection| connection.query_row(&sql, [], |row| row.get(0)).map_err(sqlite_error))
+            .await
+    }
+
+    async fn records(&self, collection: HistoryCollection, query: &RecordQuery) -> Result<Vec<Document>, CustomError> {
+        let sql = records_sql(collection, query, '?');
+        let params = vec![
+            query.pool.clone().map(Value::Text).unwrap_or(Value::Null),
+            query.after.map(Value::Integer).unwrap_or(Value::Null),
+            query.from.map(Value::Integer).unwrap_or(Value::Null),
+            query.to.map(Value::Integer).unwrap_or(Value::Null),
+            Value::Integer(query.limit.max(0)),
+        ];
+        self.with_connection(move |connection| query_columns(connection, &sql, params)?.into_iter().map(stored_document).collect())
+            .await
+    }
+
+    async fn history_intervals(&self, collection: HistoryCollection, query: &HistoryQuery) -> Result<Vec<Document>, CustomError> {
+        let sql = history_sql(collection, query, '?');
+        let params = vec![
+            Value::Integer(query.seconds_per_interval as i64),
+            Value::Integer(query.from),
+            query.to.map(Value::Integer).unwrap_or(Value::Null),
+            query.pool.clone().map(Value::Text).unwrap_or(Value::Null),
+            Value::Integer(query.limit.max(0)),
+            Value::Integer(query.skip.max(0)),
+        ];
+        self.with_connection(move |connection| {
+            let rows = query_columns(connection, &sql, params)?;
+            Ok(rows.into_iter().map(|columns| interval_document(collection, columns)).collect())
+        })
+        .await
+    }
+}
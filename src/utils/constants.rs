@@ -1 +1,4 @@
 pub const API_START_TIME:i64 = 1_647_913_096;
+pub const RUNE_ASSET:&str = "THOR.RUNE";
+pub const VALID_INTERVALS:[&str; 6] = ["hour", "day", "week", "month", "quarter", "year"];
+pub const INGESTED_COLLECTIONS:[&str; 4] = ["depths", "swaps", "earnings", "runepool"];
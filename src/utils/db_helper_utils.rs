@@ -1,7 +1,3 @@
-use mongodb::bson::doc;
-use mongodb::bson::Document;
-
-
 pub fn get_seconds_per_interval(interval: &str) -> i32 {
     match interval {
         "hour" => 3600,
@@ -14,37 +10,9 @@ pub fn get_seconds_per_interval(interval: &str) -> i32 {
     }
 }
 
-pub async fn build_query_sort_skip(
-    to: Option<u64>,
-    sort_by: Option<String>,
-    sort_order: Option<i8>,
-    page: Option<u64>,
-    limit: Option<i16>,
-    count: Option<u32>,
-) -> (Document, Document, i64, i16) {
-    // Default values
-    let page = page.unwrap_or(1);
-
-    let limit: i16 = limit.unwrap_or_else(|| count.unwrap_or(400) as i16);
-
-    let mut query = Document::new();
-
-
-    // Constructing the "end_time" filter
-    if let Some(to) = to {
-        query.insert("end_time", doc! { "$lte": to as i64 });
-    }
-
-    // Constructing the sort filter
-    let sort_filter = if let Some(sort_by) = sort_by {
-        let sort_order = sort_order.unwrap_or(1);
-        doc! { sort_by: if sort_order >= 1 { 1 } else { -1 } }
-    } else {
-        doc! { "end_time": -1 }
-    };
-
-    // Calculating skip size
-    let skip_size = (page - 1) * (limit as u64);
-
-    (query, sort_filter, skip_size as i64, limit)
+// start of the interval a record ending at end_time is bucketed in, the $group key of the history pipelines
+// rounded down to the interval
+pub fn get_interval_start(end_time: i64, seconds_per_interval: i64) -> i64 {
+    let interval_start = (end_time + 1) - (end_time - 1) % seconds_per_interval;
+    interval_start - interval_start % seconds_per_interval
 }
@@ -0,0 +1,109 @@
+// technical indicators over an ascending series, every output is aligned with the input
+// and holds None until the indicator has enough points (warm-up)
+
+use super::stats_utils::{mean, std_dev};
+
+pub fn sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 {
+        return result;
+    }
+    let mut window_sum = 0.0;
+    for (i, value) in values.iter().enumerate() {
+        window_sum += value;
+        if i >= period {
+            window_sum -= values[i - period];
+        }
+        if i + 1 >= period {
+            result[i] = Some(window_sum / period as f64);
+        }
+    }
+    result
+}
+
+// seeded with the sma of the first period points
+pub fn ema(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 || values.len() < period {
+        return result;
+    }
+    let multiplier = 2.0 / (period as f64 + 1.0);
+    let mut previous = values[..period].iter().sum::<f64>() / period as f64;
+    result[period - 1] = Some(previous);
+    for i in period..values.len() {
+        previous = (values[i] - previous) * multiplier + previous;
+        result[i] = Some(previous);
+    }
+    result
+}
+
+// wilder's smoothing of average gains and losses
+pub fn rsi(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 || values.len() <= period {
+        return result;
+    }
+    let mut avg_gain = 0.0;
+    let mut avg_loss = 0.0;
+    for i in 1..=period {
+        let change = values[i] - values[i - 1];
+        if change > 0.0 { avg_gain += change } else { avg_loss -= change }
+    }
+    avg_gain /= period as f64;
+    avg_loss /= period as f64;
+    result[period] = Some(rsi_from_averages(avg_gain, avg_loss));
+    for i in (period + 1)..values.len() {
+        let change = values[i] - values[i - 1];
+        let (gain, loss) = if change > 0.0 { (change, 0.0) } else { (0.0, -change) };
+        avg_gain = (avg_gain * (period as f64 - 1.0) + gain) / period as f64;
+        avg_loss = (avg_loss * (period as f64 - 1.0) + loss) / period as f64;
+        result[i] = Some(rsi_from_averages(avg_gain, avg_loss));
+    }
+    result
+}
+
+fn rsi_from_averages(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss == 0.0 {
+        return if avg_gain == 0.0 { 50.0 } else { 100.0 };
+    }
+    100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+}
+
+pub struct BollingerBands {
+    pub upper: Vec<Option<f64>>,
+    pub middle: Vec<Option<f64>>,
+    pub lower: Vec<Option<f64>>,
+}
+
+// sma middle band with bands at k population standard deviations
+pub fn bollinger(values: &[f64], period: usize, k: f64) -> BollingerBands {
+    let middle = sma(values, period);
+    let mut upper = vec![None; values.len()];
+    let mut lower = vec![None; values.len()];
+    for (i, mean) in middle.iter().enumerate() {
+        if let Some(mean) = mean {
+            // the window is never empty once the sma is there
+            let deviation = std_dev(&values[i + 1 - period..=i], *mean).unwrap_or_default();
+            upper[i] = Some(mean + k * deviation);
+            lower[i] = Some(mean - k * deviation);
+        }
+    }
+    BollingerBands { upper, middle, lower }
+}
+
+// rolling standard deviation of log returns over period returns (not annualized)
+pub fn volatility(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 || values.len() <= period {
+        return result;
+    }
+    let returns = values
+        .windows(2)
+        .map(|pair| if pair[0] > 0.0 && pair[1] > 0.0 { (pair[1] / pair[0]).ln() } else { 0.0 })
+        .collect::<Vec<f64>>();
+    for i in period..values.len() {
+        let window = &returns[i - period..i];
+        result[i] = mean(window).and_then(|mean| std_dev(window, mean));
+    }
+    result
+}
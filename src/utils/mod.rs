@@ -1,3 +1,7 @@
 pub mod db_helper_utils;
 pub mod constants;
-pub mod parser_utils;
\ No newline at end of file
+pub mod parser_utils;
+pub mod pool_math_utils;
+pub mod indicator_utils;
+pub mod stats_utils;
+pub mod tracing_utils;
\ No newline at end of file
@@ -1,17 +1,58 @@
-use std::error::Error as stdError;
-
 use mongodb::bson::Bson;
+use serde::{de, Deserialize, Deserializer};
+
+use crate::models::custom_error_model::CustomError;
 
-pub fn parse_to_type<T>(value: &str, field_name: &str) -> Result<T, Box<dyn stdError>>
+// midgard sends every number as a string, a value that does not parse fails the whole interval
+pub fn parse_to_type<T>(value: &str, field_name: &str) -> Result<T, CustomError>
 where
     T: std::str::FromStr,
-    T::Err: stdError + 'static,
 {
     value.parse::<T>().map_err(|_| {
-        format!("Failed to parse {} as {}", field_name, std::any::type_name::<T>()).into()
+        CustomError::InvalidInput(format!("Failed to parse {} {:?} as {}", field_name, value, std::any::type_name::<T>()))
     })
 }
 
+// 1e8 scaled amounts are stored as integers, records written before that hold doubles
+pub fn deserialize_amount<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Amount {
+        Integer(i64),
+        Double(f64),
+        Text(String),
+    }
+    match Amount::deserialize(deserializer)? {
+        Amount::Integer(value) => Ok(value),
+        Amount::Double(value) => double_amount(value).ok_or_else(|| de::Error::custom(format!("amount {} is out of range", value))),
+        Amount::Text(value) => parse_to_type(&value, "amount").map_err(de::Error::custom),
+    }
+}
+
+fn double_amount(value: f64) -> Option<i64> {
+    (value.is_finite() && value.abs() < i64::MAX as f64).then(|| value.round() as i64)
+}
+
+// stored amounts as integers, with the doubles of older records converted like deserialize_amount
+pub fn bson_to_amount(value: &Bson) -> Option<i64> {
+    match value {
+        Bson::Int32(value) => Some(*value as i64),
+        Bson::Int64(value) => Some(*value),
+        Bson::Double(value) => double_amount(*value),
+        _ => None,
+    }
+}
+
+// numbers of stored records, integers included unlike Bson::as_f64
+pub fn bson_to_f64(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Int32(value) => Some(*value as f64),
+        Bson::Int64(value) => Some(*value as f64),
+        Bson::Double(value) => Some(*value),
+        _ => None,
+    }
+}
+
 pub fn subtract_bson_values(bson_value_a: &Bson, bson_value_b: &Bson) -> f64 {
     // Attempt to convert both Bson values to f64
     let value_a = bson_value_a.as_f64().unwrap_or(0.0);
@@ -21,16 +62,10 @@ pub fn subtract_bson_values(bson_value_a: &Bson, bson_value_b: &Bson) -> f64 {
 }
 
 
-// macros for parsing
+// macros for parsing, returns the CustomError from the enclosing function
 #[macro_export]
 macro_rules! parse_field {
     ($interval:expr, $field:ident, $type:ty) => {
-        match crate::utils::parser_utils::parse_to_type::<$type>(&$interval.$field, stringify!($field)){
-            Ok(res) => res,
-            Err(e) => {
-                eprintln!("{}",e);
-                0 as $type
-            }
-        }
+        $crate::utils::parser_utils::parse_to_type::<$type>(&$interval.$field, stringify!($field))?
     };
 }
@@ -0,0 +1,44 @@
+// continuous liquidity pool (CLP) formulas used by THORChain, all amounts are 1e8 scaled like the stored depths
+// x = input amount, X = input side depth, Y = output side depth
+
+#[derive(Debug, Clone, Copy)]
+pub struct SwapLeg {
+    pub output: f64,
+    pub fee: f64,
+    pub slip: f64,
+}
+
+// y = (x * X * Y) / (x + X)^2
+pub fn get_swap_output(x: f64, input_depth: f64, output_depth: f64) -> f64 {
+    let denominator = (x + input_depth).powi(2);
+    if denominator == 0.0 {
+        return 0.0;
+    }
+    (x * input_depth * output_depth) / denominator
+}
+
+// fee = (x^2 * Y) / (x + X)^2, charged in the output asset
+pub fn get_swap_fee(x: f64, input_depth: f64, output_depth: f64) -> f64 {
+    let denominator = (x + input_depth).powi(2);
+    if denominator == 0.0 {
+        return 0.0;
+    }
+    (x.powi(2) * output_depth) / denominator
+}
+
+// slip = x / (x + X)
+pub fn get_swap_slip(x: f64, input_depth: f64) -> f64 {
+    let denominator = x + input_depth;
+    if denominator == 0.0 {
+        return 0.0;
+    }
+    x / denominator
+}
+
+pub fn get_swap_leg(x: f64, input_depth: f64, output_depth: f64) -> SwapLeg {
+    SwapLeg {
+        output: get_swap_output(x, input_depth, output_depth),
+        fee: get_swap_fee(x, input_depth, output_depth),
+        slip: get_swap_slip(x, input_depth),
+    }
+}
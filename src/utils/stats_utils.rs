@@ -0,0 +1,53 @@
+// robust statistics for baselines, median/MAD are not dragged around by the spikes we are looking for
+
+pub fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+// median absolute deviation around the median
+pub fn mad(values: &[f64], median: f64) -> Option<f64> {
+    let deviations = values.iter().map(|value| (value - median).abs()).collect::<Vec<f64>>();
+    self::median(&deviations)
+}
+
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+pub fn std_dev(values: &[f64], mean: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    Some(variance.sqrt())
+}
+
+// modified z-score (Iglewicz and Hoaglin), falls back to the classic z-score when the MAD is 0
+// which happens on flat baselines like pools with mostly zero volume hours
+pub fn robust_z_score(value: f64, baseline: &[f64]) -> Option<f64> {
+    let median = median(baseline)?;
+    let mad = mad(baseline, median)?;
+    if mad > 0.0 {
+        return Some(0.6745 * (value - median) / mad);
+    }
+    let mean = mean(baseline)?;
+    let std_dev = std_dev(baseline, mean)?;
+    if std_dev > 0.0 {
+        Some((value - mean) / std_dev)
+    } else {
+        None
+    }
+}
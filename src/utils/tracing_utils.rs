@@ -0,0 +1,17 @@
+use std::env;
+
+use tracing_subscriber::{fmt, EnvFilter};
+
+// RUST_LOG picks the levels (defaults to info), LOG_FORMAT=json switches to one json object per line
+pub fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    // stderr keeps stdout clean for cli output like `export`
+    let builder = fmt().with_env_filter(filter).with_target(false).with_writer(std::io::stderr);
+    let result = match env::var("LOG_FORMAT").as_deref() {
+        Ok("json") => builder.json().flatten_event(true).with_current_span(true).with_span_list(true).try_init(),
+        _ => builder.try_init(),
+    };
+    if let Err(e) = result {
+        eprintln!("Failed initialising logging {}", e);
+    }
+}
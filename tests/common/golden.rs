@@ -0,0 +1,16 @@
+use std::{env, fs, path::PathBuf};
+
+use serde_json::Value;
+
+// compares with tests/golden/<name>.json, run with UPDATE_GOLDEN=1 to write the files after an intended change
+pub fn assert_golden(name: &str, actual: &Value) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.json", name));
+    let pretty = serde_json::to_string_pretty(actual).unwrap() + "\n";
+    if env::var("UPDATE_GOLDEN").is_ok() {
+        fs::write(&path, pretty).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| panic!("missing {}, run with UPDATE_GOLDEN=1", path.display()));
+    let expected: Value = serde_json::from_str(&expected).unwrap();
+    assert!(expected == *actual, "{} differs from {}:\n{}", name, path.display(), pretty);
+}
@@ -0,0 +1,70 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{atomic::{AtomicUsize, Ordering}, Arc},
+};
+
+use actix_web::{dev::Service, web, App, HttpResponse, HttpServer};
+use tokenmetrics::{config::Config, services::backfill_service::backfill, stores::memory_store::MemoryStore, AppState};
+
+use super::{app_state_with_config, HOUR, T0};
+
+// synthetic midgard responses in midgard's format, named after the route and the pool
+fn fixture(name: &str) -> HttpResponse {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/midgard").join(format!("{}.json", name));
+    match fs::read_to_string(path) {
+        Ok(body) => HttpResponse::Ok().content_type("application/json").body(body),
+        Err(_) => HttpResponse::NotFound().body(format!("no fixture {}", name)),
+    }
+}
+
+async fn swaps(query: web::Query<Vec<(String, String)>>) -> HttpResponse {
+    match query.iter().find(|(key, _)| key == "pool") {
+        Some((_, pool)) => fixture(&format!("swaps_{}", pool)),
+        None => HttpResponse::BadRequest().finish(),
+    }
+}
+
+async fn depths(pool: web::Path<String>) -> HttpResponse {
+    fixture(&format!("depths_{}", pool))
+}
+
+// stand-in for midgard on a free local port serving tests/fixtures/midgard whatever the range,
+// returns the base url for MidgardConfig and the services
+pub async fn start_midgard() -> String {
+    start_counting_midgard(Arc::new(AtomicUsize::new(0))).await
+}
+
+// start_midgard counting the requests it answers
+pub async fn start_counting_midgard(requests: Arc<AtomicUsize>) -> String {
+    let server = HttpServer::new(move || {
+        let requests = requests.clone();
+        App::new()
+            .wrap_fn(move |req, srv| {
+                requests.fetch_add(1, Ordering::SeqCst);
+                srv.call(req)
+            })
+            .route("/v2/history/swaps", web::get().to(swaps))
+            .route("/v2/history/depths/{pool}", web::get().to(depths))
+            .route("/v2/history/earnings", web::get().to(|| async { fixture("earnings") }))
+            .route("/v2/history/runepool", web::get().to(|| async { fixture("runepool") }))
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let address = server.addrs()[0];
+    actix_web::rt::spawn(server.run());
+    format!("http://{}", address)
+}
+
+// ingests the synthetic midgard responses of tests/fixtures/midgard the way the backfill command does
+pub async fn ingested_state() -> AppState {
+    let mut config = Config::default();
+    config.midgard.base_url = start_midgard().await;
+    let state = app_state_with_config(config, Arc::new(MemoryStore::default())).await;
+    for collection in ["depths", "swaps", "earnings", "runepool"] {
+        let reports = backfill(&state.db, &state.config, collection, None, T0, T0 + 3 * HOUR).await.unwrap();
+        assert!(reports.iter().all(|report| report.failed_pages == 0 && report.end_time == Some(T0 + 3 * HOUR)), "{}", collection);
+    }
+    state
+}
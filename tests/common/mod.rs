@@ -0,0 +1,266 @@
+// shared by several test crates, each uses only part of it
+#![allow(dead_code)]
+
+pub mod golden;
+pub mod midgard;
+
+use std::sync::Arc;
+
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use tokenmetrics::{
+    config::{Config, DatabaseConfig},
+    models::{
+        api_request_param_model::QueryParams,
+        depth_history_model::PoolDepthPriceHistory,
+        earning_history_model::{PoolEarningHistory, PoolEarningSummary},
+        quarantine_model::QuarantinedInterval,
+        rune_pool_model::RunePool,
+        swap_history_model::SwapHistory,
+    },
+    services::db::DataBase,
+    stores::{memory_store::MemoryStore, metrics_store::{HistoryCollection, HistoryQuery, MetricsStore, RecordQuery}},
+    AppState,
+};
+
+pub const HOUR: i64 = 3_600;
+// 2024-01-01T00:00:00Z, aligned to every interval up to a day
+pub const T0: i64 = 1_704_067_200;
+
+// the mongodb client connects lazily and nothing on the history routes reaches it
+pub async fn app_state(store: Arc<dyn MetricsStore>) -> AppState {
+    app_state_with_config(Config::default(), store).await
+}
+
+pub async fn app_state_with_config(config: Config, store: Arc<dyn MetricsStore>) -> AppState {
+    let database = DatabaseConfig { uri: "mongodb://127.0.0.1:9".to_string(), name: "tokenmetrics-test".to_string(), history_uri: None };
+    let db = DataBase::init(&database).await.unwrap().with_store(store);
+    AppState::new(config, db)
+}
+
+pub fn depth(pool: &str, start_time: i64, asset_depth: i64, asset_price: f64) -> PoolDepthPriceHistory {
+    PoolDepthPriceHistory {
+        _id: ObjectId::new(),
+        pool: pool.to_string(),
+        asset_depth,
+        asset_price,
+        asset_price_usd: asset_price * 2.0,
+        end_time: start_time + HOUR,
+        liquidity_units: 100,
+        luvi: asset_price / 10.0,
+        members_count: 7,
+        rune_depth: (asset_depth as f64 * asset_price) as i64,
+        start_time,
+        synth_supply: 5,
+        synth_units: 6,
+        units: 200,
+    }
+}
+
+const SWAP_FIELDS: [&str; 35] = [
+    "average_slip", "from_trade_average_slip", "from_trade_count", "from_trade_fees", "from_trade_volume",
+    "from_trade_volume_usd", "rune_price_usd", "synth_mint_average_slip", "synth_mint_count", "synth_mint_fees",
+    "synth_mint_volume", "synth_mint_volume_usd", "synth_redeem_average_slip", "synth_redeem_count", "synth_redeem_fees",
+    "synth_redeem_volume", "synth_redeem_volume_usd", "to_asset_average_slip", "to_asset_count", "to_asset_fees",
+    "to_asset_volume", "to_asset_volume_usd", "to_rune_average_slip", "to_rune_count", "to_rune_fees", "to_rune_volume",
+    "to_rune_volume_usd", "to_trade_average_slip", "to_trade_count", "to_trade_fees", "to_trade_volume",
+    "to_trade_volume_usd", "total_count", "total_fees", "total_volume",
+];
+
+// every field is `value`, counts, fees and volumes are `value` as an integer
+pub fn swap(pool: &str, start_time: i64, value: f64) -> SwapHistory {
+    let mut record: Document = doc! {
+        "_id": ObjectId::new(),
+        "pool": pool,
+        "start_time": start_time,
+        "end_time": start_time + HOUR,
+        "total_volume_usd": value,
+    };
+    for field in SWAP_FIELDS {
+        if field.ends_with("_count") || field.ends_with("_fees") || field.ends_with("_volume") {
+            record.insert(field, value as i64);
+        } else {
+            record.insert(field, value);
+        }
+    }
+    mongodb::bson::from_document(record).unwrap()
+}
+
+pub fn earnings_summary(start_time: i64, avg_node_count: f64, earnings: i64) -> PoolEarningSummary {
+    PoolEarningSummary {
+        _id: ObjectId::new(),
+        avg_node_count,
+        block_rewards: 10,
+        bonding_earnings: 20,
+        earnings,
+        end_time: start_time + HOUR,
+        liquidity_earnings: 30,
+        liquidity_fees: 40,
+        start_time,
+        rune_price_usd: 4.0,
+    }
+}
+
+pub fn earning(pool: &str, summary: &PoolEarningSummary, rewards: i64) -> PoolEarningHistory {
+    PoolEarningHistory {
+        _id: ObjectId::new(),
+        pool: pool.to_string(),
+        asset_liquidity_fees: 1,
+        earning: 2,
+        rewards,
+        rune_liquidity_fees: 3,
+        saver_earning: 4,
+        total_liquidity_fees_rune: 5,
+        start_time: summary.start_time,
+        end_time: summary.end_time,
+        earnings_summary: summary._id,
+    }
+}
+
+pub fn rune_pool(start_time: i64, count: i64, units: i64) -> RunePool {
+    RunePool { _id: ObjectId::new(), count, end_time: start_time + HOUR, start_time, units }
+}
+
+// the same history for the store comparisons, with a replaced record and a second pool
+pub async fn seed_history(store: &dyn MetricsStore) {
+    store
+        .insert_depths(&[
+            depth("BTC.BTC", T0, 100, 10.0),
+            depth("BTC.BTC", T0 + HOUR, 110, 11.0),
+            depth("BTC.BTC", T0 + HOUR, 115, 11.5),
+            depth("BTC.BTC", T0 + 30 * HOUR, 120, 12.0),
+            depth("ETH.ETH", T0, 999, 99.0),
+        ])
+        .await
+        .unwrap();
+    store.insert_swaps(&[swap("BTC.BTC", T0, 1.0), swap("BTC.BTC", T0 + HOUR, 2.0), swap("BTC.BTC", T0 + 2 * HOUR, 3.0)]).await.unwrap();
+    for (hour, avg_node_count) in [(0, 10.0), (1, 20.0)] {
+        let summary = earnings_summary(T0 + hour * HOUR, avg_node_count, 100);
+        let pools = vec![earning("BTC.BTC", &summary, 1), earning("ETH.ETH", &summary, 2)];
+        store.insert_earnings_intervals(&[(summary, pools)]).await.unwrap();
+    }
+    store.insert_rune_pool(&[rune_pool(T0, 1, 10), rune_pool(T0 + HOUR, 2, 20)]).await.unwrap();
+}
+
+pub fn history_params(pool: Option<&str>, interval: &str) -> QueryParams {
+    QueryParams {
+        pool: pool.map(str::to_string),
+        interval: Some(interval.to_string()),
+        count: None,
+        to: Some((T0 + 48 * HOUR) as u64),
+        from: Some(T0 as u64),
+        page: None,
+        sort_by: None,
+        sort_order: None,
+        limit: None,
+    }
+}
+
+async fn both(store: &dyn MetricsStore, memory: &MemoryStore, collection: HistoryCollection, params: QueryParams) -> (Vec<Document>, Vec<Document>) {
+    let query = HistoryQuery::new(collection, params, None).unwrap();
+    (store.history_intervals(collection, &query).await.unwrap(), memory.history_intervals(collection, &query).await.unwrap())
+}
+
+pub fn quarantined(collection: &str, pool: Option<&str>, reason: &str) -> QuarantinedInterval {
+    QuarantinedInterval {
+        _id: ObjectId::new(),
+        collection: collection.to_string(),
+        pool: pool.map(str::to_string),
+        interval: serde_json::json!({ "startTime": T0.to_string(), "pools": [{ "pool": "BTC.BTC", "rewards": "abc" }] }),
+        reasons: vec![reason.to_string()],
+        quarantined_at: T0 + HOUR,
+    }
+}
+
+// seeds `store` and a MemoryStore alike and compares their intervals and responses
+// each seeding generates its own ids
+fn without_ids(mut records: Vec<Document>) -> Vec<Document> {
+    for record in records.iter_mut() {
+        record.remove("_id");
+        record.remove("earnings_summary");
+    }
+    records
+}
+
+pub async fn assert_matches_memory_store(store: &dyn MetricsStore) {
+    let memory = MemoryStore::default();
+    seed_history(store).await;
+    seed_history(&memory).await;
+
+    for interval in ["hour", "day"] {
+        let (actual, expected) = both(store, &memory, HistoryCollection::Depths, history_params(Some("BTC.BTC"), interval)).await;
+        assert!(!expected.is_empty());
+        assert_eq!(actual, expected, "depths per {}", interval);
+    }
+    let (actual, expected) = both(store, &memory, HistoryCollection::Swaps, history_params(Some("BTC.BTC"), "hour")).await;
+    assert_eq!(actual, expected);
+    let (actual, expected) = both(store, &memory, HistoryCollection::Earnings, history_params(None, "hour")).await;
+    assert_eq!(actual.len(), 4);
+    assert_eq!(actual, expected);
+    let (actual, expected) = both(store, &memory, HistoryCollection::RunePool, history_params(None, "hour")).await;
+    assert_eq!(actual, expected);
+
+    let mut sorted = history_params(Some("BTC.BTC"), "hour");
+    sorted.sort_by = Some("assetDepth".to_string());
+    sorted.sort_order = Some(1);
+    sorted.count = Some(2);
+    sorted.page = Some(2);
+    let (actual, expected) = both(store, &memory, HistoryCollection::Depths, sorted).await;
+    assert_eq!(actual, expected);
+
+    assert_eq!(
+        store.get_pool_earnings_history_api(history_params(None, "hour")).await.unwrap(),
+        memory.get_pool_earnings_history_api(history_params(None, "hour")).await.unwrap()
+    );
+    assert_eq!(
+        store.latest_end_time(HistoryCollection::Depths).await.unwrap(),
+        memory.latest_end_time(HistoryCollection::Depths).await.unwrap()
+    );
+
+    let btc = Some("BTC.BTC".to_string());
+    let record_queries = [
+        (HistoryCollection::Depths, RecordQuery { pool: btc.clone(), limit: 100, ..RecordQuery::default() }),
+        (HistoryCollection::Depths, RecordQuery { pool: btc.clone(), descending: true, limit: 1, ..RecordQuery::default() }),
+        (HistoryCollection::Depths, RecordQuery { pool: btc.clone(), from: Some(T0 + HOUR), to: Some(T0 + 3 * HOUR), limit: 100, ..RecordQuery::default() }),
+        (HistoryCollection::Swaps, RecordQuery { pool: btc.clone(), after: Some(T0 + HOUR), limit: 100, ..RecordQuery::default() }),
+        (HistoryCollection::Earnings, RecordQuery { pool: btc, limit: 100, ..RecordQuery::default() }),
+        // records of one end_time come back in the same order from every store, both ways
+        (HistoryCollection::Depths, RecordQuery { limit: 100, ..RecordQuery::default() }),
+        (HistoryCollection::Depths, RecordQuery { descending: true, limit: 100, ..RecordQuery::default() }),
+        (HistoryCollection::Earnings, RecordQuery { descending: true, limit: 100, ..RecordQuery::default() }),
+        (HistoryCollection::RunePool, RecordQuery { descending: true, limit: 2, ..RecordQuery::default() }),
+    ];
+    for (collection, query) in record_queries {
+        let expected = without_ids(memory.records(collection, &query).await.unwrap());
+        assert!(!expected.is_empty(), "{:?} {:?}", collection, query);
+        assert_eq!(without_ids(store.records(collection, &query).await.unwrap()), expected, "{:?} {:?}", collection, query);
+    }
+
+    let records = [quarantined("depths", Some("BTC.BTC"), "units \"x\" is not a number"), quarantined("earnings", None, "rewards \"abc\" is not a number")];
+    for store in [store, &memory as &dyn MetricsStore] {
+        assert_eq!(store.insert_quarantined(&records).await.unwrap(), 2);
+        assert_eq!(store.quarantined(None, 10).await.unwrap(), records.to_vec());
+        assert_eq!(store.quarantined(Some("earnings"), 10).await.unwrap(), records[1..].to_vec());
+        assert_eq!(store.quarantined_interval(&records[0]._id.to_hex()).await.unwrap().as_ref(), Some(&records[0]));
+        assert_eq!(store.quarantined_interval("not an id").await.unwrap(), None);
+        assert!(store.remove_quarantined(&records[0]._id.to_hex()).await.unwrap());
+        assert!(!store.remove_quarantined(&records[0]._id.to_hex()).await.unwrap());
+        assert_eq!(store.quarantined(None, 10).await.unwrap(), records[1..].to_vec());
+    }
+
+    // pools that add up to the liquidity earnings of their summary, the seeded ones do not
+    let summary = earnings_summary(T0 + 2 * HOUR, 30.0, 100);
+    let mut pools = vec![earning("BTC.BTC", &summary, 1), earning("ETH.ETH", &summary, 2)];
+    (pools[0].earning, pools[1].earning) = (10, 20);
+    let intervals = [(summary, pools)];
+    for store in [store, &memory as &dyn MetricsStore] {
+        assert_eq!(store.insert_earnings_intervals(&intervals).await.unwrap(), 2);
+        let mismatches: Vec<(i64, i64, i64, i64)> = store
+            .earnings_mismatches()
+            .await
+            .unwrap()
+            .iter()
+            .map(|mismatch| (mismatch.start_time, mismatch.pools, mismatch.pool_earnings, mismatch.liquidity_earnings))
+            .collect();
+        assert_eq!(mismatches, [(T0, 2, 4, 30), (T0 + HOUR, 2, 4, 30)]);
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use std::{env, fs, path::PathBuf, sync::Arc};
+
+use common::*;
+use serde_json::{json, Value};
+use tokenmetrics::{
+    cli::{run_command, Command, VerifyArgs},
+    config::Config,
+    models::earning_history_model::PoolEarningHistory,
+    services::{db::DataBase, earnings_history_service::Interval, maintenance_service::verify_collection},
+    stores::{memory_store::MemoryStore, metrics_store::{HistoryCollection, MetricsStore, RecordQuery}, sqlite_store::SqliteStore},
+};
+
+// the earnings fixture page, edited before it is parsed like the ingestion does
+fn earnings_page(edit: impl FnOnce(&mut Vec<Value>)) -> Vec<Interval> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/midgard/earnings.json");
+    let page: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let mut intervals = page["intervals"].as_array().unwrap().clone();
+    edit(&mut intervals);
+    serde_json::from_value(Value::Array(intervals)).unwrap()
+}
+
+fn stores() -> [Arc<dyn MetricsStore>; 2] {
+    [Arc::new(MemoryStore::default()), Arc::new(SqliteStore::open("sqlite://:memory:").unwrap())]
+}
+
+async fn stored_interval_starts(store: &dyn MetricsStore) -> Vec<i64> {
+    let query = store.history_query(HistoryCollection::Earnings, history_params(None, "hour")).await.unwrap();
+    let mut starts: Vec<i64> = store
+        .history_intervals(HistoryCollection::Earnings, &query)
+        .await
+        .unwrap()
+        .iter()
+        .map(|interval| interval.get_i64("interval_start").unwrap())
+        .collect();
+    starts.sort();
+    starts
+}
+
+#[actix_web::test]
+async fn a_page_whose_pools_fail_to_write_leaves_no_summary() {
+    let path = env::temp_dir().join(format!("tokenmetrics-{}.db", uuid::Uuid::new_v4().simple()));
+    let store = Arc::new(SqliteStore::open(&format!("sqlite://{}", path.display())).unwrap());
+    let state = app_state(store.clone()).await;
+    let intervals = earnings_page(|_| {});
+    // the second pool of the second interval is rejected after the summaries were written
+    let rejected = (&intervals[1].pools[1].pool, intervals[1].start_time.clone());
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    connection
+        .execute_batch(&format!(
+            "CREATE TRIGGER reject_pool BEFORE INSERT ON earnings WHEN NEW.pool = '{}' AND NEW.start_time = {} BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+            rejected.0, rejected.1
+        ))
+        .unwrap();
+    assert!(PoolEarningHistory::store_earning_history(&state.db, intervals).await.is_err());
+    assert!(stored_interval_starts(store.as_ref()).await.is_empty());
+
+    // the page fetched again is stored whole
+    connection.execute_batch("DROP TRIGGER reject_pool;").unwrap();
+    PoolEarningHistory::store_earning_history(&state.db, earnings_page(|_| {})).await.unwrap();
+    assert_eq!(stored_interval_starts(store.as_ref()).await.len(), 6);
+    drop((connection, state, store));
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+#[actix_web::test]
+async fn negative_earnings_are_quarantined() {
+    let store = Arc::new(MemoryStore::default());
+    let state = app_state(store.clone()).await;
+    let intervals = earnings_page(|intervals| intervals[1]["pools"][1]["earnings"] = json!("-5"));
+    let quarantined = PoolEarningHistory::store_earning_history(&state.db, intervals).await.unwrap();
+    assert_eq!(quarantined.len(), 1);
+    assert_eq!(quarantined[0].reasons, ["pools.earnings -5 is negative"]);
+}
+
+#[actix_web::test]
+async fn a_page_stored_again_replaces_its_summaries_and_pools() {
+    for store in stores() {
+        let state = app_state(store.clone()).await;
+        PoolEarningHistory::store_earning_history(&state.db, earnings_page(|_| {})).await.unwrap();
+        let stored = store.records(HistoryCollection::Earnings, &RecordQuery { limit: i64::MAX, ..RecordQuery::default() }).await.unwrap();
+
+        PoolEarningHistory::store_earning_history(&state.db, earnings_page(|_| {})).await.unwrap();
+        let restored = store.records(HistoryCollection::Earnings, &RecordQuery { limit: i64::MAX, ..RecordQuery::default() }).await.unwrap();
+        assert_eq!(restored.len(), stored.len());
+        assert_eq!(stored_interval_starts(store.as_ref()).await.len(), 6);
+    }
+}
+
+#[actix_web::test]
+async fn verify_lists_inconsistent_summaries_without_failing() {
+    let mut config = Config::default();
+    config.database.set_url("sqlite://:memory:");
+    let db = DataBase::init(&config.database).await.unwrap();
+    // the pools of the fixture do not add up to the liquidity earnings of their summaries
+    PoolEarningHistory::store_earning_history(&db, earnings_page(|_| {})).await.unwrap();
+    let report = verify_collection(&db, "earnings").await.unwrap();
+    assert!(!report.inconsistent_summaries.is_empty());
+    assert!(report.is_consistent());
+    let command = Command::Verify(VerifyArgs { collection: Some("earnings".to_string()) });
+    assert_eq!(run_command(&db, &config, command).await.unwrap(), 0);
+}
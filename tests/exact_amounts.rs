@@ -0,0 +1,103 @@
+mod common;
+
+use std::{fs, path::PathBuf, sync::Arc};
+
+use actix_web::{http::StatusCode, test};
+use common::*;
+use mongodb::bson::{self, doc, Bson};
+use serde_json::{json, Value};
+use tokenmetrics::{
+    build_app,
+    controllers::earnings_history_api_controller::earnings_history_response,
+    models::{depth_history_model::PoolDepthPriceHistory, earning_history_model::{PoolEarningHistory, PoolEarningSummary}},
+    services::depth_history_service::Interval,
+    stores::{memory_store::MemoryStore, metrics_store::MetricsStore, sqlite_store::SqliteStore},
+};
+
+// 2^53 + 1, the first integer a double cannot hold
+const PAST_F64: i64 = 9_007_199_254_740_993;
+
+// the BTC.BTC depths fixture page with its first asset depth replaced
+fn depths_page(asset_depth: &str) -> Vec<Interval> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/midgard/depths_BTC.BTC.json");
+    let mut page: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    page["intervals"][0]["assetDepth"] = json!(asset_depth);
+    serde_json::from_value(page["intervals"].clone()).unwrap()
+}
+
+async fn get(state: &tokenmetrics::AppState, uri: &str) -> (StatusCode, Value) {
+    let app = test::init_service(build_app(state)).await;
+    let res = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+    (res.status(), test::read_body_json(res).await)
+}
+
+#[actix_web::test]
+async fn amounts_past_f64_precision_are_served_exactly() {
+    let stores: [Arc<dyn MetricsStore>; 2] = [Arc::new(MemoryStore::default()), Arc::new(SqliteStore::open("sqlite://:memory:").unwrap())];
+    for store in stores {
+        let state = app_state(store).await;
+        let quarantined = PoolDepthPriceHistory::store_price_history(&state.db, "BTC.BTC", depths_page(&PAST_F64.to_string())).await.unwrap();
+        assert!(quarantined.is_empty());
+        let range = format!("interval=hour&from={}&to={}", T0, T0 + HOUR);
+        let (status, body) = get(&state, &format!("/depths?pool=BTC.BTC&{}", range)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["intervals"][0]["assetDepth"].as_i64(), Some(PAST_F64));
+        let (status, body) = get(&state, &format!("/v2/history/depths/BTC.BTC?{}", range)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["intervals"][0]["assetDepth"], json!(PAST_F64.to_string()));
+        assert_eq!(body["meta"]["startAssetDepth"], json!(PAST_F64.to_string()));
+    }
+}
+
+#[actix_web::test]
+async fn legacy_double_amounts_still_deserialize() {
+    let mut document = bson::to_document(&depth("BTC.BTC", T0, 0, 1.0)).unwrap();
+    document.insert("asset_depth", Bson::Double(81_234_567_890.0));
+    document.insert("units", Bson::String("2345678901234".to_string()));
+    let record: PoolDepthPriceHistory = bson::from_document(document).unwrap();
+    assert_eq!((record.asset_depth, record.units), (81_234_567_890, 2_345_678_901_234));
+}
+
+#[actix_web::test]
+async fn legacy_double_earnings_still_deserialize() {
+    let summary = earnings_summary(T0, 10.0, 0);
+    let mut document = bson::to_document(&summary).unwrap();
+    document.insert("earnings", Bson::Double(268_913_207.0));
+    document.insert("liquidity_fees", Bson::Double(15_949_748_490.0));
+    let record: PoolEarningSummary = bson::from_document(document).unwrap();
+    assert_eq!((record.earnings, record.liquidity_fees), (268_913_207, 15_949_748_490));
+
+    let mut document = bson::to_document(&earning("BTC.BTC", &summary, 1)).unwrap();
+    document.insert("earning", Bson::Double(4_405_821_942.0));
+    let record: PoolEarningHistory = bson::from_document(document).unwrap();
+    assert_eq!(record.earning, 4_405_821_942);
+}
+
+#[actix_web::test]
+async fn earnings_meta_averages_legacy_double_and_integer_summaries() {
+    let interval = |earnings: Bson, avg_node_count: Bson| {
+        doc! {
+            "pool": "BTC.BTC",
+            "earnings_summary": {
+                "avgNodeCount": avg_node_count,
+                "blockRewards": 10_i64,
+                "bondingEarnings": Bson::Double(20.0),
+                "earnings": earnings,
+                "liquidityEarnings": 30_i64,
+                "runePriceUSD": 2.0,
+            },
+        }
+    };
+    let intervals = vec![
+        interval(Bson::Double(1_000_000_001.0), Bson::Double(100.0)),
+        interval(Bson::Int64(PAST_F64), Bson::Int64(101)),
+        interval(Bson::Double(3.0), Bson::Int32(102)),
+    ];
+    let response = earnings_history_response(intervals).unwrap();
+    let meta = response.get_document("meta").unwrap();
+    assert_eq!(meta.get_i64("earnings").unwrap(), (1_000_000_001 + PAST_F64 + 3) / 3);
+    assert_eq!(meta.get_i64("bondingEarnings").unwrap(), 20);
+    assert_eq!(meta.get_i64("blockRewards").unwrap(), 10);
+    assert_eq!(meta.get_f64("avgNodeCount").unwrap(), 101.0);
+    assert_eq!(meta.get_f64("runePriceUSD").unwrap(), 2.0);
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, test};
+use common::*;
+use serde_json::{json, Value};
+use tokenmetrics::{build_app, stores::{memory_store::MemoryStore, metrics_store::MetricsStore}};
+
+async fn get(store: Arc<MemoryStore>, uri: &str) -> (StatusCode, Value) {
+    let state = app_state(store).await;
+    let app = test::init_service(build_app(&state)).await;
+    let res = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+    let status = res.status();
+    let body = test::read_body(res).await;
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+async fn seeded_depths() -> Arc<MemoryStore> {
+    let store = Arc::new(MemoryStore::default());
+    let records = [
+        depth("BTC.BTC", T0, 100, 10.0),
+        depth("BTC.BTC", T0 + HOUR, 110, 11.0),
+        depth("BTC.BTC", T0 + 2 * HOUR, 120, 12.0),
+        depth("ETH.ETH", T0, 999, 99.0),
+    ];
+    store.insert_depths(&records).await.unwrap();
+    store
+}
+
+#[actix_web::test]
+async fn depths_are_returned_per_hour_newest_first() {
+    let (status, body) = get(seeded_depths().await, &format!("/depths?pool=BTC.BTC&from={}&to={}", T0, T0 + 3 * HOUR)).await;
+    assert_eq!(status, StatusCode::OK);
+    let intervals = body["intervals"].as_array().unwrap();
+    let end_times: Vec<i64> = intervals.iter().map(|interval| interval["endTime"].as_i64().unwrap()).collect();
+    assert_eq!(end_times, vec![T0 + 3 * HOUR, T0 + 2 * HOUR, T0 + HOUR]);
+    assert_eq!(intervals[0]["assetDepth"], json!(120));
+    assert_eq!(intervals[0]["pool"], json!("BTC.BTC"));
+    assert_eq!(body["meta"]["startTime"], json!(T0 + 2 * HOUR));
+    assert_eq!(body["meta"]["endTime"], json!(T0 + HOUR));
+}
+
+#[actix_web::test]
+async fn depths_meta_follows_the_requested_order() {
+    let uri = format!("/depths?pool=BTC.BTC&from={}&to={}&sort_by=end_time&sort_order=1", T0, T0 + 3 * HOUR);
+    let (status, body) = get(seeded_depths().await, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let meta = &body["meta"];
+    assert_eq!(meta["startTime"], json!(T0));
+    assert_eq!(meta["endTime"], json!(T0 + 3 * HOUR));
+    assert_eq!(meta["startAssetDepth"], json!(100));
+    assert_eq!(meta["endAssetDepth"], json!(120));
+    assert_eq!(meta["priceShiftLoss"], json!(-2.0));
+}
+
+#[actix_web::test]
+async fn depths_keep_the_last_record_of_a_day() {
+    let (status, body) = get(seeded_depths().await, &format!("/depths?pool=BTC.BTC&interval=day&from={}", T0)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["intervals"], json!([{
+        "startTime": T0,
+        "endTime": T0 + 86_400,
+        "assetDepth": 120,
+        "assetPrice": 12.0,
+        "assetPriceUSD": 24.0,
+        "liquidityUnits": 100,
+        "luvi": 1.2,
+        "membersCount": 7,
+        "runeDepth": 1440,
+        "synthSupply": 5,
+        "synthUnits": 6,
+        "units": 200,
+        "pool": "BTC.BTC"
+    }]));
+}
+
+#[actix_web::test]
+async fn depths_reject_pools_that_are_not_configured() {
+    let (status, _) = get(seeded_depths().await, "/depths?pool=ETH.ETH").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn depths_without_records_in_range_fail_instead_of_panicking() {
+    let (status, body) = get(seeded_depths().await, &format!("/depths?pool=BTC.BTC&from={}", T0 + 100 * HOUR)).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(body["InvalidInput"].is_string());
+}
+
+#[actix_web::test]
+async fn depths_default_range_ends_at_the_latest_record() {
+    // count intervals back from the newest stored end_time, not from now
+    let (status, body) = get(seeded_depths().await, "/depths?pool=BTC.BTC&count=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["intervals"].as_array().unwrap().len(), 2);
+}
+
+#[actix_web::test]
+async fn swaps_are_paged() {
+    let store = Arc::new(MemoryStore::default());
+    let records: Vec<_> = (0..5).map(|hour| swap("BTC.BTC", T0 + hour * HOUR, hour as f64)).collect();
+    store.insert_swaps(&records).await.unwrap();
+    let uri = format!("/swaps?pool=BTC.BTC&from={}&sort_by=start_time&sort_order=1&limit=2&count=2&page=2", T0);
+    let (status, body) = get(store, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let intervals = body["intervals"].as_array().unwrap();
+    let start_times: Vec<i64> = intervals.iter().map(|interval| interval["startTime"].as_i64().unwrap()).collect();
+    assert_eq!(start_times, vec![T0 + 2 * HOUR, T0 + 3 * HOUR]);
+    assert_eq!(intervals[1]["totalCount"], json!(3));
+    assert_eq!(body["meta"]["totalVolumeUSD"], json!(3.0));
+    assert!(intervals[0].get("pool").is_none());
+}
+
+#[actix_web::test]
+async fn swaps_meta_keeps_the_mint_and_redeem_slips_apart() {
+    let store = Arc::new(MemoryStore::default());
+    let mut record = swap("BTC.BTC", T0, 1.0);
+    (record.synth_mint_average_slip, record.synth_redeem_average_slip) = (1.5, 2.5);
+    store.insert_swaps(&[record]).await.unwrap();
+    let (status, body) = get(store, &format!("/swaps?pool=BTC.BTC&from={}&to={}", T0, T0 + HOUR)).await;
+    assert_eq!(status, StatusCode::OK);
+    // the meta used to report the redeem slip as the mint slip
+    assert_eq!(body["meta"]["synthMintAverageSlip"], json!(1.5));
+    assert_eq!(body["meta"]["synthRedeemAverageSlip"], json!(2.5));
+}
+
+#[actix_web::test]
+async fn earnings_are_grouped_per_pool_with_averaged_summaries() {
+    let store = Arc::new(MemoryStore::default());
+    for (hour, avg_node_count, earnings) in [(0, 10.0, 100), (1, 20.0, 300)] {
+        let summary = earnings_summary(T0 + hour * HOUR, avg_node_count, earnings);
+        let pools = vec![earning("BTC.BTC", &summary, 1), earning("ETH.ETH", &summary, 2)];
+        store.insert_earnings_intervals(&[(summary, pools)]).await.unwrap();
+    }
+    let (status, body) = get(store, &format!("/earnings?from={}&to={}", T0, T0 + 2 * HOUR)).await;
+    assert_eq!(status, StatusCode::OK);
+    let pools = body["intervals"]["pools"].as_array().unwrap();
+    let keys: Vec<(i64, &str)> = pools
+        .iter()
+        .map(|pool| (pool["interval_start"].as_i64().unwrap(), pool["pool"].as_str().unwrap()))
+        .collect();
+    // interval_start is the raw $group key, two seconds past the bucket start
+    assert_eq!(keys, vec![(T0 + HOUR + 2, "BTC.BTC"), (T0 + HOUR + 2, "ETH.ETH"), (T0 + 2, "BTC.BTC"), (T0 + 2, "ETH.ETH")]);
+    assert!(pools.iter().all(|pool| pool.get("earnings_summary").is_none()));
+    assert_eq!(pools[1]["rewards"], json!(2));
+    // four pool rows, each carrying its interval summary
+    assert_eq!(body["meta"]["avgNodeCount"], json!(15.0));
+    assert_eq!(body["meta"]["earnings"], json!(200));
+}
+
+#[actix_web::test]
+async fn runepool_is_bucketed_and_rejects_pools() {
+    let store = Arc::new(MemoryStore::default());
+    store.insert_rune_pool(&[rune_pool(T0, 1, 10), rune_pool(T0 + HOUR, 2, 20)]).await.unwrap();
+    let (status, body) = get(store.clone(), &format!("/runepool?from={}&interval=day", T0)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["intervals"], json!([{ "startTime": T0, "endTime": T0 + 86_400, "count": 2, "units": 20 }]));
+    assert_eq!(body["meta"]["endUnits"], json!("20"));
+
+    let (status, _) = get(store, "/runepool?pool=BTC.BTC").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+}
@@ -0,0 +1,61 @@
+use tokenmetrics::utils::indicator_utils::{bollinger, ema, rsi, sma, volatility};
+
+fn assert_close(actual: Option<f64>, expected: f64) {
+    let actual = actual.unwrap();
+    assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+}
+
+#[test]
+fn sma_averages_the_last_period_points_after_its_warm_up() {
+    let values = sma(&[1.0, 2.0, 3.0, 4.0, 5.0], 3);
+    assert_eq!(values[..2], [None, None]);
+    assert_close(values[2], 2.0);
+    assert_close(values[3], 3.0);
+    assert_close(values[4], 4.0);
+    assert_eq!(sma(&[1.0, 2.0], 0), [None, None]);
+}
+
+#[test]
+fn ema_is_seeded_with_the_sma() {
+    let values = ema(&[2.0, 4.0, 6.0, 8.0], 3);
+    assert_eq!(values[..2], [None, None]);
+    assert_close(values[2], 4.0);
+    // multiplier 2 / (3 + 1)
+    assert_close(values[3], (8.0 - 4.0) * 0.5 + 4.0);
+    assert_eq!(ema(&[1.0, 2.0], 3), [None, None]);
+}
+
+#[test]
+fn rsi_uses_wilder_smoothing() {
+    assert_close(rsi(&[1.0, 2.0, 3.0, 4.0], 3)[3], 100.0);
+    assert_close(rsi(&[4.0, 3.0, 2.0, 1.0], 3)[3], 0.0);
+    assert_close(rsi(&[1.0, 1.0, 1.0, 1.0], 3)[3], 50.0);
+
+    // gains 2 and losses 1 over the first two changes, then a loss of 1
+    let values = rsi(&[10.0, 12.0, 11.0, 10.0], 2);
+    assert_eq!(values[..2], [None, None]);
+    assert_close(values[2], 100.0 - 100.0 / (1.0 + 1.0 / 0.5));
+    assert_close(values[3], 100.0 - 100.0 / (1.0 + 0.5 / 0.75));
+}
+
+#[test]
+fn bollinger_bands_are_k_population_deviations_around_the_sma() {
+    let bands = bollinger(&[1.0, 3.0, 1.0, 3.0], 2, 2.0);
+    assert_eq!(bands.middle[0], None);
+    assert_eq!(bands.upper[0], None);
+    assert_close(bands.middle[1], 2.0);
+    assert_close(bands.upper[1], 4.0);
+    assert_close(bands.lower[1], 0.0);
+    assert_close(bands.upper[3], 4.0);
+}
+
+#[test]
+fn volatility_is_the_deviation_of_log_returns() {
+    // doubling every point is a constant return
+    assert_close(volatility(&[1.0, 2.0, 4.0, 8.0], 2)[3], 0.0);
+    let values = volatility(&[1.0, 2.0, 1.0], 2);
+    assert_eq!(values[..2], [None, None]);
+    assert_close(values[2], 2f64.ln());
+    // a zero price is a zero return instead of an infinite one
+    assert!(volatility(&[0.0, 1.0, 2.0], 2)[2].unwrap().is_finite());
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use actix_web::{http::StatusCode, test};
+use common::{golden::assert_golden, midgard::ingested_state, *};
+use serde_json::Value;
+use tokenmetrics::{build_app, controllers::midgard_compat_api_controller::midgard_history_intervals, stores::metrics_store::HistoryCollection};
+
+fn midgard_fixture(name: &str) -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/midgard").join(format!("{}.json", name));
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+// prices are stored as doubles, "3.0" comes back as "3"
+fn same_numbers(ours: &Value, midgard: &Value) -> bool {
+    match (ours, midgard) {
+        (Value::Object(ours), Value::Object(midgard)) => {
+            ours.len() == midgard.len() && ours.iter().all(|(key, value)| midgard.get(key).is_some_and(|other| same_numbers(value, other)))
+        }
+        (Value::Array(ours), Value::Array(midgard)) => ours.len() == midgard.len() && ours.iter().zip(midgard).all(|(a, b)| same_numbers(a, b)),
+        (Value::String(ours), Value::String(midgard)) => ours == midgard || ours.parse::<f64>().ok() == midgard.parse::<f64>().ok(),
+        _ => false,
+    }
+}
+
+fn all_strings(value: &Value) -> bool {
+    match value {
+        Value::Object(object) => object.values().all(all_strings),
+        Value::Array(values) => values.iter().all(all_strings),
+        value => value.is_string(),
+    }
+}
+
+#[actix_web::test]
+async fn v2_routes_answer_like_the_midgard_fixtures() {
+    let state = ingested_state().await;
+    let app = test::init_service(build_app(&state)).await;
+    let range = format!("interval=hour&from={}&to={}", T0, T0 + 3 * HOUR);
+    for (fixture, uri) in [
+        ("depths_BTC.BTC", format!("/v2/history/depths/BTC.BTC?{}", range)),
+        ("swaps_BTC.BTC", format!("/v2/history/swaps?pool=BTC.BTC&{}", range)),
+        ("earnings", format!("/v2/history/earnings?{}", range)),
+        ("runepool", format!("/v2/history/runepool?{}", range)),
+    ] {
+        let res = test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{}", uri);
+        let body: Value = test::read_body_json(res).await;
+        let midgard = midgard_fixture(fixture);
+        assert!(all_strings(&body), "{} {}", uri, body);
+        assert!(same_numbers(&body["intervals"], &midgard["intervals"]), "{} {} {}", uri, body["intervals"], midgard["intervals"]);
+        let keys = |meta: &Value| meta.as_object().unwrap().keys().cloned().collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(keys(&body["meta"]), keys(&midgard["meta"]), "{}", uri);
+        assert_eq!(body["meta"]["startTime"], midgard["meta"]["startTime"], "{}", uri);
+        assert_eq!(body["meta"]["endTime"], midgard["meta"]["endTime"], "{}", uri);
+        assert_golden(&format!("v2_{}", fixture.split('_').next().unwrap()), &body);
+    }
+}
+
+#[actix_web::test]
+async fn v2_meta_follows_midgard() {
+    let state = ingested_state().await;
+    let app = test::init_service(build_app(&state)).await;
+    let range = format!("interval=hour&from={}&to={}", T0, T0 + 3 * HOUR);
+    let get = |uri: String| {
+        let app = &app;
+        async move { test::read_body_json::<Value, _>(test::call_service(app, test::TestRequest::get().uri(&uri).to_request()).await).await }
+    };
+    // the range totals and the first and last interval, like the fixture meta
+    let runepool = get(format!("/v2/history/runepool?{}", range)).await;
+    assert_eq!(runepool["meta"], midgard_fixture("runepool")["meta"]);
+    let swaps = get(format!("/v2/history/swaps?pool=BTC.BTC&{}", range)).await;
+    let midgard = midgard_fixture("swaps_BTC.BTC");
+    for field in ["totalCount", "toAssetCount", "toAssetVolume", "totalFees", "runePriceUSD"] {
+        assert_eq!(swaps["meta"][field], midgard["meta"][field], "{}", field);
+    }
+    let depths = get(format!("/v2/history/depths/BTC.BTC?{}", range)).await;
+    for field in ["startAssetDepth", "endAssetDepth", "startLPUnits", "endLPUnits", "endMemberCount"] {
+        assert_eq!(depths["meta"][field], midgard_fixture("depths_BTC.BTC")["meta"][field], "{}", field);
+    }
+    // count limits intervals, not the pool rows of an interval
+    let earnings = get(format!("/v2/history/earnings?interval=hour&count=2&from={}", T0)).await;
+    let intervals = earnings["intervals"].as_array().unwrap();
+    assert_eq!(intervals.len(), 2);
+    assert!(intervals.iter().all(|interval| interval["pools"].as_array().unwrap().len() == 2));
+    assert_eq!(earnings["meta"]["pools"][0]["pool"], "BTC.BTC");
+    assert_eq!(earnings["meta"]["pools"][0]["earnings"], "2000000005");
+}
+
+#[actix_web::test]
+async fn v2_rejects_what_midgard_rejects() {
+    let state = ingested_state().await;
+    let app = test::init_service(build_app(&state)).await;
+    for uri in [
+        "/v2/history/depths/DOGE.DOGE?interval=hour".to_string(),
+        "/v2/history/swaps?interval=fortnight".to_string(),
+        "/v2/history/runepool?count=401".to_string(),
+    ] {
+        let res = test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn v2_empty_ranges_answer_with_no_intervals() {
+    let state = ingested_state().await;
+    let app = test::init_service(build_app(&state)).await;
+    let range = format!("interval=hour&from={}&to={}", T0 + 10 * HOUR, T0 + 20 * HOUR);
+    for uri in [
+        format!("/v2/history/depths/BTC.BTC?{}", range),
+        format!("/v2/history/swaps?pool=BTC.BTC&{}", range),
+        format!("/v2/history/earnings?{}", range),
+        format!("/v2/history/runepool?{}", range),
+    ] {
+        let res = test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK, "{}", uri);
+        let body: Value = test::read_body_json(res).await;
+        assert_eq!(body["intervals"], serde_json::json!([]), "{}", uri);
+        assert!(all_strings(&body), "{} {}", uri, body);
+        assert_eq!(body["meta"]["startTime"], (T0 + 10 * HOUR).to_string(), "{}", uri);
+        assert_eq!(body["meta"]["endTime"], (T0 + 20 * HOUR).to_string(), "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn v2_earnings_read_at_most_400_intervals() {
+    let state = ingested_state().await;
+    let mut params = history_params(None, "hour");
+    params.to = Some((T0 + 1000 * HOUR) as u64);
+    let (intervals, query) = midgard_history_intervals(state.db.store.as_ref(), HistoryCollection::Earnings, params).await.unwrap();
+    assert_eq!(query.to, Some(T0 + 400 * HOUR));
+    assert!(!intervals.is_empty());
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+
+use actix_web::{http::StatusCode, test};
+use common::{golden::assert_golden, midgard::{ingested_state, start_counting_midgard}, *};
+use serde_json::Value;
+use tokenmetrics::{build_app, config::Config, services::backfill_service::backfill, stores::memory_store::MemoryStore, AppState};
+
+async fn get_all(state: &AppState, uris: &[(&str, String)]) -> Vec<(String, StatusCode, Value)> {
+    let app = test::init_service(build_app(state)).await;
+    let mut responses = Vec::new();
+    for (name, uri) in uris {
+        let res = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        let status = res.status();
+        let body = test::read_body(res).await;
+        responses.push((name.to_string(), status, serde_json::from_slice(&body).unwrap_or(Value::Null)));
+    }
+    responses
+}
+
+#[actix_web::test]
+async fn history_routes_match_the_golden_files() {
+    let state = ingested_state().await;
+    let range = format!("from={}&to={}", T0, T0 + 3 * HOUR);
+    let uris = [
+        ("depths_hour", format!("/depths?pool=BTC.BTC&{}", range)),
+        ("depths_day", format!("/depths?pool=BTC.BTC&interval=day&{}", range)),
+        ("depths_ascending", format!("/depths?pool=BTC.BTC&sort_by=end_time&sort_order=1&{}", range)),
+        ("swaps_hour", format!("/swaps?pool=BTC.BTC&{}", range)),
+        ("swaps_paged", format!("/swaps?pool=BTC.BTC&count=1&page=2&{}", range)),
+        ("earnings_hour", format!("/earnings?{}", range)),
+        ("earnings_btc", format!("/earnings?pool=BTC.BTC&{}", range)),
+        ("runepool_hour", format!("/runepool?{}", range)),
+        ("runepool_day", format!("/runepool?interval=day&{}", range)),
+    ];
+    for (name, status, body) in get_all(&state, &uris).await {
+        assert_eq!(status, StatusCode::OK, "{} {}", name, body);
+        assert_golden(&name, &body);
+    }
+}
+
+#[actix_web::test]
+async fn invalid_requests_match_the_golden_files() {
+    let state = ingested_state().await;
+    let uris = [
+        ("invalid_interval", "/depths?pool=BTC.BTC&interval=fortnight".to_string()),
+        ("unknown_pool", format!("/depths?pool=DOGE.DOGE&from={}", T0)),
+        ("runepool_with_pool", format!("/runepool?pool=BTC.BTC&from={}", T0)),
+    ];
+    for (name, status, body) in get_all(&state, &uris).await {
+        assert!(status.is_client_error() || status.is_server_error(), "{} {}", name, status);
+        assert_golden(&name, &serde_json::json!({ "status": status.as_u16(), "body": body }));
+    }
+}
+
+#[actix_web::test]
+async fn service_routes_answer_without_mongodb() {
+    let state = ingested_state().await;
+    let uris = [("lander", "/".to_string()), ("health", "/health".to_string()), ("metrics", "/metrics".to_string())];
+    for (name, status, _) in get_all(&state, &uris).await {
+        assert_eq!(status, StatusCode::OK, "{}", name);
+    }
+}
+
+#[actix_web::test]
+async fn backfills_request_every_page_once() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let mut config = Config::default();
+    config.midgard.base_url = start_counting_midgard(requests.clone()).await;
+    let state = app_state_with_config(config, Arc::new(MemoryStore::default())).await;
+    // the fixture page ends at T0 + 3 hours, asked from there it ends the backfill
+    let reports = backfill(&state.db, &state.config, "earnings", None, T0, T0 + 10 * HOUR).await.unwrap();
+    assert_eq!((reports[0].pages, reports[0].failed_pages, reports[0].end_time), (2, 0, Some(T0 + 3 * HOUR)));
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::{midgard::ingested_state, *};
+use serde_json::json;
+use tokenmetrics::{
+    services::midgard_verify_service::{verify_against_midgard, DiscrepancyKind, MidgardVerifyPlan, MidgardVerifyReport},
+    stores::metrics_store::HistoryCollection,
+    AppState,
+};
+
+// the three recorded hours as a single window
+fn plan(state: &AppState, collection: HistoryCollection) -> MidgardVerifyPlan {
+    MidgardVerifyPlan {
+        collections: vec![collection],
+        samples: 1,
+        count: 3,
+        from: T0,
+        to: Some(T0 + 3 * HOUR),
+        ..MidgardVerifyPlan::new(&state.config)
+    }
+}
+
+async fn verify(state: &AppState, collection: HistoryCollection) -> MidgardVerifyReport {
+    verify_against_midgard(&state.db, &state.config, &plan(state, collection)).await.unwrap()
+}
+
+#[actix_web::test]
+async fn ingested_intervals_match_midgard() {
+    let state = ingested_state().await;
+    for collection in [HistoryCollection::Depths, HistoryCollection::Swaps, HistoryCollection::RunePool] {
+        let report = verify(&state, collection).await;
+        assert_eq!(report.windows, 1);
+        assert!(report.compared_fields > 0);
+        let interval_discrepancies: Vec<_> = report.discrepancies.iter().filter(|discrepancy| discrepancy.key != "meta").collect();
+        assert!(interval_discrepancies.is_empty(), "{:?}", interval_discrepancies);
+    }
+}
+
+#[actix_web::test]
+async fn meta_differences_are_reported() {
+    let state = ingested_state().await;
+    let report = verify(&state, HistoryCollection::Depths).await;
+    // midgard reports luviIncrease as a ratio, we subtract
+    let luvi = report.discrepancies.iter().find(|discrepancy| discrepancy.field.as_deref() == Some("luviIncrease")).unwrap();
+    assert_eq!(luvi.kind, DiscrepancyKind::Value);
+    assert_eq!(luvi.key, "meta");
+    assert_eq!(luvi.midgard, Some(json!("1.0002726")));
+    assert!(!report.is_consistent());
+}
+
+#[actix_web::test]
+async fn changed_values_are_reported_within_tolerance() {
+    let state = ingested_state().await;
+    // a later record of the first hour replaces the ingested one in the bucket
+    let mut changed = depth("BTC.BTC", T0, 81234567890 * 101 / 100, 66.8701);
+    changed.luvi = 0.0523412;
+    state.db.store.insert_depths(&[changed]).await.unwrap();
+
+    let report = verify(&state, HistoryCollection::Depths).await;
+    let first_hour: Vec<_> = report.discrepancies.iter().filter(|discrepancy| discrepancy.key == T0.to_string()).collect();
+    let asset_depth = first_hour.iter().find(|discrepancy| discrepancy.field.as_deref() == Some("assetDepth")).unwrap();
+    assert_eq!(asset_depth.kind, DiscrepancyKind::Value);
+    assert_eq!(asset_depth.midgard, Some(json!("81234567890")));
+
+    let mut loose = plan(&state, HistoryCollection::Depths);
+    loose.tolerance = 0.05;
+    let report = verify_against_midgard(&state.db, &state.config, &loose).await.unwrap();
+    assert!(!report.discrepancies.iter().any(|discrepancy| discrepancy.field.as_deref() == Some("assetDepth")));
+}
+
+#[actix_web::test]
+async fn earnings_pools_are_compared_per_interval() {
+    let state = ingested_state().await;
+    let report = verify(&state, HistoryCollection::Earnings).await;
+    let keys: Vec<&str> = report.discrepancies.iter().map(|discrepancy| discrepancy.key.as_str()).collect();
+    // pools of every hour are found, only the fields we do not serve are missing
+    let first_pool = format!("{}/BTC.BTC", T0);
+    assert!(report
+        .discrepancies
+        .iter()
+        .filter(|discrepancy| discrepancy.key == first_pool)
+        .all(|discrepancy| discrepancy.kind == DiscrepancyKind::MissingField), "{:?}", keys);
+    assert!(!report.discrepancies.iter().any(|discrepancy| discrepancy.kind == DiscrepancyKind::MissingInterval && discrepancy.key.ends_with("/BTC.BTC") && !discrepancy.key.starts_with("meta")));
+}
+
+#[actix_web::test]
+async fn unreachable_midgard_is_a_failed_window() {
+    let mut state = ingested_state().await;
+    let mut config = state.config.as_ref().clone();
+    config.midgard.base_url = "http://127.0.0.1:9".to_string();
+    state.config = actix_web::web::Data::new(config);
+    let report = verify(&state, HistoryCollection::RunePool).await;
+    assert_eq!(report.discrepancies.len(), 1);
+    assert_eq!(report.discrepancies[0].kind, DiscrepancyKind::RequestFailed);
+}
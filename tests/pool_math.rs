@@ -0,0 +1,35 @@
+use tokenmetrics::utils::pool_math_utils::{get_swap_fee, get_swap_leg, get_swap_output, get_swap_slip};
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() <= expected.abs() * 1e-12, "{} != {}", actual, expected);
+}
+
+#[test]
+fn swap_output_fee_and_slip_follow_the_clp_formulas() {
+    // 1 in against 10 in the input depth and 20 in the output depth
+    let (x, input_depth, output_depth) = (1e8, 1e9, 2e9);
+    assert_close(get_swap_output(x, input_depth, output_depth), 1e8 * 1e9 * 2e9 / 1.1e9_f64.powi(2));
+    assert_close(get_swap_fee(x, input_depth, output_depth), 1e16 * 2e9 / 1.1e9_f64.powi(2));
+    assert_close(get_swap_slip(x, input_depth), 1.0 / 11.0);
+
+    // output and fee add up to the output of a swap without slip fee, x * Y / (x + X)
+    let leg = get_swap_leg(x, input_depth, output_depth);
+    assert_close(leg.output + leg.fee, x * output_depth / (x + input_depth));
+    assert_close(leg.slip, 1.0 / 11.0);
+}
+
+#[test]
+fn bigger_swaps_slip_more() {
+    let small = get_swap_leg(1e6, 1e9, 1e9);
+    let big = get_swap_leg(1e8, 1e9, 1e9);
+    assert!(big.slip > small.slip);
+    assert!(big.fee / big.output > small.fee / small.output);
+}
+
+#[test]
+fn empty_pools_give_nothing_instead_of_dividing_by_zero() {
+    assert_eq!(get_swap_output(0.0, 0.0, 1e9), 0.0);
+    assert_eq!(get_swap_fee(0.0, 0.0, 1e9), 0.0);
+    assert_eq!(get_swap_slip(0.0, 0.0), 0.0);
+    assert_eq!(get_swap_output(1e8, 1e9, 0.0), 0.0);
+}
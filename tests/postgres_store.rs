@@ -0,0 +1,43 @@
+mod common;
+
+use std::env;
+
+use common::*;
+use tokenmetrics::stores::{metrics_store::{HistoryCollection, MetricsStore}, postgres_store::PostgresStore};
+use tokio_postgres::{Client, NoTls};
+
+// runs against TEST_POSTGRES_URL, e.g. a local `docker run -e POSTGRES_HOST_AUTH_METHOD=trust -p 5432:5432 postgres`,
+// every run migrates a fresh schema and the url of the store points at it
+async fn postgres_store() -> (PostgresStore, String, Client) {
+    let url = env::var("TEST_POSTGRES_URL").expect("TEST_POSTGRES_URL is not set");
+    let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+    let (client, connection) = tokio_postgres::connect(&url, NoTls).await.unwrap();
+    tokio::spawn(connection);
+    client.batch_execute(&format!("CREATE SCHEMA {}", schema)).await.unwrap();
+    let separator = if url.contains('?') { '&' } else { '?' };
+    let schema_url = format!("{}{}options=-csearch_path%3D{}", url, separator, schema);
+    client.batch_execute(&format!("SET search_path TO {}", schema)).await.unwrap();
+    (PostgresStore::connect(&schema_url).await.unwrap(), schema_url, client)
+}
+
+#[actix_web::test]
+#[ignore = "needs TEST_POSTGRES_URL"]
+async fn postgres_history_matches_the_memory_store() {
+    let (postgres, _, _) = postgres_store().await;
+    assert_matches_memory_store(&postgres).await;
+}
+
+#[actix_web::test]
+#[ignore = "needs TEST_POSTGRES_URL"]
+async fn postgres_migrations_apply_once() {
+    let (postgres, schema_url, client) = postgres_store().await;
+    assert_eq!(postgres.latest_end_time(HistoryCollection::Swaps).await.unwrap(), None);
+    let applied: i64 = client.query_one("SELECT count(*) FROM schema_migrations", &[]).await.unwrap().get(0);
+    assert!(applied > 0);
+
+    // connecting to the same schema again finds the migrations recorded and applies none of them twice
+    assert!(PostgresStore::connect(&schema_url).await.is_ok());
+    assert!(PostgresStore::connect(&schema_url).await.is_ok());
+    let reapplied: i64 = client.query_one("SELECT count(*) FROM schema_migrations", &[]).await.unwrap().get(0);
+    assert_eq!(reapplied, applied);
+}
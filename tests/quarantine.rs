@@ -0,0 +1,133 @@
+mod common;
+
+use std::{fs, path::PathBuf, sync::Arc};
+
+use actix_web::{http::StatusCode, test};
+use common::*;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokenmetrics::{
+    build_app,
+    models::{depth_history_model::PoolDepthPriceHistory, earning_history_model::PoolEarningHistory, swap_history_model::SwapHistory},
+    services::validation_service::replay_quarantined,
+    stores::{memory_store::MemoryStore, metrics_store::{HistoryCollection, MetricsStore}},
+};
+
+// the intervals of a recorded midgard page, edited before they are parsed like the ingestion does
+fn page<T: DeserializeOwned>(name: &str, edit: impl FnOnce(&mut Vec<Value>)) -> Vec<T> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/midgard").join(format!("{}.json", name));
+    let page: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let mut intervals = page["intervals"].as_array().unwrap().clone();
+    edit(&mut intervals);
+    serde_json::from_value(Value::Array(intervals)).unwrap()
+}
+
+async fn stored_start_times(store: &dyn MetricsStore, collection: HistoryCollection) -> Vec<i64> {
+    let query = store.history_query(collection, history_params(Some("BTC.BTC"), "hour")).await.unwrap();
+    let mut start_times: Vec<i64> = store
+        .history_intervals(collection, &query)
+        .await
+        .unwrap()
+        .iter()
+        .map(|interval| interval.get_i64("startTime").unwrap())
+        .collect();
+    start_times.sort();
+    start_times
+}
+
+#[actix_web::test]
+async fn invalid_intervals_are_quarantined_and_the_rest_of_the_page_stored() {
+    let store = Arc::new(MemoryStore::default());
+    let state = app_state(store.clone()).await;
+    let intervals = page("depths_BTC.BTC", |intervals| {
+        intervals[0]["assetDepth"] = json!("abc");
+        intervals[2]["runeDepth"] = json!("-5");
+    });
+    let quarantined = PoolDepthPriceHistory::store_price_history(&state.db, "BTC.BTC", intervals).await.unwrap();
+    let reasons: Vec<&Vec<String>> = quarantined.iter().map(|record| &record.reasons).collect();
+    assert_eq!(reasons, [&vec!["assetDepth \"abc\" is not a number".to_string()], &vec!["runeDepth -5 is negative".to_string()]]);
+    assert_eq!(quarantined[0].interval["assetDepth"], "abc");
+    assert_eq!(quarantined[0].pool.as_deref(), Some("BTC.BTC"));
+    assert_eq!(stored_start_times(store.as_ref(), HistoryCollection::Depths).await, vec![T0 + HOUR]);
+    assert_eq!(store.quarantined(Some("depths"), 10).await.unwrap(), quarantined);
+
+    // times out of order, misaligned or reversed
+    let intervals = page("swaps_BTC.BTC", |intervals| {
+        intervals.swap(0, 1);
+        intervals[2]["startTime"] = json!((T0 + 2 * HOUR + 60).to_string());
+    });
+    let quarantined = SwapHistory::store_swap_history(&state.db, "BTC.BTC", intervals).await.unwrap();
+    let reasons: Vec<&Vec<String>> = quarantined.iter().map(|record| &record.reasons).collect();
+    assert_eq!(
+        reasons,
+        [
+            &vec![format!("startTime {} is before the endTime {} of the previous interval", T0, T0 + 2 * HOUR)],
+            &vec![format!("startTime {} is not hour aligned", T0 + 2 * HOUR + 60)],
+        ]
+    );
+    assert_eq!(stored_start_times(store.as_ref(), HistoryCollection::Swaps).await, vec![T0 + HOUR]);
+}
+
+#[actix_web::test]
+async fn earnings_pools_are_validated_with_their_interval() {
+    let store = Arc::new(MemoryStore::default());
+    let state = app_state(store.clone()).await;
+    let intervals = page("earnings", |intervals| {
+        intervals[1]["pools"][1]["rewards"] = json!("");
+        intervals[2]["endTime"] = intervals[2]["startTime"].clone();
+    });
+    let quarantined = PoolEarningHistory::store_earning_history(&state.db, intervals).await.unwrap();
+    let reasons: Vec<&Vec<String>> = quarantined.iter().map(|record| &record.reasons).collect();
+    assert_eq!(
+        reasons,
+        [
+            &vec!["pools[1].rewards \"\" is not a number".to_string()],
+            &vec![format!("startTime {} is not before endTime {}", T0 + 2 * HOUR, T0 + 2 * HOUR)],
+        ]
+    );
+    // neither the summary nor the valid pool of a rejected interval is stored
+    let query = store.history_query(HistoryCollection::Earnings, history_params(None, "hour")).await.unwrap();
+    let intervals = store.history_intervals(HistoryCollection::Earnings, &query).await.unwrap();
+    assert_eq!(intervals.len(), 2);
+    assert!(intervals.iter().all(|interval| interval.get_i64("interval_start").unwrap() < T0 + HOUR));
+}
+
+#[actix_web::test]
+async fn replay_stores_corrected_intervals_and_requarantines_the_rest() {
+    let store = Arc::new(MemoryStore::default());
+    let state = app_state(store.clone()).await;
+    let intervals = page("depths_BTC.BTC", |intervals| {
+        intervals[0]["assetDepth"] = json!("abc");
+        intervals[1]["units"] = json!("1.5");
+    });
+    let quarantined = PoolDepthPriceHistory::store_price_history(&state.db, "BTC.BTC", intervals).await.unwrap();
+    assert_eq!(quarantined[1].reasons, ["Invalid input: Failed to parse units \"1.5\" as i64"]);
+    let (bad, fraction) = (quarantined[0]._id.to_hex(), quarantined[1]._id.to_hex());
+
+    // replayed as received it fails again and is quarantined anew
+    let report = replay_quarantined(&state.db, &bad, None).await.unwrap().unwrap();
+    assert!(!report.stored);
+    let requarantined = report.quarantined.unwrap();
+    assert_eq!(requarantined.reasons, quarantined[0].reasons);
+    assert_ne!(requarantined.id, bad);
+
+    let mut corrected = quarantined[1].interval.clone();
+    corrected["units"] = json!("2");
+    let report = replay_quarantined(&state.db, &fraction, Some(corrected)).await.unwrap().unwrap();
+    assert!(report.stored && report.quarantined.is_none());
+    assert_eq!(stored_start_times(store.as_ref(), HistoryCollection::Depths).await, vec![T0 + HOUR, T0 + 2 * HOUR]);
+
+    let left: Vec<String> = store.quarantined(None, 10).await.unwrap().iter().map(|record| record._id.to_hex()).collect();
+    assert_eq!(left, [requarantined.id]);
+    assert!(replay_quarantined(&state.db, &fraction, None).await.unwrap().is_none());
+}
+
+#[actix_web::test]
+async fn quarantine_routes_need_an_admin_key() {
+    let state = app_state(Arc::new(MemoryStore::default())).await;
+    let app = test::init_service(build_app(&state)).await;
+    for req in [test::TestRequest::get().uri("/admin/quarantine"), test::TestRequest::post().uri("/admin/quarantine/0/replay")] {
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,70 @@
+use std::{thread, time::Duration};
+
+use tokenmetrics::middlewares::rate_limit_middleware::{default_route_groups, RateLimiter, RouteGroup};
+
+fn limiter() -> RateLimiter {
+    RateLimiter::new(vec![RouteGroup::new("history", &["/depths"], 2.0, 20.0, 1.0), RouteGroup::new("default", &[], 3.0, 1.0, 1.0)])
+}
+
+#[test]
+fn a_bucket_starts_full_and_refuses_past_its_capacity() {
+    let limiter = limiter();
+    let group = limiter.route_group("/depths/BTC.BTC").clone();
+    assert_eq!(limiter.try_acquire("ip:1", &group), Ok(1.0));
+    // the microseconds in between already refilled a little
+    assert_eq!(limiter.try_acquire("ip:1", &group).map(f64::floor), Ok(0.0));
+    // a whole token is missing at 20 per second, the retry is rounded up to a second
+    assert_eq!(limiter.try_acquire("ip:1", &group), Err(1));
+}
+
+#[test]
+fn tokens_refill_with_time_up_to_the_capacity() {
+    let limiter = limiter();
+    let group = limiter.route_group("/depths").clone();
+    while limiter.try_acquire("ip:1", &group).is_ok() {}
+    // 20 tokens per second for 200ms is more than the capacity of 2
+    thread::sleep(Duration::from_millis(200));
+    assert!(limiter.try_acquire("ip:1", &group).is_ok());
+    assert!(limiter.try_acquire("ip:1", &group).is_ok());
+    assert!(limiter.try_acquire("ip:1", &group).is_err());
+}
+
+#[test]
+fn retry_after_is_the_time_to_refill_the_cost() {
+    let limiter = RateLimiter::new(vec![RouteGroup::new("earnings", &["/earnings"], 10.0, 0.5, 10.0)]);
+    let group = limiter.route_group("/earnings").clone();
+    assert!(limiter.try_acquire("ip:1", &group).is_ok());
+    assert_eq!(limiter.try_acquire("ip:1", &group), Err(20));
+}
+
+#[test]
+fn buckets_are_per_client_and_group() {
+    let limiter = limiter();
+    let history = limiter.route_group("/depths").clone();
+    let default = limiter.route_group("/tools/swap-quote").clone();
+    assert_eq!(default.name, "default");
+    limiter.try_acquire("ip:1", &history).unwrap();
+    limiter.try_acquire("ip:1", &history).unwrap();
+    assert!(limiter.try_acquire("ip:1", &history).is_err());
+    assert_eq!(limiter.try_acquire("ip:2", &history), Ok(1.0));
+    assert_eq!(limiter.try_acquire("ip:1", &default), Ok(2.0));
+}
+
+#[test]
+fn prefixes_match_whole_path_segments() {
+    let limiter = RateLimiter::new(default_route_groups());
+    assert_eq!(limiter.route_group("/earnings").name, "earnings");
+    assert_eq!(limiter.route_group("/earnings/summary").name, "earnings");
+    assert_eq!(limiter.route_group("/earningsx").name, "default");
+    assert_eq!(limiter.route_group("/swaps/BTC.BTC").name, "history");
+    assert_eq!(limiter.route_group("/health").name, "default");
+}
+
+#[test]
+fn midgard_routes_share_the_groups_of_their_history() {
+    let limiter = RateLimiter::new(default_route_groups());
+    assert_eq!(limiter.route_group("/v2/history/earnings").name, "earnings");
+    assert_eq!(limiter.route_group("/v2/history/depths/BTC.BTC").name, "history");
+    assert_eq!(limiter.route_group("/v2/history/swaps").name, "history");
+    assert_eq!(limiter.route_group("/v2/history/runepool").name, "runepool");
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::{env, fs, sync::Arc};
+
+use actix_web::{http::StatusCode, test};
+use common::*;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokenmetrics::{
+    build_app,
+    config::Config,
+    models::{depth_history_model::PoolDepthPriceHistory, rune_pool_model::RunePool},
+    services::{
+        db::DataBase,
+        maintenance_service::{export_collection, find_gaps, reindex, verify_collection, ExportFormat},
+    },
+    stores::{metrics_store::{HistoryCollection, MetricsStore}, sqlite_store::SqliteStore},
+    AppState,
+};
+
+#[actix_web::test]
+async fn sqlite_history_matches_the_memory_store() {
+    let sqlite = SqliteStore::open("sqlite://:memory:").unwrap();
+    assert_matches_memory_store(&sqlite).await;
+}
+
+#[actix_web::test]
+async fn sqlite_file_keeps_the_history_across_opens() {
+    let path = env::temp_dir().join(format!("tokenmetrics-{}.db", uuid::Uuid::new_v4().simple()));
+    let url = format!("sqlite://{}", path.display());
+    SqliteStore::open(&url).unwrap().insert_rune_pool(&[rune_pool(T0, 1, 10)]).await.unwrap();
+    // reopening finds the migration recorded and the stored records
+    let reopened = SqliteStore::open(&url).unwrap();
+    assert_eq!(reopened.latest_end_time(HistoryCollection::RunePool).await.unwrap(), Some(T0 + HOUR));
+    drop(reopened);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+#[actix_web::test]
+async fn sqlite_store_serves_the_history_routes() {
+    let sqlite = Arc::new(SqliteStore::open("sqlite://:memory:").unwrap());
+    seed_history(sqlite.as_ref()).await;
+    let state = app_state(sqlite).await;
+    let app = test::init_service(build_app(&state)).await;
+    let uri = format!("/depths?pool=BTC.BTC&from={}&to={}", T0, T0 + 3 * HOUR);
+    let res = test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(res).await;
+    assert_eq!(body["intervals"].as_array().unwrap().len(), 2);
+    // the replaced record of the second hour wins
+    assert_eq!(body["intervals"][0]["assetDepth"], 115.0);
+}
+
+#[actix_web::test]
+async fn db_url_selects_an_embedded_sqlite_history() {
+    let mut config = Config::default();
+    config.database.set_url("sqlite://tokenmetrics.db");
+    assert!(config.database.is_embedded());
+    assert!(config.validate().is_ok());
+
+    config.database.set_url("mysql://localhost");
+    assert!(config.validate().is_err());
+}
+
+#[actix_web::test]
+async fn sqlite_history_runs_without_mongodb() {
+    let mut config = Config::default();
+    config.database.set_url("sqlite://:memory:");
+    let db = DataBase::init(&config.database).await.unwrap();
+    assert!(db.mongodb.is_none());
+    seed_history(db.store.as_ref()).await;
+
+    // the maintenance commands read the sqlite history
+    let gaps: Vec<(i64, i64)> = find_gaps(&db, "depths", Some("BTC.BTC"), None, None)
+        .await
+        .unwrap()
+        .iter()
+        .map(|gap| (gap.after_end_time, gap.missing_intervals))
+        .collect();
+    assert_eq!(gaps, [(T0 + 2 * HOUR, 28)]);
+    let report = verify_collection(&db, "depths").await.unwrap();
+    assert_eq!((report.records, report.malformed, report.gaps), (5, 0, 1));
+    assert_eq!(report.duplicates, [(Some("BTC.BTC".to_string()), T0 + 2 * HOUR, 2)]);
+    let mut out = Vec::new();
+    assert_eq!(export_collection(&db, "runepool", None, None, None, ExportFormat::Csv, &mut out).await.unwrap(), 2);
+    let csv = String::from_utf8(out).unwrap();
+    assert!(!csv.lines().next().unwrap().contains("_id"), "{}", csv);
+    assert_eq!(reindex(&db).await.unwrap(), Vec::<String>::new());
+
+    // keys and usage live in mongodb, a key is then only rate limited by address and keyed routes explain why they fail
+    let state = AppState::new(config, db);
+    let app = test::init_service(build_app(&state)).await;
+    for uri in ["/ready".to_string(), format!("/runepool?from={}&to={}", T0, T0 + 2 * HOUR)] {
+        let req = test::TestRequest::get().uri(&uri).insert_header(("X-API-Key", "tm_unknown")).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK, "{}", uri);
+    }
+    let req = test::TestRequest::get().uri("/usage").insert_header(("X-API-Key", "tm_unknown")).to_request();
+    let body: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(body.to_string().contains("MongoDB is not configured, set database.uri"), "{}", body);
+}
+
+// the intervals of a midgard fixture page as the ingestion parses them
+fn fixture_intervals<T: DeserializeOwned>(name: &str) -> Vec<T> {
+    let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/midgard").join(format!("{}.json", name));
+    let page: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    serde_json::from_value(page["intervals"].clone()).unwrap()
+}
+
+#[actix_web::test]
+async fn a_failed_insert_fails_the_stored_page() {
+    let path = env::temp_dir().join(format!("tokenmetrics-{}.db", uuid::Uuid::new_v4().simple()));
+    let url = format!("sqlite://{}", path.display());
+    let store = Arc::new(SqliteStore::open(&url).unwrap());
+    // every insert into the history tables is rejected from now on
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute_batch(
+            "CREATE TRIGGER reject_depths BEFORE INSERT ON depth_history BEGIN SELECT RAISE(ABORT, 'rejected'); END;
+             CREATE TRIGGER reject_rune_pool BEFORE INSERT ON rune_pool_history BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+        )
+        .unwrap();
+    let state = app_state(store.clone()).await;
+    // the page is fetched again instead of being skipped as stored
+    assert!(PoolDepthPriceHistory::store_price_history(&state.db, "BTC.BTC", fixture_intervals("depths_BTC.BTC")).await.is_err());
+    assert!(RunePool::store_rune_pool(&state.db, fixture_intervals("runepool")).await.is_err());
+    assert_eq!(store.latest_end_time(HistoryCollection::Depths).await.unwrap(), None);
+    drop(state);
+    drop(store);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
@@ -0,0 +1,42 @@
+use tokenmetrics::utils::stats_utils::{mad, mean, median, robust_z_score, std_dev};
+
+fn assert_close(actual: Option<f64>, expected: f64) {
+    let actual = actual.unwrap();
+    assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+}
+
+#[test]
+fn median_and_mad_ignore_a_spike() {
+    let values = [1.0, 2.0, 3.0, 4.0, 100.0];
+    assert_close(median(&values), 3.0);
+    assert_close(median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+    // deviations 2, 1, 0, 1, 97
+    assert_close(mad(&values, 3.0), 1.0);
+    assert_eq!(median(&[]), None);
+    assert_eq!(mad(&[], 0.0), None);
+}
+
+#[test]
+fn std_dev_is_the_population_deviation() {
+    let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+    assert_close(mean(&values), 5.0);
+    assert_close(std_dev(&values, 5.0), 2.0);
+    assert_eq!(mean(&[]), None);
+    assert_eq!(std_dev(&[], 0.0), None);
+}
+
+#[test]
+fn robust_z_score_scales_the_distance_from_the_median_by_the_mad() {
+    let baseline = [1.0, 2.0, 3.0, 4.0, 100.0];
+    assert_close(robust_z_score(13.0, &baseline), 0.6745 * 10.0);
+    assert_close(robust_z_score(3.0, &baseline), 0.0);
+}
+
+#[test]
+fn robust_z_score_falls_back_to_the_classic_z_score_on_flat_baselines() {
+    // most hours without volume give a MAD of 0
+    let baseline = [0.0, 0.0, 0.0, 0.0, 10.0];
+    assert_close(robust_z_score(10.0, &baseline), (10.0 - 2.0) / 4.0);
+    assert_eq!(robust_z_score(5.0, &[1.0, 1.0, 1.0]), None);
+    assert_eq!(robust_z_score(5.0, &[]), None);
+}
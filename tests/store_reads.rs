@@ -0,0 +1,217 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, test};
+use common::*;
+use serde_json::{json, Value};
+use tokenmetrics::{
+    build_app,
+    config::Config,
+    models::stream_event_model::{parse_topics, StreamCursor, Subscription},
+    stores::{
+        memory_store::MemoryStore,
+        metrics_store::{for_each_record_page, HistoryCollection, MetricsStore, RecordQuery},
+        sqlite_store::SqliteStore,
+    },
+};
+
+async fn call(store: Arc<dyn MetricsStore>, request: test::TestRequest) -> (StatusCode, Value) {
+    let config = Config { pools: vec!["BTC.BTC".to_string(), "ETH.ETH".to_string()], ..Config::default() };
+    let state = app_state_with_config(config, store).await;
+    let app = test::init_service(build_app(&state)).await;
+    let res = test::call_service(&app, request.to_request()).await;
+    let status = res.status();
+    let body = test::read_body(res).await;
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+async fn get(store: Arc<dyn MetricsStore>, uri: &str) -> (StatusCode, Value) {
+    call(store, test::TestRequest::get().uri(uri)).await
+}
+
+// the history in sqlite, the routes below used to read it from mongodb only
+async fn sqlite_history() -> Arc<dyn MetricsStore> {
+    let store = SqliteStore::open("sqlite://:memory:").unwrap();
+    seed_history(&store).await;
+    Arc::new(store)
+}
+
+#[actix_web::test]
+async fn candles_are_computed_from_the_store() {
+    let (status, body) = get(sqlite_history().await, &format!("/candles?pool=BTC.BTC&interval=day&from={}", T0)).await;
+    assert_eq!(status, StatusCode::OK);
+    // the replaced T0 + HOUR record (11.0) comes before its replacement (11.5) and both are in the first day
+    assert_eq!(body["candles"], json!([
+        { "time": T0, "startTime": T0, "endTime": T0 + 86_400, "open": 20.0, "high": 23.0, "low": 20.0, "close": 23.0 },
+        { "time": T0 + 86_400, "startTime": T0 + 86_400, "endTime": T0 + 2 * 86_400, "open": 24.0, "high": 24.0, "low": 24.0, "close": 24.0 },
+    ]));
+
+    let (status, body) = get(sqlite_history().await, &format!("/candles?pool=THOR.RUNE&from={}&count=2", T0)).await;
+    assert_eq!(status, StatusCode::OK);
+    let closes: Vec<f64> = body["candles"].as_array().unwrap().iter().map(|candle| candle["close"].as_f64().unwrap()).collect();
+    assert_eq!(closes, [1.0, 2.0]);
+}
+
+#[actix_web::test]
+async fn indicators_sum_flows_and_keep_the_last_level() {
+    let uri = format!("/indicators?metric=totalVolumeUSD&pool=BTC.BTC&interval=day&indicators=sma:2&from={}", T0);
+    let (status, body) = get(sqlite_history().await, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["intervals"], json!([{ "startTime": T0, "endTime": T0 + 86_400, "value": 6.0, "sma2": null }]));
+
+    let uri = format!("/indicators?metric=assetDepth&pool=BTC.BTC&indicators=sma:2&from={}&to={}", T0, T0 + 2 * HOUR);
+    let (status, body) = get(sqlite_history().await, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let points: Vec<(f64, Option<f64>)> = body["intervals"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|point| (point["value"].as_f64().unwrap(), point["sma2"].as_f64()))
+        .collect();
+    assert_eq!(points, [(100.0, None), (115.0, Some(107.5))]);
+}
+
+#[actix_web::test]
+async fn swap_quotes_use_the_latest_stored_depth() {
+    let uri = format!("/tools/swap-quote?from=THOR.RUNE&to=BTC.BTC&amount=100&at={}", T0 + 2 * HOUR);
+    let (status, body) = get(sqlite_history().await, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    // the replacement record of T0 + HOUR with 115 asset and 1322 rune depth
+    assert_eq!(body["depthEndTime"], json!(T0 + 2 * HOUR));
+    let expected = 100.0 * 1322.0 * 115.0 / (1422.0_f64 * 1422.0);
+    assert!((body["expectedOutput"].as_f64().unwrap() - expected).abs() < 1e-9, "{}", body);
+    assert_eq!(body["observedAverageSlipBps"], json!(2.0));
+
+    let (status, _) = get(sqlite_history().await, &format!("/tools/swap-quote?from=THOR.RUNE&to=BTC.BTC&amount=100&at={}", T0)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn data_freshness_reads_the_configured_pools_from_the_store() {
+    let (status, body) = get(sqlite_history().await, "/health/data").await;
+    // the seeded history is from 2024
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let latest: Vec<(String, Value, i64)> = body["collections"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|freshness| (freshness["collection"].as_str().unwrap().to_string(), freshness["pool"].clone(), freshness["latestEndTime"].as_i64().unwrap()))
+        .collect();
+    assert_eq!(latest, [
+        ("depth_history".to_string(), json!("BTC.BTC"), T0 + 31 * HOUR),
+        ("depth_history".to_string(), json!("ETH.ETH"), T0 + HOUR),
+        ("swap_history".to_string(), json!("BTC.BTC"), T0 + 3 * HOUR),
+        ("earnings".to_string(), json!("BTC.BTC"), T0 + 2 * HOUR),
+        ("earnings".to_string(), json!("ETH.ETH"), T0 + 2 * HOUR),
+        ("rune_pool_history".to_string(), Value::Null, T0 + 2 * HOUR),
+    ]);
+    assert_eq!(body["emptyCollections"], json!([]));
+}
+
+#[actix_web::test]
+async fn stream_replays_come_from_the_store_and_end_with_a_gap_past_the_limit() {
+    let store = Arc::new(MemoryStore::default());
+    let records: Vec<_> = (0..450).map(|hour| depth("BTC.BTC", T0 + hour * HOUR, 100 + hour, 10.0)).collect();
+    store.insert_depths(&records).await.unwrap();
+    let state = app_state(store).await;
+    let subscriptions = [Subscription { topic: "depths".to_string(), pool: Some("BTC.BTC".to_string()) }];
+
+    let backlog = state.db.get_stream_backlog_api(&subscriptions, &StreamCursor::parse(&(T0 + 40 * HOUR).to_string()).unwrap()).await.unwrap();
+    assert_eq!(backlog.len(), 401);
+    assert_eq!(backlog[0].end_time, T0 + 41 * HOUR);
+    assert_eq!(backlog[0].data["asset_depth"], json!(140));
+    let gap = backlog.last().unwrap();
+    assert!(gap.is_gap());
+    assert_eq!(gap.id, format!("{}:depths:BTC.BTC", T0 + 440 * HOUR));
+
+    // resuming from the gap replays the rest without one
+    let rest = state.db.get_stream_backlog_api(&subscriptions, &StreamCursor::parse(&gap.id).unwrap()).await.unwrap();
+    assert_eq!(rest.len(), 10);
+    assert!(!rest.iter().any(|event| event.is_gap()));
+    assert_eq!(rest.last().unwrap().end_time, T0 + 450 * HOUR);
+}
+
+#[actix_web::test]
+async fn stream_resumes_after_the_event_id_within_its_hour() {
+    let store = Arc::new(MemoryStore::default());
+    store.insert_depths(&[depth("BTC.BTC", T0, 100, 10.0), depth("ETH.ETH", T0, 200, 20.0)]).await.unwrap();
+    store.insert_swaps(&[swap("BTC.BTC", T0, 1.0)]).await.unwrap();
+    let state = app_state(store).await;
+    let subscriptions = parse_topics("depths,swaps").unwrap();
+
+    let backlog = state.db.get_stream_backlog_api(&subscriptions, &StreamCursor::parse(&T0.to_string()).unwrap()).await.unwrap();
+    let ids: Vec<&str> = backlog.iter().map(|event| event.id.as_str()).collect();
+    let end_time = T0 + HOUR;
+    assert_eq!(ids, [format!("{}:depths:BTC.BTC", end_time), format!("{}:depths:ETH.ETH", end_time), format!("{}:swaps:BTC.BTC", end_time)]);
+
+    // a disconnect after the first event of the hour still gets the other topics of that hour
+    let rest = state.db.get_stream_backlog_api(&subscriptions, &StreamCursor::parse(ids[0]).unwrap()).await.unwrap();
+    let rest: Vec<&str> = rest.iter().map(|event| event.id.as_str()).collect();
+    assert_eq!(rest, &ids[1..]);
+    assert!(StreamCursor::parse("soon").is_err());
+}
+
+#[actix_web::test]
+async fn a_truncated_sse_replay_ends_the_response_at_the_gap() {
+    let store = Arc::new(MemoryStore::default());
+    let records: Vec<_> = (0..450).map(|hour| depth("BTC.BTC", T0 + hour * HOUR, 100 + hour, 10.0)).collect();
+    store.insert_depths(&records).await.unwrap();
+    let state = app_state(store).await;
+    let app = test::init_service(build_app(&state)).await;
+    let request = test::TestRequest::get()
+        .uri(&format!("/stream?topics=depths:BTC.BTC&since={}", T0))
+        .insert_header(("Last-Event-ID", format!("{}:depths:BTC.BTC", T0 + 40 * HOUR)));
+    let res = test::call_service(&app, request.to_request()).await;
+    assert_eq!(res.headers().get("X-Replay-Truncated").unwrap(), "true");
+    // the body completes instead of waiting for live events
+    let body = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+    let ids: Vec<&str> = body.lines().filter_map(|line| line.strip_prefix("id: ")).collect();
+    // the event of the Last-Event-ID is fetched again and skipped, the cut hour is left for the reconnect
+    assert_eq!(ids.len(), 400);
+    assert_eq!(ids[0], format!("{}:depths:BTC.BTC", T0 + 41 * HOUR));
+    let resume = format!("{}:depths:BTC.BTC", T0 + 439 * HOUR);
+    assert_eq!(ids[398..], [resume.as_str(), resume.as_str()]);
+    assert!(body.trim_end().ends_with(&format!("{{\"id\":\"{}\",\"topic\":\"gap\",\"pool\":null,\"endTime\":{},\"data\":{{\"replayLimit\":400}}}}", resume, T0 + 439 * HOUR)));
+}
+
+#[actix_web::test]
+async fn graphql_refuses_a_history_outside_mongodb() {
+    let request = test::TestRequest::post().uri("/graphql").set_json(json!({ "query": "{ runePool { count } }" }));
+    let (status, body) = call(sqlite_history().await, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["errors"][0]["message"].as_str().unwrap().contains("database.history_uri"), "{}", body);
+}
+
+#[actix_web::test]
+async fn record_pages_end_with_whole_end_times() {
+    let store = SqliteStore::open("sqlite://:memory:").unwrap();
+    let depths: Vec<_> = (0..3_334)
+        .flat_map(|hour| ["BTC.BTC", "ETH.ETH", "LTC.LTC"].map(|pool| depth(pool, T0 + hour * HOUR, 100, 1.0)))
+        .collect();
+    store.insert_depths(&depths).await.unwrap();
+    let mut pages = Vec::new();
+    for_each_record_page(&store, HistoryCollection::Depths, RecordQuery::default(), |page| pages.push(page)).await.unwrap();
+    // the first page is cut before the last hour it only holds part of
+    assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [9_999, 3]);
+    assert_eq!(pages[1][0].get_i64("start_time").unwrap(), T0 + 3_333 * HOUR);
+}
+
+#[actix_web::test]
+async fn records_of_one_end_time_are_ordered_by_pool() {
+    let stores: [Arc<dyn MetricsStore>; 2] = [Arc::new(MemoryStore::default()), Arc::new(SqliteStore::open("sqlite://:memory:").unwrap())];
+    for store in stores {
+        store.insert_depths(&[depth("ETH.ETH", T0, 1, 1.0), depth("BTC.BTC", T0, 2, 1.0), depth("BTC.BTC", T0, 3, 1.0)]).await.unwrap();
+        for descending in [false, true] {
+            let query = RecordQuery { descending, limit: 10, ..RecordQuery::default() };
+            let depths: Vec<i64> = store
+                .records(HistoryCollection::Depths, &query)
+                .await
+                .unwrap()
+                .iter()
+                .map(|record| record.get_i64("asset_depth").unwrap())
+                .collect();
+            assert_eq!(depths, if descending { [1, 3, 2] } else { [2, 3, 1] });
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use tokenmetrics::services::alert_service::sign_payload;
+
+const BODY: &str = r#"{"rule":"r1","value":2.5}"#;
+
+#[test]
+fn signs_the_timestamp_and_body_with_hmac_sha256() {
+    // printf '%s' '1700000000.{"rule":"r1","value":2.5}' | openssl dgst -sha256 -hmac s3cret
+    assert_eq!(sign_payload("s3cret", 1_700_000_000, BODY), "0702e5085b9d69952cdc886085bf90cc1078b632a88d6e03ec0b733b6ce8ad64");
+}
+
+#[test]
+fn the_signature_changes_with_the_secret_timestamp_and_body() {
+    let signature = sign_payload("s3cret", 1_700_000_000, BODY);
+    assert_ne!(sign_payload("other", 1_700_000_000, BODY), signature);
+    // a replayed body with a fresh timestamp does not carry over the old signature
+    assert_ne!(sign_payload("s3cret", 1_700_000_001, BODY), signature);
+    assert_ne!(sign_payload("s3cret", 1_700_000_000, r#"{"rule":"r1","value":3.5}"#), signature);
+    assert_eq!(sign_payload("", 0, "").len(), 64);
+}